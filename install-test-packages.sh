@@ -2,13 +2,14 @@
 
 set -e;
 
-DIRS=$(find tests -name 'package.json');
+DIRS=$(find tests/mock-packages -name 'package.json');
 
 for pkg in $DIRS; do
   file=$(dirname "$pkg");
-  if test -d "$file"; then
-    name=$(basename "$file");
+  # Nested package.json files without a name are not packages
+  name=$(node -p "require('./$pkg').name || ''");
+  if test -n "$name"; then
     (cd "$file" && yarn link)
-    yarn link $name
+    yarn link "$name"
   fi
 done
@@ -1,6 +1,48 @@
 import test from 'ava'
-import { run } from '../index'
+import { run, generatePolicy, moduleMeta, computeGlobals } from '../index'
+
+const fixtures = '../tests/fixtures'
 
 test('call run from native code', (t) => {
   t.is(run(['basalt', '--version']), undefined)
 })
+
+test('run throws on invalid argv', (t) => {
+  t.throws(() => run(1 as any), { message: /expected an array of strings or an options object/ })
+  t.throws(() => run(['basalt', 1] as any), { message: /argv\[1\] must be a string/ })
+})
+
+test('run throws on command error', (t) => {
+  t.throws(() => run(['basalt', 'meta', `${fixtures}/missing.js`]), { message: /does not exist/ })
+})
+
+test('generate policy', (t) => {
+  const policy = generatePolicy(`${fixtures}/basic-tree/main.js`)
+  t.deepEqual(policy, { resources: {} })
+})
+
+test('module meta', (t) => {
+  const meta = moduleMeta(`${fixtures}/annotate/main.js`)
+  t.deepEqual(Object.keys(meta.imports), ['./util.js'])
+})
+
+test('compute globals', (t) => {
+  const globals = computeGlobals(`${fixtures}/annotate/main.js`)
+  t.deepEqual(globals.sort(), ['document', 'location'])
+})
+
+test('run with options', (t) => {
+  t.deepEqual(run({ command: 'policy', entry: `${fixtures}/basic-tree/main.js` }), { resources: {} })
+  t.deepEqual(run({ command: 'globals', entry: `${fixtures}/annotate/main.js` }).sort(), ['document', 'location'])
+})
+
+test('run with invalid options', (t) => {
+  t.throws(() => run({ entry: `${fixtures}/basic-tree/main.js` } as any), { message: /missing required field command/ })
+  t.throws(() => run({ command: 'policy' } as any), { message: /missing required field entry/ })
+  t.throws(() => run({ command: 'lint', entry: `${fixtures}/basic-tree/main.js` } as any), {
+    message: /unknown command lint/,
+  })
+  t.throws(() => run({ command: 'check', entry: `${fixtures}/basic-tree/main.js`, policy: [1] } as any), {
+    message: /policy\[0\] must be a string/,
+  })
+})
@@ -1 +1,12 @@
-export const run: (argv: string[]) => void
+export interface RunOptions {
+  command: 'policy' | 'bundle' | 'check' | 'meta' | 'globals'
+  entry: string
+  policy?: string | string[]
+  out?: string
+}
+
+export function run(argv: string[]): void
+export function run(options: RunOptions): any
+export const generatePolicy: (entry: string) => Record<string, any>
+export const moduleMeta: (path: string) => Record<string, any>
+export const computeGlobals: (path: string) => string[]
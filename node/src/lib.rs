@@ -1,32 +1,248 @@
 #[macro_use]
 extern crate napi_derive;
 
-use napi::{CallContext, JsObject, JsString, JsUndefined, Result};
+use napi::{
+    CallContext, Env, JsObject, JsString, JsUnknown, Result, Status, ValueType,
+};
+use serde::Serialize;
 use std::ffi::OsString;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use basalt::{
+    json,
+    policy::{
+        analysis::globals_scope::{analyze_globals, GlobalOptions},
+        builder::PolicyBuilder,
+        Policy,
+    },
+    static_module_record::{Parser, StaticModuleRecordMeta},
+    swc_utils::load_file,
+};
+
+/// Commands accepted in the options object for `run()`.
+const COMMANDS: &[&str] = &["policy", "bundle", "check", "meta", "globals"];
 
 #[module_exports]
 fn init(mut exports: JsObject) -> Result<()> {
     exports.create_named_method("run", run)?;
+    exports.create_named_method("generatePolicy", generate_policy)?;
+    exports.create_named_method("moduleMeta", module_meta)?;
+    exports.create_named_method("computeGlobals", compute_globals)?;
     Ok(())
 }
 
-#[js_function(1)]
-fn run(ctx: CallContext) -> Result<JsUndefined> {
-    let mut argv: Vec<OsString> = Vec::new();
-    let args = ctx.get::<JsObject>(0)?;
-    if args.is_array()? {
-        for i in 0..args.get_array_length_unchecked()? {
-            let arg =
-                args.get_element::<JsString>(i)?.into_utf8()?.into_owned()?;
-            argv.push(OsString::from(arg));
+/// Convert an error from the library to an error for javascript.
+fn to_js_error<E: Display>(e: E) -> napi::Error {
+    napi::Error::from_reason(format!("{:#}", e))
+}
+
+/// Error for an invalid argument.
+fn invalid_arg(reason: String) -> napi::Error {
+    napi::Error::new(Status::InvalidArg, reason)
+}
+
+/// Get the path argument at an index.
+fn get_path(ctx: &CallContext, index: usize) -> Result<PathBuf> {
+    let path = ctx.get::<JsString>(index)?.into_utf8()?.into_owned()?;
+    Ok(PathBuf::from(path))
+}
+
+/// Convert a serializable value to a javascript object.
+fn to_js_object<T: Serialize>(env: &Env, value: &T) -> Result<JsObject> {
+    env.to_js_value(value)?.coerce_to_object()
+}
+
+/// Get a string from a javascript value, `null` and `undefined`
+/// are treated as a missing value.
+fn as_string(value: JsUnknown, name: &str) -> Result<Option<String>> {
+    match value.get_type()? {
+        ValueType::Undefined | ValueType::Null => Ok(None),
+        ValueType::String => {
+            let value = unsafe { value.cast::<JsString>() };
+            Ok(Some(value.into_utf8()?.into_owned()?))
         }
+        _ => Err(invalid_arg(format!("run(): {} must be a string", name))),
+    }
+}
 
-        if let Err(e) = basalt::cli::run::<OsString>(Some(argv)) {
-            panic!("{}", e);
+/// Get an optional string field from the options object.
+fn get_string(options: &JsObject, name: &str) -> Result<Option<String>> {
+    as_string(options.get_named_property::<JsUnknown>(name)?, name)
+}
+
+/// Get a required string field from the options object.
+fn require_string(options: &JsObject, name: &str) -> Result<String> {
+    get_string(options, name)?.ok_or_else(|| {
+        invalid_arg(format!("run(): missing required field {}", name))
+    })
+}
+
+/// Get a field from the options object that is either a string
+/// or an array of strings.
+fn get_strings(options: &JsObject, name: &str) -> Result<Vec<String>> {
+    let value = options.get_named_property::<JsUnknown>(name)?;
+    if value.get_type()? == ValueType::Object {
+        let array = unsafe { value.cast::<JsObject>() };
+        if !array.is_array()? {
+            return Err(invalid_arg(format!(
+                "run(): {} must be a string or an array of strings",
+                name
+            )));
         }
+        let mut result = Vec::new();
+        for i in 0..array.get_array_length_unchecked()? {
+            let name = format!("{}[{}]", name, i);
+            let item = array.get_element::<JsUnknown>(i)?;
+            match as_string(item, &name)? {
+                Some(item) => result.push(item),
+                None => {
+                    return Err(invalid_arg(format!(
+                        "run(): {} must be a string",
+                        name
+                    )))
+                }
+            }
+        }
+        Ok(result)
     } else {
-        panic!("run(): argv must be an array of strings");
+        Ok(as_string(value, name)?.into_iter().collect())
     }
+}
+
+/// Write a value as JSON to a file.
+fn write_json<T: Serialize>(out: &Path, value: &T) -> Result<()> {
+    let contents = json::to_string_pretty(value, Default::default())
+        .map_err(to_js_error)?;
+    std::fs::write(out, contents).map_err(to_js_error)
+}
+
+/// Generate the policy for an entry point.
+fn build_policy(entry: PathBuf) -> Result<Policy> {
+    Ok(PolicyBuilder::new(entry)
+        .load()
+        .and_then(|builder| builder.analyze())
+        .map_err(to_js_error)?
+        .finalize())
+}
+
+/// Compute the static module record meta data for a module.
+fn build_meta(file: &Path) -> Result<StaticModuleRecordMeta> {
+    Parser::parse_file(file).map_err(to_js_error)
+}
+
+/// Compute the globals used by a module.
+fn build_globals(file: &Path) -> Result<Vec<String>> {
+    let (_, _, module) = load_file(file, None).map_err(to_js_error)?;
+    Ok(analyze_globals(&module, GlobalOptions::default())
+        .iter()
+        .map(|name| name.to_string())
+        .collect())
+}
 
-    ctx.env.get_undefined()
+/// Run a command.
+///
+/// Accepts either an array of strings that is parsed as the
+/// command line arguments or an options object that calls the
+/// library functions directly.
+#[js_function(1)]
+fn run(ctx: CallContext) -> Result<JsUnknown> {
+    let args = ctx.get::<JsUnknown>(0)?;
+    if args.get_type()? != ValueType::Object {
+        return Err(invalid_arg(String::from(
+            "run(): expected an array of strings or an options object",
+        )));
+    }
+    let args = unsafe { args.cast::<JsObject>() };
+    if args.is_array()? {
+        run_argv(&args)?;
+        Ok(ctx.env.get_undefined()?.into_unknown())
+    } else {
+        run_options(ctx.env, &args)
+    }
+}
+
+/// Run the command line interface for an array of arguments.
+fn run_argv(args: &JsObject) -> Result<()> {
+    let mut argv: Vec<OsString> = Vec::new();
+    for i in 0..args.get_array_length_unchecked()? {
+        let arg = args
+            .get_element::<JsString>(i)
+            .and_then(|arg| arg.into_utf8()?.into_owned())
+            .map_err(|_| {
+                invalid_arg(format!("run(): argv[{}] must be a string", i))
+            })?;
+        argv.push(OsString::from(arg));
+    }
+
+    basalt::cli::run::<OsString>(Some(argv)).map_err(to_js_error)
+}
+
+/// Run a command for an options object.
+///
+/// The options object has the shape `{ command, entry, policy, out }`;
+/// when `out` is given the result is also written to the file as JSON
+/// except for the `bundle` command which writes the bundle to the file.
+fn run_options(env: &Env, options: &JsObject) -> Result<JsUnknown> {
+    let command = require_string(options, "command")?;
+    if !COMMANDS.contains(&command.as_str()) {
+        return Err(invalid_arg(format!(
+            "run(): unknown command {}, expected one of: {}",
+            command,
+            COMMANDS.join(", ")
+        )));
+    }
+
+    let entry = PathBuf::from(require_string(options, "entry")?);
+    let policy: Vec<PathBuf> = get_strings(options, "policy")?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    let out = get_string(options, "out")?.map(PathBuf::from);
+
+    let result = match command.as_str() {
+        "bundle" => {
+            basalt::bundle(basalt::BundleArgs {
+                modules: vec![entry],
+                policy,
+                output: out,
+                ..Default::default()
+            })
+            .map_err(to_js_error)?;
+            return Ok(env.get_undefined()?.into_unknown());
+        }
+        "policy" => serde_json::to_value(build_policy(entry)?),
+        "check" => serde_json::to_value(
+            basalt::check(entry, policy).map_err(to_js_error)?,
+        ),
+        "meta" => serde_json::to_value(build_meta(&entry)?),
+        _ => serde_json::to_value(build_globals(&entry)?),
+    }
+    .map_err(to_js_error)?;
+
+    if let Some(out) = out {
+        write_json(&out, &result)?;
+    }
+    env.to_js_value(&result)
+}
+
+/// Generate the policy for an entry point.
+#[js_function(1)]
+fn generate_policy(ctx: CallContext) -> Result<JsObject> {
+    let policy = build_policy(get_path(&ctx, 0)?)?;
+    to_js_object(ctx.env, &policy)
+}
+
+/// Compute the static module record meta data for a module.
+#[js_function(1)]
+fn module_meta(ctx: CallContext) -> Result<JsObject> {
+    let meta = build_meta(&get_path(&ctx, 0)?)?;
+    to_js_object(ctx.env, &meta)
+}
+
+/// Compute the globals used by a module.
+#[js_function(1)]
+fn compute_globals(ctx: CallContext) -> Result<JsObject> {
+    let globals = build_globals(&get_path(&ctx, 0)?)?;
+    to_js_object(ctx.env, &globals)
 }
@@ -1,7 +1,13 @@
 //! Symbol access flags.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
 
 /// Represents the access control to a code symbol.
-#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(
+    Debug, Default, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize,
+)]
+#[serde(default)]
 pub struct Access {
     /// Read access to the symbol.
     pub read: bool,
@@ -12,6 +18,64 @@ pub struct Access {
 }
 
 impl Access {
+    /// Create an access without any flags set.
+    pub fn none() -> Self {
+        Default::default()
+    }
+
+    /// Create an access with all flags set.
+    pub fn all() -> Self {
+        Self {
+            read: true,
+            write: true,
+            execute: true,
+        }
+    }
+
+    /// Create an access with the flag for a kind set.
+    pub fn with(kind: AccessKind) -> Self {
+        let mut access = Self::none();
+        match kind {
+            AccessKind::Read => access.read = true,
+            AccessKind::Write => access.write = true,
+            AccessKind::Execute => access.execute = true,
+        }
+        access
+    }
+
+    /// Determine if the flag for a kind is set.
+    pub fn contains(&self, kind: AccessKind) -> bool {
+        match kind {
+            AccessKind::Read => self.read,
+            AccessKind::Write => self.write,
+            AccessKind::Execute => self.execute,
+        }
+    }
+
+    /// Render the flags in the style of file permissions, eg: `r-x`.
+    pub fn to_rwx_string(&self) -> String {
+        let flag = |set: bool, c: char| if set { c } else { '-' };
+        [
+            flag(self.read, 'r'),
+            flag(self.write, 'w'),
+            flag(self.execute, 'x'),
+        ]
+        .iter()
+        .collect()
+    }
+
+    /// Determine if any access flag is set.
+    pub fn any(&self) -> bool {
+        self.read || self.write || self.execute
+    }
+
+    /// Determine if every flag set in other is also set in this access.
+    pub fn includes(&self, other: &Self) -> bool {
+        (self.read || !other.read)
+            && (self.write || !other.write)
+            && (self.execute || !other.execute)
+    }
+
     /// Merge positive flags from other into this access.
     pub fn merge(&mut self, other: &Self) {
         if other.read {
@@ -26,8 +90,15 @@ impl Access {
     }
 }
 
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_rwx_string())
+    }
+}
+
 /// Helper for analysis tasks to determine what type of
 /// access to assign when walking AST nodes is complete.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum AccessKind {
     /// Assign read access.
     Read,
@@ -1,7 +1,5 @@
 //! Builder for creating bundles.
 
-use std::fs::File;
-use std::io::BufReader;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -9,18 +7,23 @@ use anyhow::{bail, Context, Result};
 
 use swc_common::{util::take::Take, FileName, SourceMap, DUMMY_SP};
 use swc_ecma_ast::*;
-use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
+use swc_ecma_loader::resolve::Resolve;
 use swc_ecma_visit::{Fold, FoldWith};
 
 use serde::Serialize;
 
 use crate::{
-    module::base::module_base_directory,
+    module::{
+        base::{module_base_directory, root_relative},
+        resolver::ExportsResolver,
+    },
     policy::{Merge, Policy},
+    stats::Timings,
     swc_utils::load_file,
 };
 
 use super::{
+    graph::ModuleGraph,
     loader::load_modules,
     serializer::{Serializer, Value},
 };
@@ -37,6 +40,10 @@ pub(crate) struct BundleBuilder {
     program: Program,
     source_map: Arc<SourceMap>,
     resolver: Box<dyn Resolve>,
+    root: Option<PathBuf>,
+    content_hash: bool,
+    timings: Option<Arc<Timings>>,
+    graph: ModuleGraph,
 }
 
 impl BundleBuilder {
@@ -48,35 +55,65 @@ impl BundleBuilder {
             body: vec![],
             shebang: None,
         });
-        let resolver: Box<dyn Resolve> =
-            Box::new(NodeModulesResolver::default());
+        let resolver: Box<dyn Resolve> = Box::new(ExportsResolver::default());
 
         Self {
             policy: Default::default(),
             program,
             source_map,
             resolver,
+            root: None,
+            content_hash: false,
+            timings: None,
+            graph: Default::default(),
         }
     }
 
+    /// Set the project root used to compute module identifiers,
+    /// defaults to the current working directory.
+    ///
+    /// When a root is set the module paths in the graph are
+    /// relative to the root.
+    pub fn root(mut self, root: PathBuf) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Set the source map used to parse modules.
+    ///
+    /// Parsed modules are cached for the process so pass the same
+    /// source map to the policy builder to share the modules and
+    /// keep the spans of shared modules valid for both builders.
+    pub fn source_map(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Include the module content in module identifiers.
+    pub fn content_hash(mut self, content_hash: bool) -> Self {
+        self.content_hash = content_hash;
+        self
+    }
+
+    /// Set a collector for the time spent in each phase.
+    pub fn timings(mut self, timings: Arc<Timings>) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
     /// Load policy files.
     pub fn load_policy_files(mut self, policy: &Vec<PathBuf>) -> Result<Self> {
-        for file in policy {
-            let f = File::open(file).context(format!(
-                "Unable to open policy file {}",
-                file.display()
-            ))?;
-            let reader = BufReader::new(f);
-            let mut policy: Policy = serde_json::from_reader(reader).context(
-                format!("Failed to parse JSON in {}", file.display()),
-            )?;
-            self.policy.merge(&mut policy);
-        }
+        self.policy.merge(&Policy::load_files(policy)?);
         Ok(self)
     }
 
     /// Fold into a single program.
-    pub fn fold(mut self, entry: PathBuf) -> Result<Self> {
+    ///
+    /// Modules shared between entry points are only emitted once.
+    ///
+    /// Modules are emitted in dependency order; an error is returned
+    /// when circular dependencies prevent ordering the modules.
+    pub fn fold(mut self, entries: Vec<PathBuf>) -> Result<Self> {
         // Load and inject the runtime
         let module = self.load_runtime_module()?;
         let mut runtime_module = RuntimeModule { module };
@@ -84,14 +121,33 @@ impl BundleBuilder {
 
         // [123, {'./util.js': 456 }, function(){ module.exports = 42 }, { package: '<root>' }]
 
-        // Build modules data structure
-        let (expr, entry_point_ids) =
-            load_modules(entry, Arc::clone(&self.source_map), &self.resolver)?;
+        // Build modules data structure, dependencies are
+        // emitted before the modules that depend upon them
+        let relative = self.root.is_some();
+        let root = match self.root.take() {
+            Some(root) => root,
+            None => std::env::current_dir()?,
+        };
+        let (expr, mut graph) = load_modules(
+            &entries,
+            Arc::clone(&self.source_map),
+            &self.resolver,
+            &root,
+            self.content_hash,
+            self.timings.as_deref(),
+        )?;
+        if relative {
+            for module in graph.modules.iter_mut() {
+                module.path = root_relative(&module.path, Some(&root));
+            }
+        }
+        // List of entry point ids, built before the graph is
+        // moved into the builder
+        let entry_expr = self.build_entry_points(&graph.entry)?;
+        self.graph = graph;
         let mut modules_decl = ModulesDecl { expr };
         self.program = self.program.fold_children_with(&mut modules_decl);
 
-        // List of entry point ids
-        let entry_expr = self.build_entry_points(entry_point_ids)?;
         let mut entries_decl = EntryPointsDecl { expr: entry_expr };
         self.program = self.program.fold_children_with(&mut entries_decl);
 
@@ -114,6 +170,11 @@ impl BundleBuilder {
         Ok(self)
     }
 
+    /// Module graph resolved when folding the program.
+    pub fn graph(&self) -> &ModuleGraph {
+        &self.graph
+    }
+
     /// Finalize the bundled program.
     pub fn finalize(self) -> (Program, Arc<SourceMap>) {
         (self.program, self.source_map)
@@ -147,8 +208,8 @@ impl BundleBuilder {
         Ok(module)
     }
 
-    fn build_entry_points(&self, ids: Vec<u32>) -> Result<Expr> {
-        let mut serializer = Serializer {};
+    fn build_entry_points(&self, ids: &[String]) -> Result<Expr> {
+        let mut serializer = Serializer::new();
         let value = ids.serialize(&mut serializer)?;
         if let Value::Array(arr) = value {
             return Ok(Expr::Array(arr));
@@ -242,7 +303,7 @@ struct PolicyDecl {
 
 impl PolicyDecl {
     fn build_policy(policy: Policy) -> Result<Expr> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let value = policy.serialize(&mut serializer)?;
         if let Value::Object(obj) = value {
             return Ok(Expr::Object(obj));
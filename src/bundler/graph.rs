@@ -0,0 +1,47 @@
+//! Module graph resolved by the bundler.
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// Modules and dependencies resolved when building a bundle.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModuleGraph {
+    /// Modules in the order they are emitted.
+    pub modules: Vec<GraphModule>,
+    /// Dependencies between modules.
+    pub edges: Vec<GraphEdge>,
+    /// Identifiers of the entry point modules.
+    pub entry: Vec<String>,
+}
+
+impl ModuleGraph {
+    /// Get the dependencies of a module.
+    pub fn dependencies<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> impl Iterator<Item = &'a GraphEdge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+}
+
+/// Module in the graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphModule {
+    /// Module identifier.
+    pub id: String,
+    /// Resolved path for the module.
+    pub path: PathBuf,
+    /// Package that owns the module.
+    pub package: String,
+}
+
+/// Dependency of one module upon another.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    /// Identifier of the dependent module.
+    pub from: String,
+    /// Identifier of the dependency.
+    pub to: String,
+    /// Specifier used to import the dependency.
+    pub specifier: String,
+}
@@ -0,0 +1,53 @@
+//! Stable module identifiers for bundles.
+//!
+//! Identifiers are derived from the path of a module relative to a
+//! project root so that bundles are reproducible across machines where
+//! the absolute paths differ.
+use std::path::{Component, Path};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Compute the identifier for a module.
+///
+/// When the module is not within the root directory the path is
+/// used as is. When content is given it is included in the hash so
+/// that the identifier changes when the module changes.
+pub fn module_id<P: AsRef<Path>, R: AsRef<Path>>(
+    path: P,
+    root: R,
+    content: Option<&[u8]>,
+) -> String {
+    let path = path.as_ref();
+    let relative = path.strip_prefix(root.as_ref()).unwrap_or(path);
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, portable_path(relative).as_bytes());
+    if let Some(content) = content {
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, content);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Path with forward slash separators so that identifiers
+/// do not depend upon the platform.
+fn portable_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            Component::ParentDir => Some("..".into()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The FNV-1a hash is used as the output must be stable
+/// between releases which is not guaranteed by the standard
+/// library hasher.
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
@@ -1,8 +1,9 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{bail, Result};
+use indexmap::IndexMap;
 use serde::Serialize;
 
 use swc_common::{FileName, SourceMap, DUMMY_SP};
@@ -16,14 +17,17 @@ use crate::{
     },
     module::{
         dependencies::is_dependent_module,
-        node::{
-            cached_modules, parse_module, VisitedDependency, VisitedModule,
-        },
+        node::{parse_file_with_timings, VisitedModule},
     },
     static_module_record::transform::transform_module_function,
+    stats::Timings,
 };
 
-use super::serializer::Serializer;
+use super::{
+    graph::{GraphEdge, GraphModule, ModuleGraph},
+    ids::module_id,
+    serializer::Serializer,
+};
 
 const ROOT_PACKAGE: &str = "<root>";
 
@@ -43,95 +47,242 @@ pub enum ModuleKind {
     Cjs,
 }
 
-pub(super) fn load_modules<P: AsRef<Path>>(
-    file: P,
+pub(super) fn load_modules(
+    entries: &[PathBuf],
     source_map: Arc<SourceMap>,
     resolver: &Box<dyn Resolve>,
-) -> Result<(Expr, Vec<u32>)> {
-    let mut list = Vec::new();
-    let module =
-        parse_module(file.as_ref(), resolver, Arc::clone(&source_map))?;
-
-    let root_entry_id = {
-        let cached = cached_modules();
-        let entry = cached.get(file.as_ref()).unwrap();
-        let value = entry.value();
-        match &**value {
-            VisitedModule::Module(_, node) | VisitedModule::Json(_, node) => {
-                node.id
+    root: &Path,
+    content_hash: bool,
+    timings: Option<&Timings>,
+) -> Result<(Expr, ModuleGraph)> {
+    let mut modules: IndexMap<PathBuf, BundledModule> = IndexMap::new();
+
+    // Visit the module graph from each entry point, modules shared
+    // between entry points are only collected once.
+    //
+    // Use all the resolved dependencies as the resolved list
+    // on the node excludes modules that were already cached
+    let mut pending: VecDeque<(String, PathBuf)> = entries
+        .iter()
+        .map(|entry| (ROOT_PACKAGE.to_string(), entry.clone()))
+        .collect();
+    while let Some((package, path)) = pending.pop_front() {
+        if modules.contains_key(&path) {
+            continue;
+        }
+
+        let module = parse_file_with_timings(
+            &path,
+            resolver,
+            Arc::clone(&source_map),
+            timings,
+        )?;
+
+        let mut dependencies = Vec::new();
+        if let VisitedModule::Module(_, node) | VisitedModule::Json(_, node) =
+            &*module
+        {
+            for (spec, dep) in node.all_resolved.iter() {
+                if let FileName::Real(dep) = dep {
+                    let package = if is_dependent_module(&spec) {
+                        normalize_specifier(spec.clone())
+                    } else {
+                        ROOT_PACKAGE.to_string()
+                    };
+                    pending.push_back((package, dep.clone()));
+                    dependencies.push((spec.clone(), dep.clone()));
+                }
             }
-            _ => unreachable!("Main entry point cannot be a builtin"),
         }
+
+        modules.insert(
+            path.clone(),
+            BundledModule {
+                package,
+                path,
+                module,
+                dependencies,
+            },
+        );
+    }
+
+    let list = sort_modules(modules)?;
+
+    // Assign stable identifiers to the modules
+    let mut ids: HashMap<PathBuf, String> = HashMap::new();
+    for item in list.iter() {
+        let content = if content_hash {
+            Some(std::fs::read(&item.path)?)
+        } else {
+            None
+        };
+        ids.insert(
+            item.path.clone(),
+            module_id(&item.path, root, content.as_ref().map(|c| &c[..])),
+        );
+    }
+
+    let mut graph = ModuleGraph::default();
+    for item in list.iter() {
+        let id = ids[&item.path].clone();
+        for (spec, dep) in item.dependencies.iter() {
+            graph.edges.push(GraphEdge {
+                from: id.clone(),
+                to: ids[dep].clone(),
+                specifier: spec.clone(),
+            });
+        }
+        graph.modules.push(GraphModule {
+            id,
+            path: item.path.clone(),
+            package: item.package.clone(),
+        });
+    }
+    for entry in entries {
+        graph.entry.push(ids[entry].clone());
+    }
+
+    Ok((transform_modules(list, &graph)?, graph))
+}
+
+/// Module to be included in a bundle.
+struct BundledModule {
+    /// Package name for the module.
+    package: String,
+    /// Path to the module.
+    path: PathBuf,
+    /// The parsed module.
+    module: Arc<VisitedModule>,
+    /// Specifiers and paths for the dependencies that are
+    /// also included in the bundle.
+    dependencies: Vec<(String, PathBuf)>,
+}
+
+/// Sort modules so that every module is emitted after its dependencies.
+///
+/// A module that imports itself does not prevent ordering. Cycles
+/// that include a CommonJS module are legal as `require()` returns
+/// the partial exports of a module that is still loading so the
+/// cycle is broken at the edge that closes it; a cycle of ES modules
+/// cannot be linked in dependency order and is an error.
+fn sort_modules(
+    modules: IndexMap<PathBuf, BundledModule>,
+) -> Result<Vec<BundledModule>> {
+    let esm: Vec<bool> = modules
+        .values()
+        .map(|module| match &*module.module {
+            VisitedModule::Module(_, node) => {
+                matches!(module_kind(&node.module), ModuleKind::Esm)
+            }
+            _ => false,
+        })
+        .collect();
+
+    let edges: Vec<Vec<usize>> = modules
+        .values()
+        .enumerate()
+        .map(|(i, module)| {
+            module
+                .dependencies
+                .iter()
+                .filter_map(|(_, dep)| modules.get_index_of(dep))
+                .filter(|j| *j != i)
+                .collect()
+        })
+        .collect();
+
+    let mut sorter = ModuleSorter {
+        paths: modules.keys().collect(),
+        esm,
+        marks: vec![Mark::Unvisited; edges.len()],
+        edges,
+        stack: Vec::new(),
+        order: Vec::new(),
     };
+    for i in 0..sorter.paths.len() {
+        sorter.visit(i)?;
+    }
+    let order = sorter.order;
 
-    // Add the root entry point module
-    list.push((ROOT_PACKAGE.to_string(), Arc::clone(&module)));
-
-    // Visit the module graph and collect the module nodes
-    let mut visitor = |dep: VisitedDependency| {
-        if let FileName::Real(path) = &dep.file_name {
-            let cached = cached_modules();
-            if let Some(item) = cached.get(path) {
-                let module = item.value();
-                let spec = if is_dependent_module(&dep.spec) {
-                    normalize_specifier(dep.spec)
-                } else {
-                    ROOT_PACKAGE.to_string()
-                };
-                list.push((spec, Arc::clone(module)));
+    let mut modules: Vec<Option<BundledModule>> = modules
+        .into_iter()
+        .map(|(_, module)| Some(module))
+        .collect();
+    Ok(order
+        .into_iter()
+        .map(|i| modules[i].take().unwrap())
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Mark {
+    Unvisited,
+    Visiting,
+    Done,
+}
+
+/// Depth first search that emits modules in post order.
+struct ModuleSorter<'a> {
+    paths: Vec<&'a PathBuf>,
+    esm: Vec<bool>,
+    edges: Vec<Vec<usize>>,
+    marks: Vec<Mark>,
+    stack: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl ModuleSorter<'_> {
+    fn visit(&mut self, i: usize) -> Result<()> {
+        match self.marks[i] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                let pos = self.stack.iter().position(|j| *j == i).unwrap();
+                if self.stack[pos..].iter().any(|j| !self.esm[*j]) {
+                    return Ok(());
+                }
+                let cycle: Vec<String> = self.stack[pos..]
+                    .iter()
+                    .chain(std::iter::once(&i))
+                    .map(|j| self.paths[*j].display().to_string())
+                    .collect();
+                bail!(
+                    "unable to order ES modules, circular dependency {}",
+                    cycle.join(" -> ")
+                );
             }
+            Mark::Unvisited => {}
         }
-        Ok(())
-    };
 
-    if let VisitedModule::Module(_, node) = &*module {
-        node.visit(source_map, &mut visitor)?;
+        self.marks[i] = Mark::Visiting;
+        self.stack.push(i);
+        for j in self.edges[i].clone() {
+            self.visit(j)?;
+        }
+        self.stack.pop();
+        self.marks[i] = Mark::Done;
+        self.order.push(i);
+        Ok(())
     }
-
-    Ok((transform_modules(list)?, vec![root_entry_id]))
 }
 
 fn transform_modules(
-    modules: Vec<(String, Arc<VisitedModule>)>,
+    modules: Vec<BundledModule>,
+    graph: &ModuleGraph,
 ) -> Result<Expr> {
-    let mut serializer = Serializer {};
+    let mut serializer = Serializer::new();
 
     let mut arr = ArrayLit {
         span: DUMMY_SP,
         elems: vec![],
     };
 
-    //let mut out = Vec::new();
-    for (spec, item) in modules {
-        match &*item {
+    for (bundled, entry) in modules.into_iter().zip(graph.modules.iter()) {
+        match &*bundled.module {
             VisitedModule::Module(_, module)
             | VisitedModule::Json(_, module) => {
-                let dependencies: HashMap<String, u32> = module
-                    .resolved
-                    .iter()
-                    .map(|(spec, file_name)| {
-                        let id: Option<u32> =
-                            if let FileName::Real(path) = &file_name {
-                                let cached = cached_modules();
-                                if let Some(item) = cached.get(path) {
-                                    let module = item.value();
-                                    match &**module {
-                                        VisitedModule::Module(_, module)
-                                        | VisitedModule::Json(_, module) => {
-                                            Some(module.id)
-                                        }
-                                        _ => None,
-                                    }
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            };
-                        return (spec.to_string(), id);
-                    })
-                    .filter(|(_, id)| id.is_some())
-                    .map(|(spec, id)| (spec, id.unwrap()))
+                let dependencies: HashMap<&str, &str> = graph
+                    .dependencies(&entry.id)
+                    .map(|edge| (&edge.specifier[..], &edge.to[..]))
                     .collect();
 
                 let mut item = ArrayLit {
@@ -140,7 +291,7 @@ fn transform_modules(
                 };
 
                 // Module id
-                let id = module.id.serialize(&mut serializer)?;
+                let id = entry.id.serialize(&mut serializer)?;
                 item.elems.push(Some(ExprOrSpread {
                     spread: None,
                     expr: id.into_boxed_expr(),
@@ -162,7 +313,7 @@ fn transform_modules(
 
                 // Package options
                 let opts = ModuleOptions {
-                    package: spec,
+                    package: bundled.package,
                     r#type: kind,
                 };
                 let opts = opts.serialize(&mut serializer)?;
@@ -184,14 +335,18 @@ fn transform_modules(
     Ok(Expr::Array(arr))
 }
 
-fn into_module_function(module: &Module) -> Result<(Box<Expr>, ModuleKind)> {
+/// Detect whether a module uses ESM or CommonJS syntax.
+fn module_kind(module: &Module) -> ModuleKind {
     let mut detector = Es6Detector {
         esm: false,
         cjs: false,
     };
     module.visit_children_with(&mut detector);
+    detector.kind()
+}
 
-    let kind = detector.kind();
+fn into_module_function(module: &Module) -> Result<(Box<Expr>, ModuleKind)> {
+    let kind = module_kind(&module);
     match &kind {
         ModuleKind::Esm => Ok((transform_esm(module)?, kind)),
         ModuleKind::Cjs => Ok((transform_cjs(module)?, kind)),
@@ -6,26 +6,63 @@ use std::sync::Arc;
 use swc_common::SourceMap;
 use swc_ecma_ast::Program;
 
+use crate::stats::Timings;
+
 mod builder;
+mod graph;
+mod ids;
 mod loader;
 mod serializer;
 
+pub use graph::{GraphEdge, GraphModule, ModuleGraph};
+pub use ids::module_id;
+
 /// Options for bundling.
-#[derive(Debug)]
+#[derive(Default)]
 pub struct BundleOptions {
-    pub(crate) module: PathBuf,
-    pub(crate) policy: Vec<PathBuf>,
+    /// Entry point modules.
+    pub modules: Vec<PathBuf>,
+    /// Policy files for the bundle.
+    pub policy: Vec<PathBuf>,
+    /// Project root for module identifiers.
+    pub root: Option<PathBuf>,
+    /// Include the module content in module identifiers.
+    pub content_hash: bool,
+    /// Collector for the time spent in each phase.
+    pub timings: Option<Arc<Timings>>,
+    /// Source map used to parse modules, pass the source map
+    /// given to a policy builder to share the parsed modules.
+    pub source_map: Option<Arc<SourceMap>>,
 }
 
 /// Generate a bundle from the given options.
-pub fn bundle(options: BundleOptions) -> Result<(Program, Arc<SourceMap>)> {
-    let builder = builder::BundleBuilder::new();
-    let module = options
-        .module
-        .canonicalize()
-        .context("Failed to determine canonical path for module entry point")?;
-    Ok(builder
-        .load_policy_files(&options.policy)?
-        .fold(module)?
-        .finalize())
+///
+/// Returns the bundled program, source map and the resolved module graph.
+pub fn bundle(
+    options: BundleOptions,
+) -> Result<(Program, Arc<SourceMap>, ModuleGraph)> {
+    let mut builder = builder::BundleBuilder::new();
+    if let Some(root) = options.root {
+        builder = builder.root(root);
+    }
+    builder = builder.content_hash(options.content_hash);
+    if let Some(timings) = options.timings {
+        builder = builder.timings(timings);
+    }
+    if let Some(source_map) = options.source_map {
+        builder = builder.source_map(source_map);
+    }
+    let modules = options
+        .modules
+        .iter()
+        .map(|module| {
+            module.canonicalize().context(
+                "Failed to determine canonical path for module entry point",
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let builder = builder.load_policy_files(&options.policy)?.fold(modules)?;
+    let graph = builder.graph().clone();
+    let (program, source_map) = builder.finalize();
+    Ok((program, source_map, graph))
 }
@@ -1,6 +1,11 @@
 //! Convert a `Serialize` implementation into `swc_ecma_ast` nodes.
 //!
-//! Primitives `i64` and `u64` will be serialized to the `BigInt` type.
+//! Primitives `i64`, `u64`, `i128` and `u128` will be serialized to
+//! the `BigInt` type.
+//!
+//! Map keys must be a string, number or bigint to be represented as an
+//! object literal; use `Serializer::with_map_fallback()` to serialize
+//! other key types to a `Map` instead.
 //!
 use serde::ser::{self, Serialize};
 use std::error::Error as StdError;
@@ -11,6 +16,8 @@ use num_bigint::BigInt as BigIntValue;
 use swc_common::DUMMY_SP;
 use swc_ecma_ast::*;
 
+const MAP: &str = "Map";
+
 #[inline]
 fn str_lit(value: &str) -> Str {
     Str {
@@ -62,6 +69,7 @@ pub enum Value {
     Number(Number),
     BigInt(BigInt),
     Null(Null),
+    Map(NewExpr),
 }
 
 impl Value {
@@ -74,6 +82,7 @@ impl Value {
             Value::Number(num) => Box::new(Expr::Lit(Lit::Num(num))),
             Value::BigInt(num) => Box::new(Expr::Lit(Lit::BigInt(num))),
             Value::Null(null) => Box::new(Expr::Lit(Lit::Null(null))),
+            Value::Map(map) => Box::new(Expr::New(map)),
         }
     }
 }
@@ -149,11 +158,87 @@ impl<'a> ser::SerializeTupleStruct for SerializeArray<'a> {
 }
 
 /// Serialize to an object literal.
+///
+/// When the serializer allows the map fallback and a key type is not
+/// supported by object literals the entries are collected so that
+/// a `Map` is created instead.
 #[doc(hidden)]
 pub struct SerializeObject<'a> {
     literal: ObjectLit,
+    entries: Option<Vec<(Box<Expr>, Box<Expr>)>>,
     ser: &'a mut Serializer,
 }
+
+impl<'a> SerializeObject<'a> {
+    /// Convert the object literal properties into `Map` entries.
+    fn into_map_entries(&mut self) -> Vec<(Box<Expr>, Box<Expr>)> {
+        let props = std::mem::take(&mut self.literal.props);
+        props
+            .into_iter()
+            .filter_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match *prop {
+                    Prop::KeyValue(KeyValueProp { key, value }) => {
+                        let key = match key {
+                            PropName::Str(lit) => Expr::Lit(Lit::Str(lit)),
+                            PropName::Num(num) => Expr::Lit(Lit::Num(num)),
+                            PropName::BigInt(num) => {
+                                Expr::Lit(Lit::BigInt(num))
+                            }
+                            PropName::Ident(ident) => {
+                                Expr::Lit(Lit::Str(str_lit(ident.sym.as_ref())))
+                            }
+                            PropName::Computed(computed) => *computed.expr,
+                        };
+                        Some((Box::new(key), value))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Create a `new Map([[key, value]])` expression from entries.
+    fn map_expr(entries: Vec<(Box<Expr>, Box<Expr>)>) -> NewExpr {
+        NewExpr {
+            span: DUMMY_SP,
+            callee: Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: MAP.into(),
+                optional: false,
+            })),
+            args: Some(vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: entries
+                        .into_iter()
+                        .map(|(key, value)| {
+                            Some(ExprOrSpread {
+                                spread: None,
+                                expr: Box::new(Expr::Array(ArrayLit {
+                                    span: DUMMY_SP,
+                                    elems: vec![
+                                        Some(ExprOrSpread {
+                                            spread: None,
+                                            expr: key,
+                                        }),
+                                        Some(ExprOrSpread {
+                                            spread: None,
+                                            expr: value,
+                                        }),
+                                    ],
+                                })),
+                            })
+                        })
+                        .collect(),
+                })),
+            }]),
+            type_args: None,
+        }
+    }
+}
+
 impl<'a> ser::SerializeStruct for SerializeObject<'a> {
     type Ok = Value;
     type Error = Error;
@@ -208,6 +293,9 @@ impl<'a> ser::SerializeMap for SerializeObject<'a> {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        if let Some(entries) = self.entries {
+            return Ok(Value::Map(SerializeObject::map_expr(entries)));
+        }
         Ok(Value::Object(self.literal))
     }
 
@@ -221,13 +309,29 @@ impl<'a> ser::SerializeMap for SerializeObject<'a> {
         V: Serialize,
     {
         let key = key.serialize(&mut *self.ser)?;
+
+        // Already collecting entries for a `Map`
+        if let Some(entries) = self.entries.as_mut() {
+            let value = value.serialize(&mut *self.ser)?;
+            entries.push((key.into_boxed_expr(), value.into_boxed_expr()));
+            return Ok(());
+        }
+
         let key = match key {
             Value::String(lit) => PropName::Str(lit),
             Value::Number(num) => PropName::Num(num),
             Value::BigInt(num) => PropName::BigInt(num),
             _ => {
-                // TODO: convert other key types to use `Map`
-                return Err(Error::ObjectKeyType);
+                if !self.ser.map_fallback {
+                    return Err(Error::ObjectKeyType);
+                }
+
+                // Switch to collecting entries for a `Map`
+                let mut entries = self.into_map_entries();
+                let value = value.serialize(&mut *self.ser)?;
+                entries.push((key.into_boxed_expr(), value.into_boxed_expr()));
+                self.entries = Some(entries);
+                return Ok(());
             }
         };
 
@@ -331,7 +435,23 @@ impl<'a> ser::SerializeStructVariant for SerializeStructVariant<'a> {
 }
 
 /// Perform serialization into AST nodes.
-pub struct Serializer;
+#[derive(Debug, Default)]
+pub struct Serializer {
+    map_fallback: bool,
+}
+
+impl Serializer {
+    /// Create a serializer.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create a serializer that converts maps with key types
+    /// that are not supported by object literals to a `Map`.
+    pub fn with_map_fallback() -> Self {
+        Self { map_fallback: true }
+    }
+}
 
 impl<'a> ser::Serializer for &'a mut Serializer {
     type Ok = Value;
@@ -371,6 +491,13 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         }))
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::BigInt(BigInt {
+            span: DUMMY_SP,
+            value: BigIntValue::from(v),
+        }))
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.serialize_f64(v as f64)
     }
@@ -390,6 +517,13 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         }))
     }
 
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::BigInt(BigInt {
+            span: DUMMY_SP,
+            value: BigIntValue::from(v),
+        }))
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.serialize_f64(v as f64)
     }
@@ -518,6 +652,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     ) -> Result<Self::SerializeMap, Self::Error> {
         Ok(SerializeObject {
             ser: self,
+            entries: None,
             literal: ObjectLit {
                 span: DUMMY_SP,
                 props: {
@@ -607,7 +742,7 @@ mod tests {
 
     #[test]
     fn serialize_primitives() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
 
         let ch = 'c';
         let value = ch.serialize(&mut serializer)?;
@@ -703,7 +838,25 @@ mod tests {
             value
         );
 
-        // i128 / u128 ?
+        let i_128 = 128i128;
+        let value = i_128.serialize(&mut serializer)?;
+        assert_eq!(
+            Value::BigInt(BigInt {
+                span: DUMMY_SP,
+                value: BigIntValue::from(i_128),
+            }),
+            value
+        );
+
+        let u_128 = 256u128;
+        let value = u_128.serialize(&mut serializer)?;
+        assert_eq!(
+            Value::BigInt(BigInt {
+                span: DUMMY_SP,
+                value: BigIntValue::from(u_128),
+            }),
+            value
+        );
 
         let float32 = 3.14f64;
         let value = float32.serialize(&mut serializer)?;
@@ -730,7 +883,7 @@ mod tests {
 
     #[test]
     fn serialize_string() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let string = String::from("mock");
         let value = string.serialize(&mut serializer)?;
         assert_eq!(Value::String(str_lit("mock")), value);
@@ -739,7 +892,7 @@ mod tests {
 
     #[test]
     fn serialize_byte_array() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let bytes = [0u8, 0u8];
         let value = (&bytes).serialize(&mut serializer)?;
         assert_eq!(
@@ -769,7 +922,7 @@ mod tests {
 
     #[test]
     fn serialize_option() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
 
         let none: Option<()> = None;
         let value = none.serialize(&mut serializer)?;
@@ -790,7 +943,7 @@ mod tests {
 
     #[test]
     fn serialize_unit() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let unit = ();
         let value = unit.serialize(&mut serializer)?;
         assert_eq!(Value::Null(Null { span: DUMMY_SP }), value);
@@ -804,7 +957,7 @@ mod tests {
 
     #[test]
     fn serialize_unit_struct() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
 
         let unit_struct = Marker {
             marker: std::marker::PhantomData,
@@ -841,7 +994,7 @@ mod tests {
 
     #[test]
     fn serialize_unit_variant() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let variant = EnvPolicy {
             env: UnitVariant::Frozen,
         };
@@ -865,7 +1018,7 @@ mod tests {
 
     #[test]
     fn serialize_newtype_struct() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let mm = Millimeters(0);
         let value = mm.serialize(&mut serializer)?;
         let expected = Value::Number(Number {
@@ -884,7 +1037,7 @@ mod tests {
 
     #[test]
     fn serialize_newtype_variant() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let mm = Length::Millimeters(0);
         let value = mm.serialize(&mut serializer)?;
         let expected = Value::Object(ObjectLit {
@@ -906,7 +1059,7 @@ mod tests {
 
     #[test]
     fn serialize_seq() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let list = vec!["1", "2"];
         let value = list.serialize(&mut serializer)?;
         let expected = Value::Array(ArrayLit {
@@ -929,7 +1082,7 @@ mod tests {
 
     #[test]
     fn serialize_tuple() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let list = ("3", "4");
         let value = list.serialize(&mut serializer)?;
         let expected = Value::Array(ArrayLit {
@@ -955,7 +1108,7 @@ mod tests {
 
     #[test]
     fn serialize_tuple_struct() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let color = Rgb(0xff, 0x66, 0x00);
         let value = color.serialize(&mut serializer)?;
         let expected = Value::Array(ArrayLit {
@@ -996,7 +1149,7 @@ mod tests {
 
     #[test]
     fn serialize_tuple_variant() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let variant = TupleVariant::T("1".into(), "2".into());
         let value = variant.serialize(&mut serializer)?;
         let expected = Value::Object(ObjectLit {
@@ -1032,7 +1185,7 @@ mod tests {
     #[test]
     fn serialize_map() -> Result<()> {
         use std::collections::HashMap;
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
 
         // String keys
         let mut map = HashMap::new();
@@ -1095,7 +1248,68 @@ mod tests {
         let mut map = HashMap::new();
         map.insert((1, 2), 3);
         let result = map.serialize(&mut serializer);
-        println!("{:#?}", result);
+        assert_eq!(Err(Error::ObjectKeyType), result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_map_fallback() -> Result<()> {
+        use std::collections::HashMap;
+        let mut serializer = Serializer::with_map_fallback();
+
+        let mut map = HashMap::new();
+        map.insert((1, 2), 3);
+        let value = map.serialize(&mut serializer)?;
+
+        let num = |value: f64| {
+            Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value,
+                }))),
+            })
+        };
+
+        let expected = Value::Map(NewExpr {
+            span: DUMMY_SP,
+            callee: Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: MAP.into(),
+                optional: false,
+            })),
+            args: Some(vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: vec![Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(Expr::Array(ArrayLit {
+                            span: DUMMY_SP,
+                            elems: vec![
+                                Some(ExprOrSpread {
+                                    spread: None,
+                                    expr: Box::new(Expr::Array(ArrayLit {
+                                        span: DUMMY_SP,
+                                        elems: vec![num(1f64), num(2f64)],
+                                    })),
+                                }),
+                                num(3f64),
+                            ],
+                        })),
+                    })],
+                })),
+            }]),
+            type_args: None,
+        });
+        assert_eq!(expected, value);
+
+        // Supported key types still use an object literal
+        let mut map = HashMap::new();
+        map.insert("foo", "bar");
+        let value = map.serialize(&mut serializer)?;
+        assert!(matches!(value, Value::Object(_)));
 
         Ok(())
     }
@@ -1109,7 +1323,7 @@ mod tests {
 
     #[test]
     fn serialize_struct() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let con = Container {
             message: "mock".into(),
             amount: 32,
@@ -1152,7 +1366,7 @@ mod tests {
 
     #[test]
     fn serialize_struct_variant() -> Result<()> {
-        let mut serializer = Serializer {};
+        let mut serializer = Serializer::new();
         let variant = StructVariant::Rgb {
             r: "ff".into(),
             g: "66".into(),
@@ -1,11 +1,18 @@
 //! Command line parsing exposed via the library for the node bindings.
 use std::ffi::OsString;
+use std::io;
 use std::path::PathBuf;
 
 use anyhow::Result;
 use structopt::StructOpt;
 
-use super::{bundle, globals, inspect, meta, parse, policy, transform, tree};
+use super::json::Indent;
+use super::policy::{builder::BuiltinGranularity, PolicyFormat};
+use super::printer::PrintFormat;
+use super::{
+    audit, bundle, check, diff, error::BasaltError, globals, inspect, list,
+    meta, parse, policy, transform, BundleArgs, PolicyArgs,
+};
 
 #[derive(StructOpt)]
 enum Debug {
@@ -14,13 +21,28 @@ enum Debug {
         /// Code to parse and print
         #[structopt(short, long)]
         code: Option<String>,
-        /// Module entry point
+        /// Print the AST as JSON
+        #[structopt(short, long)]
+        json: bool,
+        /// Module entry point, use `-` to read from stdin
         #[structopt(parse(from_os_str))]
         module: Option<PathBuf>,
     },
 
     /// Parse a dependency graph
     Parse {
+        /// Number of worker threads
+        #[structopt(short, long)]
+        threads: Option<usize>,
+
+        /// Report unresolved imports and continue parsing the graph
+        #[structopt(long)]
+        continue_on_error: bool,
+
+        /// Print the time spent in each phase to stderr
+        #[structopt(long)]
+        stats: bool,
+
         /// Module entry point
         #[structopt(parse(from_os_str))]
         module: PathBuf,
@@ -32,6 +54,19 @@ enum Debug {
         #[structopt(short, long)]
         debug: bool,
 
+        /// Keep deep member paths
+        #[structopt(long)]
+        deep: bool,
+
+        /// Report `global` as a global symbol rather than
+        /// treating it as the node global object
+        #[structopt(long)]
+        no_node_global: bool,
+
+        /// Include the location of the first reference to each global
+        #[structopt(long)]
+        loc: bool,
+
         /// Module entry point
         #[structopt(parse(from_os_str))]
         module: PathBuf,
@@ -39,6 +74,10 @@ enum Debug {
 
     /// Generate the static module record meta data for a module
     Meta {
+        /// Indentation for the JSON output: number of spaces or tab
+        #[structopt(long, default_value = "2")]
+        indent: Indent,
+
         /// Module entry point
         #[structopt(parse(from_os_str))]
         module: PathBuf,
@@ -50,6 +89,22 @@ enum Debug {
         #[structopt(short, long)]
         json: bool,
 
+        /// Include a source map in the JSON output
+        #[structopt(long, requires = "json")]
+        source_map: bool,
+
+        /// Minify the generated program
+        #[structopt(short, long)]
+        minify: bool,
+
+        /// Generate a CommonJS function instead of a functor
+        #[structopt(long)]
+        commonjs: bool,
+
+        /// Sort the imports by module specifier
+        #[structopt(long)]
+        sort_imports: bool,
+
         /// Module entry point
         #[structopt(parse(from_os_str))]
         module: PathBuf,
@@ -59,12 +114,33 @@ enum Debug {
 #[derive(StructOpt)]
 #[structopt(about = "Lavamoat analyzer and bundler")]
 enum Commands {
-    /// Print the module tree
-    Tree {
+    /// Print the module graph
+    #[structopt(alias = "tree")]
+    List {
         /// Print the file name for each module
         #[structopt(short = "f", long)]
         include_file: bool,
 
+        /// Output format: tree, flat or json
+        #[structopt(
+            long,
+            default_value = "tree",
+            possible_values = &["tree", "flat", "json"]
+        )]
+        format: PrintFormat,
+
+        /// Print the circular dependencies
+        #[structopt(long)]
+        cycles: bool,
+
+        /// Print file paths relative to a project root
+        #[structopt(long, parse(from_os_str))]
+        root: Option<PathBuf>,
+
+        /// Print the globals and builtins used by each module
+        #[structopt(long)]
+        annotate: bool,
+
         /// Module entry point
         #[structopt(parse(from_os_str))]
         module: PathBuf,
@@ -84,18 +160,102 @@ enum Commands {
         /// Write bundle to output
         #[structopt(short, long)]
         output: Option<PathBuf>,
+        /// Project root for module identifiers
+        #[structopt(long, parse(from_os_str))]
+        root: Option<PathBuf>,
+        /// Include module content in module identifiers
+        #[structopt(long)]
+        content_hash: bool,
+        /// Write the resolved module graph as JSON
+        #[structopt(long, parse(from_os_str))]
+        graph: Option<PathBuf>,
+        /// Print the time spent in each phase to stderr
+        #[structopt(long)]
+        stats: bool,
+        /// Additional bundle entry point(s)
+        #[structopt(short, long = "entry", parse(from_os_str))]
+        entries: Vec<PathBuf>,
         /// Bundle entry point
         #[structopt(parse(from_os_str))]
-        module: PathBuf,
+        module: Option<PathBuf>,
     },
 
     /// Generate a lavamoat policy file
     Policy {
+        /// Additional module names to treat as builtins
+        #[structopt(long = "builtin")]
+        builtins: Vec<String>,
+
+        /// Output format for the policy
+        #[structopt(
+            long,
+            default_value = "json",
+            possible_values = &["json", "yaml", "toml"]
+        )]
+        format: PolicyFormat,
+
+        /// Indentation for the JSON output: number of spaces or tab
+        #[structopt(long, default_value = "2")]
+        indent: Indent,
+
+        /// Print file paths in warnings relative to a project root
+        #[structopt(long, parse(from_os_str))]
+        root: Option<PathBuf>,
+
+        /// Cache the analysis for each module in a directory
+        #[structopt(long, parse(from_os_str))]
+        cache_dir: Option<PathBuf>,
+
+        /// Grant builtins per module or per member
+        #[structopt(
+            long,
+            default_value = "member",
+            possible_values = &["module", "member"]
+        )]
+        builtin_granularity: BuiltinGranularity,
+
+        /// Print the time spent in each phase to stderr
+        #[structopt(long)]
+        stats: bool,
+
         /// Module entry point
         #[structopt(parse(from_os_str))]
         module: PathBuf,
     },
 
+    /// Check a module against policy file(s) without bundling
+    ///
+    /// Prints the capabilities used by the module that are not
+    /// granted by the policy and exits with an error when there
+    /// are violations.
+    #[structopt(alias = "dry-run")]
+    Check {
+        /// Path to policy file(s)
+        #[structopt(short, long)]
+        policy: Vec<PathBuf>,
+
+        /// Print the policy grants that are not used as JSON
+        #[structopt(long)]
+        unused: bool,
+
+        /// Module entry point
+        #[structopt(parse(from_os_str))]
+        module: PathBuf,
+    },
+
+    /// Compare two policy files
+    ///
+    /// Exits with an error when the other policy grants capabilities
+    /// that are not granted by the base policy.
+    Diff {
+        /// Base policy file
+        #[structopt(parse(from_os_str))]
+        base: PathBuf,
+        /// Policy file to compare with the base policy
+        #[structopt(parse(from_os_str))]
+        other: PathBuf,
+    },
+
     /// Utility debugging commands
     Debug {
         #[structopt(subcommand)]
@@ -111,7 +271,9 @@ where
     if std::env::var("RUST_LOG").ok().is_none() {
         std::env::set_var("RUST_LOG", "info");
     }
-    pretty_env_logger::init();
+    // Ignore the error when the logger is already initialized,
+    // the node binding may run several commands in one process
+    let _ = pretty_env_logger::try_init();
 
     let app = Commands::clap();
     let matches = if let Some(argv) = argv {
@@ -121,27 +283,117 @@ where
     };
     let args = Commands::from_clap(&matches);
     match args {
-        Commands::Tree {
+        Commands::List {
             module,
             include_file,
+            format,
+            cycles,
+            root,
+            annotate,
         } => {
-            tree(module, include_file)?;
+            list(module, include_file, format, cycles, root, annotate)?;
         }
         Commands::Bundle {
             module,
+            entries,
+            policy,
+            output,
+            source_map,
+            source_map_url,
+            root,
+            content_hash,
+            graph,
+            stats,
+        } => bundle(BundleArgs {
+            modules: module.into_iter().chain(entries).collect(),
             policy,
             output,
             source_map,
             source_map_url,
-        } => bundle(module, policy, output, source_map, source_map_url)?,
+            root,
+            content_hash,
+            graph,
+            stats,
+        })?,
 
-        Commands::Policy { module } => policy(module)?,
+        Commands::Policy {
+            module,
+            builtins,
+            format,
+            indent,
+            root,
+            cache_dir,
+            builtin_granularity,
+            stats,
+        } => policy(PolicyArgs {
+            file: module,
+            builtins,
+            format,
+            indent,
+            root,
+            cache_dir,
+            builtin_granularity,
+            stats,
+        })?,
+        Commands::Check {
+            module,
+            policy,
+            unused: true,
+        } => {
+            let unused = audit(module, policy)?;
+            println!("{}", serde_json::to_string_pretty(&unused)?);
+        }
+        Commands::Check { module, policy, .. } => {
+            let violations = check(module, policy)?;
+            for violation in violations.iter() {
+                println!("{}", violation);
+            }
+            if !violations.is_empty() {
+                return Err(BasaltError::Policy(format!(
+                    "found {} policy violation(s)",
+                    violations.len()
+                ))
+                .into());
+            }
+        }
+        Commands::Diff { base, other } => diff(base, other)?,
         Commands::Debug { cmd } => match cmd {
-            Debug::Inspect { code, module } => inspect(code, module)?,
-            Debug::Parse { module } => parse(module)?,
-            Debug::Globals { module, debug } => globals(module, debug)?,
-            Debug::Meta { module } => meta(module)?,
-            Debug::Transform { module, json } => transform(module, json)?,
+            Debug::Inspect { code, module, json } => inspect(
+                code,
+                module,
+                json,
+                Box::new(io::stdin()),
+                &mut io::stdout(),
+            )?,
+            Debug::Parse {
+                module,
+                threads,
+                continue_on_error,
+                stats,
+            } => parse(module, threads, continue_on_error, stats)?,
+            Debug::Globals {
+                module,
+                debug,
+                deep,
+                no_node_global,
+                loc,
+            } => globals(module, debug, deep, !no_node_global, loc)?,
+            Debug::Meta { module, indent } => meta(module, indent)?,
+            Debug::Transform {
+                module,
+                json,
+                source_map,
+                minify,
+                commonjs,
+                sort_imports,
+            } => transform(
+                module,
+                json,
+                source_map,
+                minify,
+                commonjs,
+                sort_imports,
+            )?,
         },
     }
     Ok(())
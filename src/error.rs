@@ -0,0 +1,103 @@
+//! Errors returned by the library functions.
+//!
+//! Internally errors are propagated using `anyhow`; the functions
+//! exported from the crate root convert to [BasaltError] so callers
+//! can match on the kind of error.
+//!
+//! Modules raise the typed variants where the error originates so
+//! the kind is preserved when the error is converted.
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::swc_utils::ParseError;
+
+/// Result type for the library functions.
+pub type Result<T> = std::result::Result<T, BasaltError>;
+
+/// Error returned by the library functions.
+#[derive(Debug)]
+pub enum BasaltError {
+    /// Failed to parse a module.
+    Parse(ParseError),
+    /// Failed to resolve a module specifier.
+    Resolve(String),
+    /// Invalid policy or a policy check failed.
+    Policy(String),
+    /// Invalid combination of options.
+    Usage(String),
+    /// Error reading or writing a file.
+    Io(io::Error),
+    /// Any other error.
+    Other(anyhow::Error),
+}
+
+impl BasaltError {
+    /// Error for a module path that is not a file.
+    pub fn not_a_file<P: AsRef<Path>>(path: P) -> Self {
+        BasaltError::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "module {} does not exist or is not a file",
+                path.as_ref().display()
+            ),
+        ))
+    }
+}
+
+impl fmt::Display for BasaltError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BasaltError::Parse(err) => write!(f, "{}", err),
+            BasaltError::Resolve(message)
+            | BasaltError::Policy(message)
+            | BasaltError::Usage(message) => write!(f, "{}", message),
+            BasaltError::Io(err) => write!(f, "{}", err),
+            BasaltError::Other(err) => write!(f, "{:#}", err),
+        }
+    }
+}
+
+impl std::error::Error for BasaltError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BasaltError::Parse(err) => Some(err),
+            BasaltError::Io(err) => Some(err),
+            BasaltError::Other(err) => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for BasaltError {
+    fn from(err: anyhow::Error) -> Self {
+        // Typed errors are recovered even when context was added,
+        // other errors keep any context message
+        let err = match err.downcast::<BasaltError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        match err.downcast::<ParseError>() {
+            Ok(err) => BasaltError::Parse(err),
+            Err(err) if err.chain().count() == 1 => {
+                match err.downcast::<io::Error>() {
+                    Ok(err) => BasaltError::Io(err),
+                    Err(err) => BasaltError::Other(err),
+                }
+            }
+            Err(err) => BasaltError::Other(err),
+        }
+    }
+}
+
+impl From<io::Error> for BasaltError {
+    fn from(err: io::Error) -> Self {
+        BasaltError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for BasaltError {
+    fn from(err: serde_json::Error) -> Self {
+        BasaltError::Other(err.into())
+    }
+}
@@ -49,21 +49,10 @@ pub fn pattern_words<'a>(pat: &'a Pat, names: &mut Vec<&'a JsWord>) {
                     ObjectPatProp::Assign(entry) => {
                         names.push(&entry.key.sym);
                     }
+                    // The key is a property name, eg: `{ key: value }`
+                    // only declares `value`
                     ObjectPatProp::KeyValue(entry) => {
-                        let will_recurse = match &*entry.value {
-                            Pat::Object(_) => true,
-                            Pat::Array(_) => true,
-                            _ => false,
-                        };
                         pattern_words(&*entry.value, names);
-                        if !will_recurse {
-                            match &entry.key {
-                                PropName::Ident(ident) => {
-                                    names.push(&ident.sym);
-                                }
-                                _ => {}
-                            }
-                        }
                     }
                     ObjectPatProp::Rest(entry) => {
                         pattern_words(&*entry.arg, names);
@@ -96,7 +85,7 @@ pub fn pattern_words<'a>(pat: &'a Pat, names: &mut Vec<&'a JsWord>) {
 /// caniuse-lite/data/features/background-img-opts.js
 /// ```
 ///
-/// Or the scoped specifier is trasformed to `@babel/runtime`:
+/// Or the scoped specifier is transformed to `@babel/runtime`:
 ///
 /// ```text
 /// @babel/runtime/helpers/typeof
@@ -131,22 +120,30 @@ pub fn is_module_exports(n: &PatOrExpr) -> bool {
             Pat::Ident(ident) => {
                 return ident.id.sym.as_ref() == EXPORTS;
             }
-            Pat::Expr(expr) => match &**expr {
-                Expr::Member(n) => {
-                    if let (ExprOrSuper::Expr(expr), Expr::Ident(prop)) =
-                        (&n.obj, &*n.prop)
-                    {
-                        if let Expr::Ident(obj) = &**expr {
-                            return obj.as_ref() == MODULE
-                                && prop.as_ref() == EXPORTS;
-                        }
-                    }
-                }
-                _ => {}
-            },
+            Pat::Expr(expr) => return is_module_exports_expr(expr),
             _ => {}
         },
-        _ => {}
+        PatOrExpr::Expr(expr) => return is_module_exports_expr(expr),
     }
     false
 }
+
+/// Determine if an expression is `exports` or `module.exports`.
+pub fn is_module_exports_expr(n: &Expr) -> bool {
+    match n {
+        Expr::Ident(ident) => ident.sym.as_ref() == EXPORTS,
+        Expr::Member(n) => {
+            if let (ExprOrSuper::Expr(expr), Expr::Ident(prop)) =
+                (&n.obj, &*n.prop)
+            {
+                if let Expr::Ident(obj) = &**expr {
+                    return !n.computed
+                        && obj.as_ref() == MODULE
+                        && prop.as_ref() == EXPORTS;
+                }
+            }
+            false
+        }
+        _ => false,
+    }
+}
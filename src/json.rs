@@ -0,0 +1,60 @@
+//! Helpers for writing JSON output.
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+
+/// Indentation for pretty printed JSON.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Indent {
+    /// Indent using a number of spaces.
+    Spaces(usize),
+    /// Indent using a tab character.
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(2)
+    }
+}
+
+impl FromStr for Indent {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "tab" => Ok(Indent::Tab),
+            _ => match s.parse::<usize>() {
+                Ok(spaces) => Ok(Indent::Spaces(spaces)),
+                Err(_) => bail!(
+                    "invalid indent {}, expected a number of spaces or tab",
+                    s
+                ),
+            },
+        }
+    }
+}
+
+impl Indent {
+    /// Get the bytes written for each level of indentation.
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Indent::Spaces(spaces) => vec![b' '; *spaces],
+            Indent::Tab => vec![b'\t'],
+        }
+    }
+}
+
+/// Serialize a value to pretty printed JSON using the given indentation.
+pub fn to_string_pretty<T: Serialize + ?Sized>(
+    value: &T,
+    indent: Indent,
+) -> Result<String> {
+    let indent = indent.bytes();
+    let mut out = Vec::new();
+    let formatter = PrettyFormatter::with_indent(&indent);
+    let mut serializer = Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(out)?)
+}
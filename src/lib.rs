@@ -4,39 +4,54 @@
 #![feature(once_cell)]
 #![deny(missing_docs)]
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::OpenOptions;
-use std::io::{self, prelude::*, Read};
+use std::io::{self, prelude::*};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
 
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Context};
 
 use swc::config::SourceMapsConfig;
-use swc_common::SourceMap;
+use swc_common::FileName;
 use swc_ecma_visit::VisitWith;
 
 pub mod access;
 pub mod bundler;
 pub mod cli;
+pub mod error;
 pub mod helpers;
+pub mod json;
 pub mod module;
 pub mod policy;
+pub mod preprocess;
 pub mod printer;
 pub mod static_module_record;
+pub mod stats;
 pub mod swc_utils;
 
+pub use error::BasaltError;
 pub use static_module_record::{
     Parser, StaticModuleRecordProgram, TransformSource,
 };
 
-use policy::{analysis::globals_scope::GlobalAnalysis, builder::PolicyBuilder};
+use error::Result;
+use policy::{
+    analysis::globals_scope::{
+        analyze_globals, analyze_globals_with_locations, GlobalAnalysis,
+        GlobalOptions,
+    },
+    builder::{BuiltinGranularity, PolicyBuilder},
+    Merge, Policy, PolicyFormat, UnusedGrants, Violation,
+};
+use stats::{time, Phase, Timings};
 
 /// Write a file and create the parent directory when necessary.
 fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(
     path: P,
     contents: C,
-) -> Result<()> {
+) -> anyhow::Result<()> {
     if let Some(parent) = path.as_ref().parent() {
         if !parent.exists() {
             std::fs::create_dir_all(parent)?;
@@ -50,7 +65,10 @@ fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(
 }
 
 /// Append sourceMappingURL to a target file.
-fn append_source_mapping_url<P: AsRef<Path>>(path: P, url: &str) -> Result<()> {
+fn append_source_mapping_url<P: AsRef<Path>>(
+    path: P,
+    url: &str,
+) -> anyhow::Result<()> {
     let url = format!("//# sourceMappingURL={}", url);
     let mut file = OpenOptions::new()
         .write(true)
@@ -69,28 +87,113 @@ fn append_source_mapping_url<P: AsRef<Path>>(path: P, url: &str) -> Result<()> {
     Ok(())
 }
 
-/// Generate a bundle.
-pub fn bundle(
-    module: PathBuf,
-    policy: Vec<PathBuf>,
-    output: Option<PathBuf>,
-    source_map_path: Option<PathBuf>,
-    source_map_url: Option<String>,
-) -> Result<()> {
+/// Get the canonical path for an optional project root.
+fn canonical_root(root: Option<PathBuf>) -> anyhow::Result<Option<PathBuf>> {
+    if let Some(root) = root {
+        Ok(Some(root.canonicalize().context(format!(
+            "unable to get canonical path for {}",
+            root.display()
+        ))?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Create a collector for the phase timings when stats are enabled.
+fn timings(stats: bool) -> Option<Arc<Timings>> {
+    if stats {
+        Some(Arc::new(Timings::new()))
+    } else {
+        None
+    }
+}
+
+/// Print the phase timings to stderr when stats are enabled.
+fn print_timings(timings: Option<Arc<Timings>>) {
+    if let Some(timings) = timings {
+        eprintln!("{}", timings);
+    }
+}
+
+/// Options for generating a bundle.
+#[derive(Debug, Default)]
+pub struct BundleArgs {
+    /// Entry point modules.
+    pub modules: Vec<PathBuf>,
+    /// Policy files for the bundle.
+    pub policy: Vec<PathBuf>,
+    /// Write the bundle to this file, otherwise print to stdout.
+    pub output: Option<PathBuf>,
+    /// Write the source map to this file.
+    pub source_map: Option<PathBuf>,
+    /// URL for the source map appended to the bundle file.
+    pub source_map_url: Option<String>,
+    /// Project root for module identifiers.
+    pub root: Option<PathBuf>,
+    /// Include the module content in module identifiers.
+    pub content_hash: bool,
+    /// Write the module graph as JSON to this file.
+    pub graph: Option<PathBuf>,
+    /// Print the time spent in each phase to stderr.
+    pub stats: bool,
+}
+
+/// Generate a bundle from one or more entry points.
+pub fn bundle(args: BundleArgs) -> Result<()> {
+    let BundleArgs {
+        modules,
+        policy,
+        output,
+        source_map: source_map_path,
+        source_map_url,
+        root,
+        content_hash,
+        graph: graph_path,
+        stats,
+    } = args;
+
     if policy.is_empty() {
-        bail!("bundle command requires some policy file(s) (use --policy)");
+        return Err(BasaltError::Usage(String::from(
+            "bundle command requires some policy file(s) (use --policy)",
+        )));
     }
 
-    let module = module.canonicalize().context(format!(
-        "unable to get canonical path for {}",
-        module.display()
-    ))?;
+    if modules.is_empty() {
+        return Err(BasaltError::Usage(String::from(
+            "bundle command requires an entry point (use --entry)",
+        )));
+    }
+
+    let modules = modules
+        .into_iter()
+        .map(|module| {
+            module.canonicalize().context(format!(
+                "unable to get canonical path for {}",
+                module.display()
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let root = canonical_root(root)?;
+    let timings = timings(stats);
+
+    let options = bundler::BundleOptions {
+        modules,
+        policy,
+        root,
+        content_hash,
+        timings: timings.clone(),
+        ..Default::default()
+    };
+    let (program, source_map, graph) = bundler::bundle(options)?;
+    if let Some(path) = &graph_path {
+        write_file(path, serde_json::to_string_pretty(&graph)?)?;
+    }
 
-    let options = bundler::BundleOptions { module, policy };
-    let (program, source_map) = bundler::bundle(options)?;
     let source_maps_config = SourceMapsConfig::Bool(true);
-    let result =
-        swc_utils::print(&program, source_map, None, None, source_maps_config)?;
+    let result = time(timings.as_deref(), Phase::Serialize, || {
+        swc_utils::print(&program, source_map, None, None, source_maps_config)
+    })?;
 
     if let Some(path) = &output {
         write_file(path, result.code)?;
@@ -122,72 +225,330 @@ pub fn bundle(
         }
     }
 
+    print_timings(timings);
+
     Ok(())
 }
 
 /// Inspect the AST for a string or file.
-pub fn inspect(code: Option<String>, file: Option<PathBuf>) -> Result<()> {
+///
+/// When `json` is set the AST is written as compact JSON otherwise
+/// the pretty debug representation is written.
+///
+/// The source is read from the input when the file is `-`.
+pub fn inspect<W: Write>(
+    code: Option<String>,
+    file: Option<PathBuf>,
+    json: bool,
+    input: Box<dyn Read>,
+    output: &mut W,
+) -> Result<()> {
     if code.is_some() && file.is_some() {
-        bail!(
-            "the --code and file options are mutually exclusive, choose one."
-        );
+        return Err(BasaltError::Usage(String::from(
+            "the --code and file options are mutually exclusive, choose one.",
+        )));
     } else {
-        if let Some(code) = code {
+        let is_stdin = file
+            .as_ref()
+            .map(|file| PathBuf::from("-") == *file)
+            .unwrap_or(false);
+        let module = if is_stdin {
+            let mut source = TransformSource::Reader {
+                reader: input,
+                file_name: String::from("stdin"),
+            };
+            let content = source.read()?.unwrap_or_default();
+            let (_, _, module) = swc_utils::load_code(
+                &content,
+                Some(FileName::Custom(String::from("stdin"))),
+                None,
+            )?;
+            Some(module)
+        } else if let Some(code) = code {
             let (_, _, module) = swc_utils::load_code(&code, None, None)?;
-            println!("{:#?}", module);
+            Some(module)
         } else if let Some(file) = file {
             let (_, _, module) = swc_utils::load_file(&file, None)?;
-            println!("{:#?}", module);
+            Some(module)
+        } else {
+            None
+        };
+        if let Some(module) = module {
+            if json {
+                writeln!(output, "{}", serde_json::to_string(&module)?)?;
+            } else {
+                writeln!(output, "{:#?}", module)?;
+            }
         }
     }
     Ok(())
 }
 
 /// Parse all the modules in a dependency graph.
-pub fn parse(file: PathBuf) -> Result<()> {
+///
+/// When continue on error is set dependencies that fail to resolve
+/// and modules that fail to parse are logged as warnings.
+///
+/// When stats is set the time spent in each phase is printed
+/// to stderr.
+pub fn parse(
+    file: PathBuf,
+    threads: Option<usize>,
+    continue_on_error: bool,
+    stats: bool,
+) -> Result<()> {
+    if !file.is_file() {
+        return Err(BasaltError::not_a_file(&file));
+    }
     let now = SystemTime::now();
-    let (parsed_modules, visited_modules) = module::parser::parse(file)?;
+    let timings = timings(stats);
+    let summary = module::parser::parse(
+        file,
+        threads,
+        continue_on_error,
+        timings.as_deref(),
+    )?;
+    for failure in summary.failures.iter() {
+        match &failure.specifier {
+            Some(spec) => log::warn!(
+                "Unresolved {} in {}: {}",
+                spec,
+                failure.file.display(),
+                failure.message
+            ),
+            None => log::warn!(
+                "Failed {}: {}",
+                failure.file.display(),
+                failure.message
+            ),
+        }
+    }
     if let Ok(t) = now.elapsed() {
-        log::debug!("Visited {} module(s)", visited_modules);
-        log::info!("Parsed {} module(s) in {:?}", parsed_modules, t);
+        log::debug!("Visited {} module(s)", summary.visited);
+        log::info!("Parsed {} module(s) in {:?}", summary.modules, t);
+        log::info!(
+            "Found {} import(s), {} builtin(s) and {} package(s)",
+            summary.imports,
+            summary.builtins.len(),
+            summary.packages.len()
+        );
     }
+    print_timings(timings);
     Ok(())
 }
 
+/// Options for generating a policy.
+#[derive(Debug, Default)]
+pub struct PolicyArgs {
+    /// Entry point module.
+    pub file: PathBuf,
+    /// Module names to treat as builtins in addition to the
+    /// node builtin modules.
+    pub builtins: Vec<String>,
+    /// Format for the policy.
+    pub format: PolicyFormat,
+    /// Indentation for JSON output.
+    pub indent: json::Indent,
+    /// Project root for file paths in warnings.
+    pub root: Option<PathBuf>,
+    /// Directory used to cache the analysis for each module.
+    pub cache_dir: Option<PathBuf>,
+    /// Granularity of the builtin entries.
+    pub builtin_granularity: BuiltinGranularity,
+    /// Print the time spent in each phase to stderr.
+    pub stats: bool,
+}
+
 /// Generate a policy file.
-pub fn policy(file: PathBuf) -> Result<()> {
+///
+/// The policy is printed in the given format, the indentation
+/// only applies to JSON output. When a root is given file paths
+/// in warnings are relative to the root.
+///
+/// When a cache directory is given the analysis for each module is
+/// cached so that only modules that have changed are analyzed.
+pub fn policy(args: PolicyArgs) -> Result<()> {
+    let PolicyArgs {
+        file,
+        builtins,
+        format,
+        indent,
+        root,
+        cache_dir,
+        builtin_granularity,
+        stats,
+    } = args;
+
     if !file.is_file() {
-        bail!("module {} does not exist or is not a file", file.display());
+        return Err(BasaltError::not_a_file(&file));
     }
 
-    let builder = PolicyBuilder::new(file);
+    let mut builder = PolicyBuilder::new(file)
+        .extra_builtins(builtins.into_iter().collect())
+        .builtin_granularity(builtin_granularity);
+    if let Some(root) = canonical_root(root)? {
+        builder = builder.root(root);
+    }
+    if let Some(cache_dir) = cache_dir {
+        builder = builder.cache_dir(cache_dir);
+    }
+    let timings = timings(stats);
+    if let Some(timings) = &timings {
+        builder = builder.timings(Arc::clone(timings));
+    }
     let policy = builder.load()?.analyze()?.finalize();
-    let policy_content = serde_json::to_string_pretty(&policy)?;
-    println!("{}", policy_content);
+    let policy_content = time(timings.as_deref(), Phase::Serialize, || {
+        format.serialize(&policy, indent)
+    })?;
+    println!("{}", policy_content.trim_end());
+
+    print_timings(timings);
 
     Ok(())
 }
 
-/// Print the dependency graph as a tree.
-pub fn tree(file: PathBuf, include_file: bool) -> Result<()> {
+/// Generate a policy for every file that matches the glob patterns
+/// and merge the results into a single policy.
+///
+/// Files matched by more than one pattern are only analyzed once;
+/// it is an error when no files match the patterns.
+pub fn policy_glob(patterns: Vec<String>) -> Result<Policy> {
+    let mut files = BTreeSet::new();
+    for pattern in patterns.iter() {
+        let paths = glob::glob(pattern).map_err(|e| {
+            BasaltError::Usage(format!(
+                "invalid glob pattern {}: {}",
+                pattern, e
+            ))
+        })?;
+        for path in paths {
+            let path = path.map_err(|e| e.into_error())?;
+            if path.is_file() {
+                files.insert(path.canonicalize()?);
+            }
+        }
+    }
+
+    if files.is_empty() {
+        return Err(BasaltError::Usage(format!(
+            "no files match the glob pattern(s) {}",
+            patterns.join(", ")
+        )));
+    }
+
+    let mut policy: Policy = Default::default();
+    for file in files {
+        let used = PolicyBuilder::new(file).load()?.analyze()?.finalize();
+        policy.merge(&used);
+    }
+    Ok(policy)
+}
+
+/// Check the capabilities used by a module against policy files.
+///
+/// The policy for the module is generated and compared with the
+/// merged policy files; any builtin, global or package access that
+/// is not granted is returned as a violation.
+pub fn check(module: PathBuf, policy: Vec<PathBuf>) -> Result<Vec<Violation>> {
+    let (granted, used) = load_check(module, policy, "check")?;
+    Ok(granted.violations(&used))
+}
+
+/// Audit policy files for grants that are not used by a module.
+///
+/// The policy for the module is generated and compared with the
+/// merged policy files; the result maps package names to the
+/// builtin, global and package entries that are granted but unused.
+pub fn audit(
+    module: PathBuf,
+    policy: Vec<PathBuf>,
+) -> Result<BTreeMap<String, UnusedGrants>> {
+    let (granted, used) = load_check(module, policy, "audit")?;
+    Ok(granted.unused(&used))
+}
+
+/// Load the merged policy files and generate the policy
+/// used by a module.
+fn load_check(
+    module: PathBuf,
+    policy: Vec<PathBuf>,
+    command: &str,
+) -> Result<(Policy, Policy)> {
+    if policy.is_empty() {
+        return Err(BasaltError::Usage(format!(
+            "{} command requires some policy file(s) (use --policy)",
+            command
+        )));
+    }
+
+    if !module.is_file() {
+        return Err(BasaltError::not_a_file(&module));
+    }
+
+    let granted = Policy::load_files(&policy)?;
+    let used = PolicyBuilder::new(module).load()?.analyze()?.finalize();
+    Ok((granted, used))
+}
+
+/// Print the difference between two policy files.
+///
+/// Returns an error when the other policy grants capabilities
+/// that are not granted by the base policy.
+pub fn diff(base: PathBuf, other: PathBuf) -> Result<()> {
+    let result = Policy::load_file(&base)?.diff(&Policy::load_file(&other)?);
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    if result.has_added_capabilities() {
+        return Err(BasaltError::Policy(format!(
+            "policy {} grants capabilities not in {}",
+            other.display(),
+            base.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Print the dependency graph.
+///
+/// When a root is given file paths are printed relative to the root;
+/// when annotate is set the globals and builtins used by each module
+/// are printed with the module.
+pub fn list(
+    file: PathBuf,
+    include_file: bool,
+    format: printer::PrintFormat,
+    cycles: bool,
+    root: Option<PathBuf>,
+    annotate: bool,
+) -> Result<()> {
     if !file.is_file() {
-        bail!("module {} does not exist or is not a file", file.display());
+        return Err(BasaltError::not_a_file(&file));
     }
-    let options = printer::PrintOptions { include_file };
+    let root = canonical_root(root)?;
+    let file = if root.is_some() {
+        file.canonicalize()?
+    } else {
+        file
+    };
+    let options = printer::PrintOptions {
+        include_file,
+        format,
+        cycles,
+        root,
+        annotate,
+    };
     let printer = printer::Printer::new();
     printer.print(file, &options)?;
     Ok(())
 }
 
-/// Print the static module record meta data as JSON.
-pub fn meta(file: PathBuf) -> Result<()> {
+/// Print the static module record meta data as JSON using
+/// the given indentation.
+pub fn meta(file: PathBuf, indent: json::Indent) -> Result<()> {
     if !file.is_file() {
-        bail!("module {} does not exist or is not a file", file.display());
+        return Err(BasaltError::not_a_file(&file));
     }
-    let mut parser = Parser::new();
-    let (_, _, module) = crate::swc_utils::load_file(file, None)?;
-    let smr = parser.parse(&module)?;
-    let contents = serde_json::to_string_pretty(&smr)?;
+    let meta = static_module_record::Parser::parse_file(&file)?;
+    let contents = json::to_string_pretty(&meta, indent)?;
     println!("{}", contents);
     Ok(())
 }
@@ -196,20 +557,43 @@ pub fn meta(file: PathBuf) -> Result<()> {
 ///
 /// By default it prints the global symbols in a module, if the
 /// debug option is given the scope tree is printed.
-pub fn globals(file: PathBuf, debug: bool) -> Result<()> {
+///
+/// When deep is set the full member paths are printed rather than
+/// flattening to the parent reference.
+///
+/// When ignore node global is set the node `global` keyword is
+/// treated as the global object so `global.foo` is printed as `foo`,
+/// otherwise `global` is printed as a global symbol.
+///
+/// When with locations is set each global is printed with the line
+/// and column for the first reference to the global.
+pub fn globals(
+    file: PathBuf,
+    debug: bool,
+    deep: bool,
+    ignore_node_global: bool,
+    with_locations: bool,
+) -> Result<()> {
     if !file.is_file() {
-        bail!("module {} does not exist or is not a file", file.display());
+        return Err(BasaltError::not_a_file(&file));
     }
 
-    let mut analyzer = GlobalAnalysis::new(Default::default());
-    let (_, _, module) = crate::swc_utils::load_file(&file, None)?;
-    module.visit_children_with(&mut analyzer);
+    let (_, source_map, module) = crate::swc_utils::load_file(&file, None)?;
+
+    let options = GlobalOptions::default()
+        .keep_member_paths(deep)
+        .ignore_node_global(ignore_node_global);
 
     if debug {
+        let mut analyzer = GlobalAnalysis::new(options);
+        module.visit_children_with(&mut analyzer);
         println!("{:#?}", analyzer);
+    } else if with_locations {
+        let globals =
+            analyze_globals_with_locations(&module, options, &source_map);
+        println!("{}", serde_json::to_string_pretty(&globals)?);
     } else {
-        let globals = analyzer.compute_globals();
-        let globals = analyzer.flatten_join(globals);
+        let globals = analyze_globals(&module, options);
         println!("{}", serde_json::to_string_pretty(&globals)?);
     }
 
@@ -217,41 +601,51 @@ pub fn globals(file: PathBuf, debug: bool) -> Result<()> {
 }
 
 /// Transform a module to a static module record program.
-pub fn transform(file: PathBuf, json: bool) -> Result<()> {
+///
+/// When source map is set the source map for the program is
+/// included in the JSON output; when minify is set the program
+/// is printed using minified formatting; when commonjs is set the
+/// program is a function that accepts `require`, `module` and `exports`;
+/// when sort imports is set the imports are sorted by module specifier.
+pub fn transform(
+    file: PathBuf,
+    json: bool,
+    source_map: bool,
+    minify: bool,
+    commonjs: bool,
+    sort_imports: bool,
+) -> Result<()> {
     let is_stdin = PathBuf::from("-") == file;
     if !file.is_file() && !is_stdin {
-        bail!("module {} does not exist or is not a file", file.display());
+        return Err(BasaltError::not_a_file(&file));
     }
 
     let source = if is_stdin {
-        let mut buffer = String::new();
-        let mut stdin = io::stdin();
-        stdin.read_to_string(&mut buffer)?;
-        TransformSource::Str {
-            content: buffer,
+        TransformSource::Reader {
+            reader: Box::new(io::stdin()),
             file_name: String::from("stdin"),
         }
     } else {
         TransformSource::File(file)
     };
 
-    let source_map: Arc<SourceMap> = Arc::new(Default::default());
-    let (meta, result) = static_module_record::transform(source, source_map)?;
+    let output_kind = if commonjs {
+        static_module_record::OutputKind::CommonJs
+    } else {
+        static_module_record::OutputKind::StaticModuleRecord
+    };
+    let options = static_module_record::TransformOptions {
+        source_map,
+        minify,
+        output_kind,
+        sort_imports,
+    };
+    let output =
+        static_module_record::transform_program_with_options(source, options)?;
     if json {
-        let output = StaticModuleRecordProgram {
-            meta,
-            program: trim_code(result.code),
-        };
         print!("{}", serde_json::to_string_pretty(&output)?);
     } else {
-        print!("{}", result.code);
+        print!("{}", output.program);
     }
     Ok(())
 }
-
-/// Helper to remove a trailing semi-colon from the generated functor program
-/// as a trailing semi-colon breaks static module record interoperability.
-fn trim_code(code: String) -> String {
-    let out = code.trim_end();
-    out.trim_end_matches(";").to_string()
-}
@@ -1,5 +1,5 @@
 //! Helper to determine the base path for a module.
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const PACKAGE: &str = "package.json";
 
@@ -15,3 +15,62 @@ pub fn module_base_directory(path: &PathBuf) -> Option<PathBuf> {
     }
     None
 }
+
+/// Attempt to find the name of the package that owns a module.
+///
+/// Walks up from the resolved path for the module to the nearest
+/// `package.json` that declares a `name`; nested `package.json` files
+/// without a `name` (for example, to set the module `type` for a
+/// sub-directory) are skipped.
+pub fn module_package_name(path: &Path) -> Option<String> {
+    module_package(path).and_then(|pkg| field(&pkg, "name"))
+}
+
+/// Attempt to find the version of the package that owns a module.
+///
+/// Uses the same `package.json` as [module_package_name].
+pub fn module_package_version(path: &Path) -> Option<String> {
+    module_package(path).and_then(|pkg| field(&pkg, "version"))
+}
+
+/// Find the nearest `package.json` that declares a `name`.
+fn module_package(path: &Path) -> Option<serde_json::Value> {
+    let mut parent = path.parent();
+    while let Some(p) = parent {
+        let pkg = p.join(PACKAGE);
+        if pkg.is_file() {
+            if let Some(value) = read_package(&pkg) {
+                if field(&value, "name").is_some() {
+                    return Some(value);
+                }
+            }
+        }
+        parent = p.parent();
+    }
+    None
+}
+
+/// Parse a `package.json` file.
+fn read_package(pkg: &Path) -> Option<serde_json::Value> {
+    let contents = std::fs::read_to_string(pkg).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Get a non-empty string field from a parsed `package.json`.
+fn field(pkg: &serde_json::Value, key: &str) -> Option<String> {
+    pkg.get(key)
+        .and_then(|value| value.as_str())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+}
+
+/// Make a path relative to a project root.
+///
+/// The path is returned unchanged when no root is given or the
+/// path is not within the root directory.
+pub fn root_relative(path: &Path, root: Option<&Path>) -> PathBuf {
+    match root.and_then(|root| path.strip_prefix(root).ok()) {
+        Some(relative) => relative.to_path_buf(),
+        None => path.to_path_buf(),
+    }
+}
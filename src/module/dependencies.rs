@@ -4,6 +4,14 @@
 //! Run `node -p "require('module').builtinModules"` to generate the
 //! list of built in packages for a version of node.
 //!
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::error::BasaltError;
+
+/// File extensions to try when resolving a local module.
+pub const EXTENSIONS: &'static [&'static str] = &["js", "mjs", "cjs", "json"];
 
 /// List of built in packages for latest stable node with LTS (node@16).
 pub const NODE_LATEST_STABLE: &'static [&'static str] = &[
@@ -89,3 +97,93 @@ pub fn is_local_module(s: &str) -> bool {
 pub fn is_dependent_module(s: &str) -> bool {
     !is_builtin_module(s) && !is_local_module(s)
 }
+
+/// Determine if a local module specifier refers to a directory
+/// with a `package.json` file.
+///
+/// These directories should be resolved using a resolver that
+/// honours the `main` field of the `package.json` file.
+pub fn is_package_directory<P: AsRef<Path>>(specifier: &str, from: P) -> bool {
+    if !is_local_module(specifier) {
+        return false;
+    }
+    let base = from.as_ref().parent().unwrap_or(from.as_ref());
+    let path = base.join(specifier);
+    path.is_dir() && path.join("package.json").is_file()
+}
+
+/// Normalize a path by removing `.` segments and resolving `..`
+/// segments against the preceding segment.
+///
+/// Symbolic links are not followed so that modules installed
+/// using a link remain inside the `node_modules` directory.
+pub fn normalize_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.as_ref().components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match result.components().last() {
+                Some(Component::Normal(_)) => {
+                    result.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => result.push(component),
+            },
+            _ => result.push(component),
+        }
+    }
+    result
+}
+
+/// Resolve a local module specifier relative to the file
+/// that declared the dependency.
+///
+/// Candidates are tried in the order used by node; the exact path,
+/// the path with each of the `EXTENSIONS` appended and finally an
+/// `index` file in a directory with each of the `EXTENSIONS`.
+///
+/// The resolved path is normalized so that a module has the same
+/// path however it was imported.
+///
+/// Package specifiers are not handled here and should be resolved
+/// by searching `node_modules`; neither are directories with a
+/// `package.json` file, see `is_package_directory()`.
+pub fn resolve<P: AsRef<Path>>(specifier: &str, from: P) -> Result<PathBuf> {
+    if !is_local_module(specifier) {
+        return Err(BasaltError::Resolve(format!(
+            "Cannot resolve {}, not a local module specifier",
+            specifier
+        ))
+        .into());
+    }
+
+    let base = from.as_ref().parent().unwrap_or(from.as_ref());
+    let path = normalize_path(base.join(specifier));
+
+    let mut candidates = vec![path.clone()];
+    for ext in EXTENSIONS {
+        let mut file = path.clone().into_os_string();
+        file.push(".");
+        file.push(ext);
+        candidates.push(PathBuf::from(file));
+    }
+    for ext in EXTENSIONS {
+        candidates.push(path.join(format!("index.{}", ext)));
+    }
+
+    if let Some(file) = candidates.iter().find(|p| p.is_file()) {
+        return Ok(file.to_path_buf());
+    }
+
+    let tried: Vec<String> = candidates
+        .iter()
+        .map(|p| format!("  {}", p.display()))
+        .collect();
+    Err(BasaltError::Resolve(format!(
+        "Failed to resolve {} from {}, tried:\n{}",
+        specifier,
+        from.as_ref().display(),
+        tried.join("\n")
+    ))
+    .into())
+}
@@ -4,3 +4,4 @@ pub mod base;
 pub mod dependencies;
 pub mod node;
 pub mod parser;
+pub mod resolver;
@@ -4,6 +4,7 @@
 //! iterator for resolving and loading dependencies.
 //!
 
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::{
     atomic::{AtomicU32, Ordering::SeqCst},
@@ -20,15 +21,23 @@ use swc_common::{
 use swc_ecma_ast::{Module, TargetEnv};
 use swc_ecma_dep_graph::{analyze_dependencies, DependencyDescriptor};
 
-use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
+use swc_ecma_loader::resolve::Resolve;
 
-use crate::module::dependencies::is_builtin_module;
-use crate::swc_utils::load_file;
+use crate::module::{
+    dependencies::{
+        is_builtin_module, is_local_module, is_package_directory,
+        normalize_path, resolve,
+    },
+    resolver::ExportsResolver,
+};
+use crate::stats::{time, Phase, Timings};
+use crate::swc_utils::{load_code, load_file};
 
 /// Counter of module ids.
 static COUNTER: SyncLazy<AtomicU32> = SyncLazy::new(|| AtomicU32::new(0));
 
-/// Cache of visited modules.
+/// Cache of visited modules keyed by canonical path so that a file
+/// reached using different paths is only parsed once.
 static CACHE: SyncLazy<DashMap<PathBuf, Arc<VisitedModule>>> =
     SyncLazy::new(|| DashMap::new());
 
@@ -38,6 +47,14 @@ pub fn cached_modules(
     &CACHE
 }
 
+/// Get the cache key for a file.
+///
+/// Uses the canonical path when the file exists so relative paths,
+/// `..` segments and symbolic links share the same cache entry.
+fn cache_key(file: &Path) -> PathBuf {
+    file.canonicalize().unwrap_or_else(|_| file.to_path_buf())
+}
+
 /// Stores the data for a visited module dependency.
 pub enum VisitedModule {
     /// A Javascript module.
@@ -90,25 +107,89 @@ pub fn parse_module<P: AsRef<Path>>(
     resolver: &Box<dyn Resolve>,
     source_map: Arc<SourceMap>,
 ) -> Result<Arc<VisitedModule>> {
-    let buf = file.as_ref().to_path_buf();
+    parse_module_with_timings(file, resolver, source_map, None)
+}
+
+/// Parse a module using a cached value when available and
+/// collect the time spent in each phase when timings are given.
+pub fn parse_module_with_timings<P: AsRef<Path>>(
+    file: P,
+    resolver: &Box<dyn Resolve>,
+    source_map: Arc<SourceMap>,
+    timings: Option<&Timings>,
+) -> Result<Arc<VisitedModule>> {
+    let buf = cache_key(file.as_ref());
     if let Some(entry) = CACHE.get(&buf) {
         let module = entry.value();
         return Ok(module.clone());
     }
 
-    let (file_name, _source_map, module) =
-        load_file(file.as_ref(), Some(source_map))?;
+    let (file_name, source_map, module) = time(timings, Phase::Parse, || {
+        load_file(file.as_ref(), Some(source_map))
+    })?;
+
+    let module = visit_module(
+        file_name,
+        Arc::new(module),
+        source_map,
+        resolver,
+        timings,
+    )?;
+    let entry = CACHE.entry(buf).or_insert(module);
+    Ok(entry.value().clone())
+}
 
+/// Parse source code as a module, analyze dependencies and resolve
+/// dependency file paths relative to the file path.
+///
+/// The file does not need to exist, it is only used to resolve
+/// dependencies. The module is not cached as the source may differ
+/// from the file on disc, dependencies are cached as usual.
+pub fn parse_source<P: AsRef<Path>>(
+    content: &str,
+    file: P,
+    resolver: &Box<dyn Resolve>,
+    source_map: Arc<SourceMap>,
+    timings: Option<&Timings>,
+) -> Result<Arc<VisitedModule>> {
+    let buf = file.as_ref().to_path_buf();
+    let (file_name, source_map, module) = time(timings, Phase::Parse, || {
+        load_code(content, Some(FileName::Real(buf)), Some(source_map))
+    })?;
+
+    let module = visit_module(
+        file_name,
+        Arc::new(module),
+        source_map,
+        resolver,
+        timings,
+    )?;
+    Ok(module)
+}
+
+/// Analyze and resolve the dependencies for a parsed module.
+fn visit_module(
+    file_name: FileName,
+    module: Arc<Module>,
+    source_map: Arc<SourceMap>,
+    resolver: &Box<dyn Resolve>,
+    timings: Option<&Timings>,
+) -> Result<Arc<VisitedModule>> {
     let id = COUNTER.fetch_add(1, SeqCst);
     let comments: SingleThreadedComments = Default::default();
     let mut node = ModuleNode {
-        module: Arc::new(module),
+        module,
+        source_map,
         dependencies: None,
         resolved: Default::default(),
+        all_resolved: Default::default(),
         id,
     };
-    node.analyze(&comments);
-    node.resolve(resolver, &file_name)?;
+    time(timings, Phase::Analyze, || node.analyze(&comments));
+    time(timings, Phase::Resolve, || {
+        node.resolve(resolver, &file_name)
+    })?;
+    node.all_resolved = node.resolved.clone();
 
     // Don't bother walking dependencies that have already
     // been visited.
@@ -121,39 +202,52 @@ pub fn parse_module<P: AsRef<Path>>(
         .into_iter()
         .filter(|(_, file_name)| {
             if let FileName::Real(module_path) = &file_name {
-                return CACHE.get(module_path).is_none();
+                return CACHE.get(&cache_key(module_path)).is_none();
             }
             true
         })
         .collect();
 
-    let module = Arc::new(VisitedModule::Module(file_name, node));
-    let entry = CACHE.entry(buf).or_insert(module);
-    Ok(entry.value().clone())
+    Ok(Arc::new(VisitedModule::Module(file_name, node)))
 }
 
 /// Parse a JSON file as a module.
 pub fn parse_json<P: AsRef<Path>>(
+    file: P,
+    resolver: &Box<dyn Resolve>,
+    source_map: Arc<SourceMap>,
+) -> Result<Arc<VisitedModule>> {
+    parse_json_with_timings(file, resolver, source_map, None)
+}
+
+/// Parse a JSON file as a module and collect the time spent
+/// parsing when timings are given.
+pub fn parse_json_with_timings<P: AsRef<Path>>(
     file: P,
     _resolver: &Box<dyn Resolve>,
     source_map: Arc<SourceMap>,
+    timings: Option<&Timings>,
 ) -> Result<Arc<VisitedModule>> {
-    let buf = file.as_ref().to_path_buf();
+    let buf = cache_key(file.as_ref());
     if let Some(entry) = CACHE.get(&buf) {
         let module = entry.value();
         return Ok(module.clone());
     }
 
     let id = COUNTER.fetch_add(1, SeqCst);
-    let source_file = source_map.load_file(file.as_ref())?;
     let file_name = FileName::Real(file.as_ref().to_path_buf());
-    let module = load_json_as_module(&source_file)?;
+    let module = time(timings, Phase::Parse, || -> Result<Module> {
+        let source_file = source_map.load_file(file.as_ref())?;
+        load_json_as_module(&source_file)
+    })?;
 
     let node = ModuleNode {
         id,
         module: Arc::new(module),
+        source_map,
         dependencies: None,
         resolved: vec![],
+        all_resolved: vec![],
     };
 
     let module = Arc::new(VisitedModule::Json(file_name, node));
@@ -166,6 +260,17 @@ pub fn parse_file<P: AsRef<Path>>(
     file: P,
     resolver: &Box<dyn Resolve>,
     source_map: Arc<SourceMap>,
+) -> Result<Arc<VisitedModule>> {
+    parse_file_with_timings(file, resolver, source_map, None)
+}
+
+/// Parse a file, analyze dependencies and resolve dependency file paths
+/// collecting the time spent in each phase when timings are given.
+pub fn parse_file_with_timings<P: AsRef<Path>>(
+    file: P,
+    resolver: &Box<dyn Resolve>,
+    source_map: Arc<SourceMap>,
+    timings: Option<&Timings>,
 ) -> Result<Arc<VisitedModule>> {
     let extension = file
         .as_ref()
@@ -174,25 +279,83 @@ pub fn parse_file<P: AsRef<Path>>(
     if let Some(ref extension) = extension {
         let extension = &extension[..];
         match extension {
-            "json" => parse_json(file, resolver, source_map),
-            _ => parse_module(file, resolver, source_map),
+            "json" => {
+                parse_json_with_timings(file, resolver, source_map, timings)
+            }
+            _ => parse_module_with_timings(file, resolver, source_map, timings),
         }
     } else {
-        parse_module(file, resolver, source_map)
+        parse_module_with_timings(file, resolver, source_map, timings)
     }
 }
 
+/// Resolve the file name for a dependency specifier declared
+/// by the module with the base file name.
+///
+/// Local modules are resolved relative to the base file and other
+/// specifiers are resolved using the resolver; local directories
+/// with a `package.json` file are also resolved using the resolver
+/// so that the `main` field is honoured.
+pub fn resolve_dependency(
+    resolver: &Box<dyn Resolve>,
+    base: &FileName,
+    spec: &str,
+) -> Result<FileName> {
+    Ok(match base {
+        FileName::Real(from) if is_local_module(spec) => {
+            if is_package_directory(spec, from) {
+                match resolver
+                    .resolve(base, spec)
+                    .context(format!("Failed to resolve module for {}", spec))?
+                {
+                    FileName::Real(path) => {
+                        FileName::Real(normalize_path(path))
+                    }
+                    file_name => file_name,
+                }
+            } else {
+                FileName::Real(resolve(spec, from)?)
+            }
+        }
+        _ => resolver
+            .resolve(base, spec)
+            .context(format!("Failed to resolve module for {}", spec))?,
+    })
+}
+
 /// Encapsulates a module and it's dependencies.
-#[derive(Debug)]
 pub struct ModuleNode {
     /// The module identifier.
     pub id: u32,
     /// The underlying module AST node.
     pub module: Arc<Module>,
+    /// The source map used to parse the module.
+    ///
+    /// Spans for the module are positions in this source map which
+    /// may not be the source map of the caller when the module was
+    /// cached by a previous parse.
+    pub source_map: Arc<SourceMap>,
     /// The parsed dependencies of this module.
     pub dependencies: Option<Vec<DependencyDescriptor>>,
     /// The resolved paths for the dependencies.
+    ///
+    /// Dependencies that were already cached when this module was
+    /// parsed are not included so they are not walked again.
     pub resolved: Vec<(String, FileName)>,
+    /// The resolved paths for every dependency of this module.
+    pub all_resolved: Vec<(String, FileName)>,
+}
+
+impl fmt::Debug for ModuleNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModuleNode")
+            .field("id", &self.id)
+            .field("module", &self.module)
+            .field("dependencies", &self.dependencies)
+            .field("resolved", &self.resolved)
+            .field("all_resolved", &self.all_resolved)
+            .finish()
+    }
 }
 
 impl ModuleNode {
@@ -208,29 +371,44 @@ impl ModuleNode {
         resolver: &Box<dyn Resolve>,
         base: &FileName,
     ) -> Result<()> {
+        let resolved = self.resolve_dependencies(resolver, base)?;
+        self.resolved.extend(resolved);
+        Ok(())
+    }
+
+    /// Resolve the file names for every direct dependency of this module.
+    ///
+    /// Unlike `resolve()` this does not modify the list of resolved
+    /// dependencies and includes modules that have already been cached.
+    pub fn resolve_dependencies(
+        &self,
+        resolver: &Box<dyn Resolve>,
+        base: &FileName,
+    ) -> Result<Vec<(String, FileName)>> {
+        let mut resolved = Vec::new();
         if let Some(deps) = &self.dependencies {
             for dep in deps {
                 let spec = format!("{}", dep.specifier);
-                let file_name = resolver.resolve(base, &spec).context(
-                    format!("Failed to resolve module for {}", &spec),
-                )?;
-                self.resolved.push((spec, file_name));
+                let file_name = resolve_dependency(resolver, base, &spec)?;
+                resolved.push((spec, file_name));
             }
         }
-        Ok(())
+        Ok(resolved)
     }
 
     /// Iterate the resolved dependencies of this module and
     /// attempt to load a module for each resolved dependency.
-    fn iter<'a>(&'a self, source_map: Arc<SourceMap>) -> NodeIterator<'a> {
+    fn iter<'a>(
+        &'a self,
+        source_map: Arc<SourceMap>,
+        timings: Option<&'a Timings>,
+    ) -> NodeIterator<'a> {
         NodeIterator {
             node: self,
             index: 0,
-            resolver: Box::new(NodeModulesResolver::new(
-                TargetEnv::Node,
-                Default::default(),
-            )),
+            resolver: Box::new(ExportsResolver::new(TargetEnv::Node)),
             source_map,
+            timings,
         }
     }
 
@@ -240,6 +418,20 @@ impl ModuleNode {
         source_map: Arc<SourceMap>,
         callback: &mut F,
     ) -> Result<()>
+    where
+        F: FnMut(VisitedDependency) -> Result<()>,
+    {
+        self.visit_with_timings(source_map, None, callback)
+    }
+
+    /// Visit all dependencies of this node recursively collecting
+    /// the time spent in each phase when timings are given.
+    pub fn visit_with_timings<F>(
+        &self,
+        source_map: Arc<SourceMap>,
+        timings: Option<&Timings>,
+        callback: &mut F,
+    ) -> Result<()>
     where
         F: FnMut(VisitedDependency) -> Result<()>,
     {
@@ -247,7 +439,7 @@ impl ModuleNode {
             open: Vec::new(),
             parents: Vec::new(),
         };
-        self.visit_all(self, &mut state, source_map, callback)
+        self.visit_all(self, &mut state, source_map, timings, callback)
     }
 
     fn visit_all<F>(
@@ -255,6 +447,7 @@ impl ModuleNode {
         node: &ModuleNode,
         state: &mut VisitState,
         source_map: Arc<SourceMap>,
+        timings: Option<&Timings>,
         callback: &mut F,
     ) -> Result<()>
     where
@@ -262,7 +455,7 @@ impl ModuleNode {
     {
         state.open.push(BranchState { last: false });
 
-        for res in node.iter(Arc::clone(&source_map)) {
+        for res in node.iter(Arc::clone(&source_map), timings) {
             let (i, spec, parsed) = res?;
             let last = i == (node.resolved.len() - 1);
             state.open.last_mut().unwrap().last = last;
@@ -299,6 +492,7 @@ impl ModuleNode {
                         &dep,
                         state,
                         Arc::clone(&source_map),
+                        timings,
                         callback,
                     )?;
                     state.parents.pop();
@@ -318,6 +512,7 @@ pub struct NodeIterator<'a> {
     resolver: Box<dyn Resolve>,
     index: usize,
     source_map: Arc<SourceMap>,
+    timings: Option<&'a Timings>,
 }
 
 impl<'a> Iterator for NodeIterator<'a> {
@@ -332,10 +527,11 @@ impl<'a> Iterator for NodeIterator<'a> {
 
             match &resolved.1 {
                 FileName::Real(file_name) => {
-                    return match parse_file(
+                    return match parse_file_with_timings(
                         file_name,
                         &self.resolver,
                         Arc::clone(&self.source_map),
+                        self.timings,
                     ) {
                         Ok(parsed) => Some(Ok((
                             self.index - 1,
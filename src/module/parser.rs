@@ -1,42 +1,337 @@
 //! Helper to parse all modules in a dependency graph for performance timing purposes.
-use std::path::Path;
-use std::sync::Arc;
+//!
+//! Modules are parsed concurrently one level of the dependency graph
+//! at a time; modules that have already been seen are skipped so each
+//! module is only parsed once.
+//!
+//! When parsing continues on error the dependencies that fail to
+//! resolve and modules that fail to parse are collected as failures
+//! and the rest of the graph is parsed.
+//!
+//! Circular dependencies can be found using `detect_cycles()`.
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering::SeqCst},
+    Arc, Mutex,
+};
 
 use anyhow::Result;
+use dashmap::DashSet;
+use rayon::{prelude::*, ThreadPoolBuilder};
+use serde::Serialize;
 
-use swc_common::SourceMap;
-use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
+use swc_common::{FileName, SourceMap};
+use swc_ecma_ast::TargetEnv;
+use swc_ecma_loader::resolve::Resolve;
 
-use crate::module::node::{
-    cached_modules, parse_file, VisitedDependency, VisitedModule,
+use crate::{
+    helpers::normalize_specifier,
+    module::{
+        dependencies::{is_builtin_module, is_dependent_module},
+        node::{
+            parse_file, parse_file_with_timings, resolve_dependency,
+            ModuleNode, VisitedModule,
+        },
+        resolver::ExportsResolver,
+    },
+    stats::{time, Phase, Timings},
+    swc_utils::load_file,
 };
 
+/// Summary of a parsed dependency graph.
+#[derive(Debug, Default, Serialize)]
+pub struct ParseSummary {
+    /// Number of parsed modules.
+    pub modules: usize,
+    /// Number of dependencies that were visited.
+    pub visited: usize,
+    /// Number of import declarations and calls to `require()`
+    /// in the parsed modules.
+    pub imports: usize,
+    /// Builtin modules referenced by the parsed modules.
+    pub builtins: BTreeSet<String>,
+    /// Unique external packages referenced by the parsed modules.
+    pub packages: BTreeSet<String>,
+    /// Failures collected when parsing continues on error.
+    pub failures: Vec<ParseFailure>,
+}
+
+/// Module that failed to parse or a dependency that failed to resolve.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseFailure {
+    /// The module being parsed.
+    pub file: PathBuf,
+    /// Specifier for a dependency that failed to resolve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub specifier: Option<String>,
+    /// Reason for the failure.
+    pub message: String,
+}
+
+/// Dependency information collected whilst parsing.
+#[derive(Default)]
+struct Collector {
+    seen: DashSet<PathBuf>,
+    modules: AtomicUsize,
+    visited: AtomicUsize,
+    imports: AtomicUsize,
+    builtins: DashSet<String>,
+    packages: DashSet<String>,
+    failures: Mutex<Vec<ParseFailure>>,
+}
+
+impl Collector {
+    fn fail(&self, file: &Path, specifier: Option<String>, message: String) {
+        self.failures.lock().unwrap().push(ParseFailure {
+            file: file.to_path_buf(),
+            specifier,
+            message,
+        });
+    }
+}
+
 /// Parse all the modules in a dependency graph.
-pub fn parse<P: AsRef<Path>>(file: P) -> Result<(usize, usize)> {
-    let resolver: Box<dyn Resolve> = Box::new(NodeModulesResolver::default());
+///
+/// The number of worker threads may be given otherwise the
+/// default for the thread pool is used.
+///
+/// Returns a summary including the number of parsed modules and
+/// the number of dependencies that were visited.
+///
+/// When continue on error is set a dependency that fails to resolve
+/// or a module that fails to parse is added to the failures in the
+/// summary instead of returning an error.
+///
+/// When timings are given the time spent in each phase is collected.
+pub fn parse<P: AsRef<Path>>(
+    file: P,
+    threads: Option<usize>,
+    continue_on_error: bool,
+    timings: Option<&Timings>,
+) -> Result<ParseSummary> {
+    let mut builder = ThreadPoolBuilder::new();
+    if let Some(threads) = threads {
+        builder = builder.num_threads(threads);
+    }
+    let pool = builder.build()?;
+
+    // Canonicalize the entry so it matches the resolved paths
+    // of any dependencies that import it
+    let file = file.as_ref().canonicalize()?;
     let source_map: Arc<SourceMap> = Arc::new(Default::default());
-    let module = parse_file(file.as_ref(), &resolver, Arc::clone(&source_map))?;
+    let resolver: Box<dyn Resolve> =
+        Box::new(ExportsResolver::new(TargetEnv::Node));
+    let collector: Collector = Default::default();
+    collector.seen.insert(file.clone());
+
+    let mut pending = vec![file];
+    while !pending.is_empty() {
+        let next: Result<Vec<Vec<PathBuf>>> = pool.install(|| {
+            pending
+                .par_iter()
+                .map(|file| {
+                    visit(
+                        file,
+                        &resolver,
+                        &source_map,
+                        &collector,
+                        continue_on_error,
+                        timings,
+                    )
+                })
+                .collect()
+        });
+        pending = next?.into_iter().flatten().collect();
+    }
 
-    let node = match &*module {
-        VisitedModule::Module(_, node) => Some(node),
-        VisitedModule::Json(_, node) => Some(node),
-        VisitedModule::Builtin(_) => None,
+    let mut failures = collector.failures.into_inner().unwrap();
+    failures
+        .sort_by(|a, b| (&a.file, &a.specifier).cmp(&(&b.file, &b.specifier)));
+
+    Ok(ParseSummary {
+        modules: collector.modules.load(SeqCst),
+        visited: collector.visited.load(SeqCst),
+        imports: collector.imports.load(SeqCst),
+        builtins: collector.builtins.into_iter().collect(),
+        packages: collector.packages.into_iter().collect(),
+        failures,
+    })
+}
+
+/// Parse a module and get the dependencies that have not been seen yet.
+fn visit(
+    file: &Path,
+    resolver: &Box<dyn Resolve>,
+    source_map: &Arc<SourceMap>,
+    collector: &Collector,
+    continue_on_error: bool,
+    timings: Option<&Timings>,
+) -> Result<Vec<PathBuf>> {
+    match parse_file_with_timings(
+        file,
+        resolver,
+        Arc::clone(source_map),
+        timings,
+    ) {
+        Ok(module) => {
+            collector.modules.fetch_add(1, SeqCst);
+            Ok(dependencies(&*module, collector))
+        }
+        Err(_) if continue_on_error => Ok(visit_partial(
+            file, resolver, source_map, collector, timings,
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parse a module resolving each dependency individually so that
+/// the dependencies which do resolve are still visited.
+///
+/// The module is not cached as the resolved dependencies are incomplete.
+fn visit_partial(
+    file: &Path,
+    resolver: &Box<dyn Resolve>,
+    source_map: &Arc<SourceMap>,
+    collector: &Collector,
+    timings: Option<&Timings>,
+) -> Vec<PathBuf> {
+    let (file_name, source_map, module) =
+        match time(timings, Phase::Parse, || {
+            load_file(file, Some(Arc::clone(source_map)))
+        }) {
+            Ok(result) => result,
+            Err(e) => {
+                collector.fail(file, None, format!("{:#}", e));
+                return vec![];
+            }
+        };
+    collector.modules.fetch_add(1, SeqCst);
+
+    let mut node = ModuleNode {
+        id: 0,
+        module: Arc::new(module),
+        source_map,
+        dependencies: None,
+        resolved: vec![],
+        all_resolved: vec![],
     };
+    time(timings, Phase::Analyze, || {
+        node.analyze(&Default::default())
+    });
 
-    let mut visited_count = 0;
+    if let Some(deps) = &node.dependencies {
+        for dep in deps {
+            let spec = dep.specifier.to_string();
+            let resolved = time(timings, Phase::Resolve, || {
+                resolve_dependency(resolver, &file_name, &spec)
+            });
+            match resolved {
+                Ok(resolved) => {
+                    node.resolved.push((spec.clone(), resolved.clone()));
+                    node.all_resolved.push((spec, resolved));
+                }
+                Err(e) => collector.fail(file, Some(spec), format!("{:#}", e)),
+            }
+        }
+    }
 
-    // Visitor is a noop
-    let mut visitor = |_dep: VisitedDependency| {
-        visited_count += 1;
-        Ok(())
+    dependencies(&VisitedModule::Module(file_name, node), collector)
+}
+
+/// Get the dependencies of a module that have not been seen yet.
+fn dependencies(module: &VisitedModule, collector: &Collector) -> Vec<PathBuf> {
+    let node = match module {
+        VisitedModule::Module(_, node) => node,
+        VisitedModule::Json(_, node) => node,
+        VisitedModule::Builtin(_) => return vec![],
     };
 
-    if let Some(node) = node {
-        node.visit(source_map, &mut visitor)?;
+    if let Some(deps) = &node.dependencies {
+        collector.imports.fetch_add(deps.len(), SeqCst);
+        for dep in deps {
+            let spec = dep.specifier.as_ref();
+            if is_builtin_module(spec) {
+                collector.builtins.insert(spec.to_string());
+            } else if is_dependent_module(spec) {
+                collector.packages.insert(normalize_specifier(spec));
+            }
+        }
     }
 
-    // WTF: Visited 29146348 modules!
-    //eprintln!("Visited {} modules!", visited_count);
+    // The resolved list excludes cached modules so walk all
+    // the resolved dependencies, the seen set skips duplicates
+    node.all_resolved
+        .iter()
+        .filter_map(|(_, file_name)| {
+            collector.visited.fetch_add(1, SeqCst);
+            if let FileName::Real(path) = file_name {
+                // Only the first thread to insert the path parses the module
+                if collector.seen.insert(path.clone()) {
+                    return Some(path.clone());
+                }
+            }
+            None
+        })
+        .collect()
+}
 
-    Ok((cached_modules().len(), visited_count))
+/// Find the circular dependencies in a dependency graph.
+///
+/// Each cycle is the list of module paths that form the cycle in
+/// import order; the last module in the list imports the first module.
+pub fn detect_cycles<P: AsRef<Path>>(file: P) -> Result<Vec<Vec<PathBuf>>> {
+    let mut search = CycleSearch {
+        resolver: Box::new(ExportsResolver::new(TargetEnv::Node)),
+        source_map: Arc::new(Default::default()),
+        stack: Vec::new(),
+        done: HashSet::new(),
+        cycles: Vec::new(),
+    };
+    search.visit(file.as_ref().to_path_buf())?;
+    Ok(search.cycles)
+}
+
+/// Depth first search of a dependency graph that records
+/// an edge back to a module on the stack as a cycle.
+struct CycleSearch {
+    resolver: Box<dyn Resolve>,
+    source_map: Arc<SourceMap>,
+    stack: Vec<PathBuf>,
+    done: HashSet<PathBuf>,
+    cycles: Vec<Vec<PathBuf>>,
+}
+
+impl CycleSearch {
+    fn visit(&mut self, file: PathBuf) -> Result<()> {
+        if let Some(pos) = self.stack.iter().position(|p| p == &file) {
+            self.cycles.push(self.stack[pos..].to_vec());
+            return Ok(());
+        }
+        if self.done.contains(&file) {
+            return Ok(());
+        }
+
+        let module =
+            parse_file(&file, &self.resolver, Arc::clone(&self.source_map))?;
+        // Resolve all the dependencies as the resolved list
+        // on the node excludes modules that were already cached
+        let dependencies = match &*module {
+            VisitedModule::Module(file_name, node)
+            | VisitedModule::Json(file_name, node) => {
+                node.resolve_dependencies(&self.resolver, file_name)?
+            }
+            VisitedModule::Builtin(_) => vec![],
+        };
+
+        self.stack.push(file.clone());
+        for (_, file_name) in dependencies {
+            if let FileName::Real(path) = file_name {
+                self.visit(path)?;
+            }
+        }
+        self.stack.pop();
+        self.done.insert(file);
+        Ok(())
+    }
 }
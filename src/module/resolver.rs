@@ -0,0 +1,239 @@
+//! Resolver that honours the `exports` field of `package.json`.
+//!
+//! Packages that declare `exports` are resolved using the subpath
+//! and condition rules that node uses; other specifiers and packages
+//! without `exports` are delegated to the node modules resolver.
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use swc_common::FileName;
+use swc_ecma_ast::TargetEnv;
+use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
+
+use crate::{
+    error::BasaltError,
+    helpers::normalize_specifier,
+    module::dependencies::{is_builtin_module, is_dependent_module},
+};
+
+const NODE_MODULES: &str = "node_modules";
+const PACKAGE: &str = "package.json";
+
+/// Conditions matched by default, these select the ES module
+/// entry points of a package.
+pub const DEFAULT_CONDITIONS: &'static [&'static str] = &["import", "default"];
+
+/// The `exports` field of a `package.json` file.
+///
+/// Maps preserve key order as the first matching condition wins.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Exports {
+    Target(String),
+    Fallback(Vec<Exports>),
+    Map(IndexMap<String, Exports>),
+    Null,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    exports: Option<Exports>,
+}
+
+/// Resolve module specifiers using the package `exports` field.
+pub struct ExportsResolver {
+    conditions: Vec<String>,
+    inner: NodeModulesResolver,
+}
+
+impl ExportsResolver {
+    /// Create a resolver for a target environment using the
+    /// default conditions.
+    pub fn new(target_env: TargetEnv) -> Self {
+        Self::from_resolver(NodeModulesResolver::new(
+            target_env,
+            Default::default(),
+        ))
+    }
+
+    fn from_resolver(inner: NodeModulesResolver) -> Self {
+        Self {
+            conditions: DEFAULT_CONDITIONS
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            inner,
+        }
+    }
+
+    /// Set the conditions to match in conditional exports.
+    pub fn conditions(mut self, conditions: Vec<String>) -> Self {
+        self.conditions = conditions;
+        self
+    }
+
+    /// Find the directory for a package by walking up from the
+    /// directory of the importing module.
+    fn package_directory(&self, base: &Path, name: &str) -> Option<PathBuf> {
+        let mut parent = base.parent();
+        while let Some(p) = parent {
+            let dir = p.join(NODE_MODULES).join(name);
+            if dir.join(PACKAGE).is_file() {
+                return Some(dir);
+            }
+            parent = p.parent();
+        }
+        None
+    }
+
+    /// Resolve a package subpath using the `exports` field.
+    ///
+    /// Returns `None` when the package does not declare `exports`.
+    fn resolve_exports(
+        &self,
+        dir: &Path,
+        spec: &str,
+        subpath: &str,
+    ) -> Result<Option<PathBuf>> {
+        let contents = std::fs::read_to_string(dir.join(PACKAGE))?;
+        let package: Package = serde_json::from_str(&contents)?;
+        let exports = match package.exports {
+            Some(exports) => exports,
+            None => return Ok(None),
+        };
+
+        let target = match &exports {
+            Exports::Map(map) if map.keys().any(|k| k.starts_with('.')) => {
+                self.match_subpath(dir, map, subpath)
+            }
+            // Shorthand for the main entry point
+            _ if subpath == "." => self.resolve_target(dir, &exports, None),
+            _ => None,
+        };
+
+        match target {
+            Some(path) => Ok(Some(path.canonicalize()?)),
+            None => Err(BasaltError::Resolve(format!(
+                "Package subpath {} is not exported from {} ({})",
+                subpath,
+                spec,
+                dir.join(PACKAGE).display()
+            ))
+            .into()),
+        }
+    }
+
+    /// Match a subpath against the keys of an exports map, exact
+    /// matches take precedence over the longest matching pattern.
+    fn match_subpath(
+        &self,
+        dir: &Path,
+        map: &IndexMap<String, Exports>,
+        subpath: &str,
+    ) -> Option<PathBuf> {
+        match map.get(subpath) {
+            Some(target) if !subpath.contains('*') => {
+                self.resolve_target(dir, target, None)
+            }
+            _ => self.longest_pattern(dir, map, subpath),
+        }
+    }
+
+    /// Resolve the pattern with the longest prefix that matches
+    /// a subpath.
+    fn longest_pattern(
+        &self,
+        dir: &Path,
+        map: &IndexMap<String, Exports>,
+        subpath: &str,
+    ) -> Option<PathBuf> {
+        let (prefix, suffix, target) = map
+            .iter()
+            .filter_map(|(key, target)| {
+                let (prefix, suffix) = key.split_once('*')?;
+                if subpath.len() >= key.len()
+                    && subpath.starts_with(prefix)
+                    && subpath.ends_with(suffix)
+                {
+                    Some((prefix, suffix, target))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(prefix, _, _)| prefix.len())?;
+        let replacement = &subpath[prefix.len()..subpath.len() - suffix.len()];
+        self.resolve_target(dir, target, Some(replacement))
+    }
+
+    /// Resolve an exports target to a file in the package.
+    fn resolve_target(
+        &self,
+        dir: &Path,
+        target: &Exports,
+        replacement: Option<&str>,
+    ) -> Option<PathBuf> {
+        match target {
+            Exports::Target(target) => {
+                // Targets must be relative to the package directory
+                if !target.starts_with("./") {
+                    return None;
+                }
+                let target = match replacement {
+                    Some(replacement) => target.replace('*', replacement),
+                    None => target.clone(),
+                };
+                let path = dir.join(target);
+                if path.is_file() {
+                    Some(path)
+                } else {
+                    None
+                }
+            }
+            Exports::Fallback(targets) => targets
+                .iter()
+                .find_map(|t| self.resolve_target(dir, t, replacement)),
+            Exports::Map(conditions) => {
+                conditions.iter().find_map(|(condition, target)| {
+                    if self.conditions.contains(condition) {
+                        self.resolve_target(dir, target, replacement)
+                    } else {
+                        None
+                    }
+                })
+            }
+            Exports::Null => None,
+        }
+    }
+}
+
+impl Default for ExportsResolver {
+    fn default() -> Self {
+        Self::from_resolver(NodeModulesResolver::default())
+    }
+}
+
+impl Resolve for ExportsResolver {
+    fn resolve(&self, base: &FileName, spec: &str) -> Result<FileName> {
+        if let FileName::Real(base) = base {
+            if is_dependent_module(spec) && !is_builtin_module(spec) {
+                let name = normalize_specifier(spec);
+                if let Some(dir) = self.package_directory(base, &name) {
+                    let subpath = if spec.len() > name.len() {
+                        format!(".{}", &spec[name.len()..])
+                    } else {
+                        String::from(".")
+                    };
+                    if let Some(path) =
+                        self.resolve_exports(&dir, spec, &subpath)?
+                    {
+                        return Ok(FileName::Real(path));
+                    }
+                }
+            }
+        }
+        self.inner.resolve(base, spec)
+    }
+}
@@ -22,17 +22,20 @@
 //! ```
 //!
 //! Will only yield the entire `process` builtin and not the full path (`process.env.FOO`).
+//! The scope builder records the location of `with` statements so the
+//! policy builder can warn that a generated policy may be incomplete.
 //!
 use swc_atoms::JsWord;
 use swc_ecma_ast::*;
 use swc_ecma_visit::{Node, Visit, VisitWith};
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 
 use crate::{
     access::{Access, AccessKind},
     policy::analysis::{
-        dynamic_import::is_require_expr, member_expr::member_expr_words,
+        dynamic_import::is_require_expr, join_words,
+        member_expr::member_expr_words,
     },
 };
 
@@ -41,77 +44,95 @@ use super::scope_builder::{Builtin, Local};
 const FUNCTION_METHODS: [&str; 5] =
     ["call", "apply", "bind", "toSource", "toString"];
 
-/// Visit a module and generate the set of access
-/// to builtin packages.
-#[derive(Default)]
-#[deprecated(note = "Merged in with scope builder, use scope builder instead")]
-pub struct BuiltinAnalysis;
-
-impl BuiltinAnalysis {
-    /// Analyze and compute the builtins for a module.
-    pub fn analyze(
-        &self,
-        module: &Module,
-        candidates: Vec<Builtin>,
-    ) -> IndexMap<JsWord, Access> {
-        let mut analyzer = BuiltinAnalyzer {
-            candidates,
-            access: Default::default(),
-        };
-
-        module.visit_children_with(&mut analyzer);
+/// Flatten the access for builtin paths into dot-delimited keys.
+///
+/// Deep properties are removed when a parent path exists and the
+/// parent access is updated with any flags set on the child property.
+pub fn flatten_access(
+    map: IndexMap<Vec<JsWord>, Access>,
+) -> IndexMap<JsWord, Access> {
+    let compare = map.clone();
+    let mut updated: IndexMap<Vec<JsWord>, Access> = Default::default();
+    let mut result: IndexMap<Vec<JsWord>, Access> = map
+        .into_iter()
+        .filter(|(k, access)| {
+            for (key, parent_access) in compare.iter() {
+                if key.len() < k.len() && k.starts_with(key) {
+                    let mut new_access = *parent_access;
+                    new_access.merge(access);
+                    updated.insert(key.clone(), new_access);
+                    return false;
+                }
+            }
+            true
+        })
+        .collect();
 
-        self.compute(self.filter(analyzer.access))
+    // Overwrite with updated access flags
+    for (k, v) in updated {
+        result.insert(k, v);
     }
 
-    /// Filter the list of access removing deep properties when an existing
-    /// parent object already exists.
-    ///
-    /// The parent access is updated with any flags set on the child property access.
-    fn filter(
-        &self,
-        map: IndexMap<Vec<JsWord>, Access>,
-    ) -> IndexMap<Vec<JsWord>, Access> {
-        let compare = map.clone();
-        let mut updated: IndexMap<Vec<JsWord>, Access> = Default::default();
-        let mut result: IndexMap<Vec<JsWord>, Access> = map
-            .into_iter()
-            .filter(|(k, access)| {
-                for (key, parent_access) in compare.iter() {
-                    if key.len() < k.len() {
-                        if k.starts_with(&key) {
-                            let mut new_access = parent_access.clone();
-                            new_access.merge(&access);
-                            updated.insert(key.clone(), new_access);
-                            return false;
-                        }
-                    }
-                }
-                true
-            })
-            .collect();
+    result
+        .into_iter()
+        .map(|(words, access)| (join_words(&words), access))
+        .collect()
+}
 
-        // Overwrite with updated access flags
-        for (k, v) in updated {
-            result.insert(k, v);
-        }
+/// Compute the access for paths to builtin modules.
+///
+/// Keys are prefixed with the name of the builtin module.
+pub fn builtin_access(
+    module: &Module,
+    candidates: Vec<Builtin>,
+) -> IndexMap<Vec<JsWord>, Access> {
+    let mut analyzer = BuiltinAnalyzer {
+        candidates,
+        access: Default::default(),
+    };
+    module.visit_children_with(&mut analyzer);
+    analyzer.access
+}
 
-        result
-    }
+/// Compute the access for paths to global symbols.
+///
+/// Like the builtin analysis this is not scope aware so a local
+/// symbol that shadows a global may contribute to the access flags.
+pub fn global_access(
+    module: &Module,
+    globals: &IndexSet<Vec<JsWord>>,
+) -> IndexMap<Vec<JsWord>, Access> {
+    let roots: IndexSet<&JsWord> =
+        globals.iter().filter_map(|words| words.get(0)).collect();
+    let candidates = roots
+        .into_iter()
+        .map(|word| Builtin {
+            static_assign: false,
+            source: word.clone(),
+            locals: vec![Local::Default(word.clone())],
+            matched: true,
+        })
+        .collect();
+    builtin_access(module, candidates)
+}
 
-    /// Compute the builtins.
-    fn compute(
-        &self,
-        access: IndexMap<Vec<JsWord>, Access>,
-    ) -> IndexMap<JsWord, Access> {
-        let mut out: IndexMap<JsWord, Access> = Default::default();
-        for (words, access) in access {
-            let words: Vec<String> =
-                words.into_iter().map(|w| w.as_ref().to_string()).collect();
-            out.insert(JsWord::from(words.join(".")), access.clone());
+/// Merge the access for all paths that are a parent or child of a key.
+///
+/// When no access is found for the key read access is assumed.
+pub fn access_for(
+    key: &[JsWord],
+    access: &IndexMap<Vec<JsWord>, Access>,
+) -> Access {
+    let mut result: Access = Default::default();
+    for (words, flags) in access.iter() {
+        if words.starts_with(key) || key.starts_with(words) {
+            result.merge(flags);
         }
-        out
     }
+    if !result.any() {
+        result.read = true;
+    }
+    result
 }
 
 /// Analyze the imports and require calls to built in modules.
@@ -130,6 +151,7 @@ impl BuiltinAnalyzer {
                     Local::Default(word) => word,
                     Local::Named(word) => word,
                     Local::Alias(word, _) => word,
+                    Local::Member(_, _) => return false,
                 };
                 word == sym
             });
@@ -142,17 +164,7 @@ impl BuiltinAnalyzer {
 
     fn insert_access(&mut self, words_key: Vec<JsWord>, kind: &AccessKind) {
         let entry = self.access.entry(words_key).or_insert(Default::default());
-        match kind {
-            AccessKind::Read => {
-                entry.read = true;
-            }
-            AccessKind::Write => {
-                entry.write = true;
-            }
-            AccessKind::Execute => {
-                entry.execute = true;
-            }
-        }
+        entry.merge(&Access::with(*kind));
     }
 
     fn access_visit_expr(&mut self, n: &Expr, kind: &AccessKind) {
@@ -261,7 +273,8 @@ impl BuiltinAnalyzer {
                                     Local::Named(word) => {
                                         vec![source, word.clone()]
                                     }
-                                    Local::Default(_word) => vec![source],
+                                    Local::Default(_word)
+                                    | Local::Member(_, _) => vec![source],
                                     Local::Alias(_word, alias) => {
                                         vec![source, alias.clone()]
                                     }
@@ -313,10 +326,11 @@ impl BuiltinAnalyzer {
                     self.access_visit_expr(&*expr, kind);
                 }
             }
+            // The tag function of a tagged template is called
             Expr::TaggedTpl(n) => {
-                self.access_visit_expr(&*n.tag, kind);
+                self.access_visit_expr(&*n.tag, &AccessKind::Execute);
                 for expr in n.tpl.exprs.iter() {
-                    self.access_visit_expr(&*expr, kind);
+                    self.access_visit_expr(&*expr, &AccessKind::Read);
                 }
             }
             _ => {}
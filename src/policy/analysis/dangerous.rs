@@ -0,0 +1,82 @@
+//! Helper functions to detect code evaluation.
+//!
+//! Calls to `eval()`, the `Function` constructor and timers that are
+//! passed a string execute arbitrary code so the capabilities used by
+//! the code cannot be determined statically.
+//!
+//! Like the detection for `require()` calls the names are matched
+//! syntactically so a local symbol that shadows `eval` is reported.
+
+use swc_atoms::JsWord;
+use swc_ecma_ast::*;
+
+const EVAL: &str = "eval";
+const FUNCTION: &str = "Function";
+const TIMERS: [&str; 2] = ["setTimeout", "setInterval"];
+const GLOBAL_OBJECTS: [&str; 4] = ["globalThis", "global", "window", "self"];
+
+/// Detect a call that evaluates code.
+///
+/// Direct calls such as `eval(code)` and indirect calls such as
+/// `(0, eval)(code)` or `window.eval(code)` are detected, the name
+/// of the function is returned.
+pub fn is_dangerous_call(call: &CallExpr) -> Option<JsWord> {
+    let callee = match &call.callee {
+        ExprOrSuper::Expr(callee) => callee_name(callee)?,
+        _ => return None,
+    };
+    match callee.as_ref() {
+        EVAL | FUNCTION => Some(callee),
+        name if TIMERS.contains(&name) => match call.args.get(0) {
+            Some(arg) if is_string(&*arg.expr) => Some(callee),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Detect a call to the `Function` constructor, eg: `new Function(code)`.
+pub fn is_dangerous_new(n: &NewExpr) -> Option<JsWord> {
+    let callee = callee_name(&*n.callee)?;
+    if callee.as_ref() == FUNCTION {
+        Some(callee)
+    } else {
+        None
+    }
+}
+
+/// Get the name of the function for a callee.
+fn callee_name(n: &Expr) -> Option<JsWord> {
+    match n {
+        Expr::Ident(id) => Some(id.sym.clone()),
+        // `(0, eval)`
+        Expr::Paren(n) => callee_name(&*n.expr),
+        Expr::Seq(n) => n.exprs.last().and_then(|expr| callee_name(&*expr)),
+        // `window.eval` or `globalThis['eval']`
+        Expr::Member(n) => {
+            let is_global = match &n.obj {
+                ExprOrSuper::Expr(obj) => match &**obj {
+                    Expr::Ident(id) => {
+                        GLOBAL_OBJECTS.contains(&id.sym.as_ref())
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+            if !is_global {
+                return None;
+            }
+            match &*n.prop {
+                Expr::Ident(id) if !n.computed => Some(id.sym.clone()),
+                Expr::Lit(Lit::Str(s)) if n.computed => Some(s.value.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Determine if an expression is a string literal or template.
+fn is_string(n: &Expr) -> bool {
+    matches!(n, Expr::Lit(Lit::Str(_)) | Expr::Tpl(_))
+}
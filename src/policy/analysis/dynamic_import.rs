@@ -1,4 +1,5 @@
-//! Helper functions to detect calls to `require` or `import`.
+//! Helper functions to detect calls to `require`, `require.resolve`
+//! or `import`.
 
 use swc_atoms::JsWord;
 use swc_ecma_ast::*;
@@ -6,6 +7,8 @@ use swc_ecma_ast::*;
 use super::member_expr::walk;
 
 const REQUIRE: &str = "require";
+const REQUIRE_RESOLVE: &str = "require.resolve";
+const RESOLVE: &str = "resolve";
 const IMPORT: &str = "import";
 
 /// Import that is a function call.
@@ -25,6 +28,61 @@ pub fn is_require_expr<'a>(n: &'a Expr) -> Option<DynamicCall<'a>> {
     is_call_module(n, REQUIRE)
 }
 
+/// Detect a call to `require()` where the argument is not a
+/// string literal, eg: `require(name)` or `require('./' + name)`.
+///
+/// The module for these calls cannot be determined statically.
+pub fn is_dynamic_require_expr(n: &Expr) -> bool {
+    if let Expr::Call(call) = n {
+        if let ExprOrSuper::Expr(callee) = &call.callee {
+            if let Expr::Ident(id) = &**callee {
+                return id.sym.as_ref() == REQUIRE
+                    && string_arg(call).is_none();
+            }
+        }
+    }
+    false
+}
+
+/// Detect an expression that is a call to `require.resolve()`.
+///
+/// Resolving a module only computes the path to the module so the
+/// call does not access the module value.
+///
+/// The call must be a simple call expression (single string argument).
+pub fn is_require_resolve_expr<'a>(n: &'a Expr) -> Option<DynamicCall<'a>> {
+    if let Expr::Call(call) = n {
+        if let ExprOrSuper::Expr(callee) = &call.callee {
+            if let Expr::Member(member) = &**callee {
+                let is_require = match &member.obj {
+                    ExprOrSuper::Expr(obj) => match &**obj {
+                        Expr::Ident(id) => id.sym.as_ref() == REQUIRE,
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                let is_resolve = match &*member.prop {
+                    Expr::Ident(id) if !member.computed => {
+                        id.sym.as_ref() == RESOLVE
+                    }
+                    Expr::Lit(Lit::Str(s)) if member.computed => {
+                        s.value.as_ref() == RESOLVE
+                    }
+                    _ => false,
+                };
+                if is_require && is_resolve {
+                    return string_arg(call).map(|arg| DynamicCall {
+                        arg,
+                        member: None,
+                        fn_name: REQUIRE_RESOLVE,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Detect an expression that is a call to `import()`.
 ///
 /// The call must be a simple call expression (single string argument).
@@ -53,12 +111,12 @@ fn is_call_module<'a>(
 
             // `require('buffer').Buffer`
             if let Some(Expr::Call(call)) = expressions.get(0) {
-                let prop_name =
-                    if let Some(Expr::Ident(id)) = expressions.get(1) {
-                        Some(&id.sym)
-                    } else {
-                        None
-                    };
+                let prop_name = match expressions.get(1) {
+                    Some(Expr::Ident(id)) => Some(&id.sym),
+                    // `require('buffer')['Buffer']`
+                    Some(Expr::Lit(Lit::Str(s))) => Some(&s.value),
+                    _ => None,
+                };
 
                 return is_simple_call(call, fn_name).map(|arg| DynamicCall {
                     arg,
@@ -80,19 +138,23 @@ fn is_simple_call<'a>(
     call: &'a CallExpr,
     fn_name: &'static str,
 ) -> Option<&'a JsWord> {
-    if call.args.len() == 1 {
-        if let ExprOrSuper::Expr(n) = &call.callee {
-            if let Expr::Ident(id) = &**n {
-                if id.sym.as_ref() == fn_name {
-                    let arg = call.args.get(0).unwrap();
-                    if let Expr::Lit(lit) = &*arg.expr {
-                        if let Lit::Str(s) = lit {
-                            return Some(&s.value);
-                        }
-                    }
-                }
+    if let ExprOrSuper::Expr(n) = &call.callee {
+        if let Expr::Ident(id) = &**n {
+            if id.sym.as_ref() == fn_name {
+                return string_arg(call);
             }
         }
     }
     None
 }
+
+/// Get the argument for a call with a single string literal argument.
+fn string_arg<'a>(call: &'a CallExpr) -> Option<&'a JsWord> {
+    if call.args.len() == 1 {
+        let arg = call.args.get(0).unwrap();
+        if let Expr::Lit(Lit::Str(s)) = &*arg.expr {
+            return Some(&s.value);
+        }
+    }
+    None
+}
@@ -14,16 +14,21 @@
 //! cannot be referenced using `this` in strict mode this is not a major problem.
 //!
 
+use std::collections::HashSet;
+
 use swc_atoms::JsWord;
+use swc_common::{SourceMap, Span};
 use swc_ecma_ast::*;
-use swc_ecma_visit::{Node, Visit};
+use swc_ecma_visit::{Node, Visit, VisitWith};
 
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
 
 use crate::policy::analysis::{
-    flatten, join_keys,
+    flatten, join_keys, join_words,
     scope_builder::{Scope, ScopeBuilder, WordOrPath},
 };
+use crate::swc_utils::span_to_location;
 
 // SEE: https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects
 
@@ -124,6 +129,26 @@ pub struct GlobalOptions {
     filter_global_functions: bool,
     filter_dynamic_import: bool,
     ignore_node_global: bool,
+    keep_member_paths: bool,
+}
+
+impl GlobalOptions {
+    /// Preserve the full member paths for globals rather than
+    /// collapsing deep properties into the parent reference.
+    pub fn keep_member_paths(mut self, flag: bool) -> Self {
+        self.keep_member_paths = flag;
+        self
+    }
+
+    /// Treat the `global` keyword exposed by node as a reference to
+    /// the global object.
+    ///
+    /// When set a member path such as `global.foo` is reported as the
+    /// global `foo`; otherwise `global` is reported as a global symbol.
+    pub fn ignore_node_global(mut self, flag: bool) -> Self {
+        self.ignore_node_global = flag;
+        self
+    }
 }
 
 impl Default for GlobalOptions {
@@ -136,10 +161,69 @@ impl Default for GlobalOptions {
             filter_global_functions: true,
             filter_dynamic_import: true,
             ignore_node_global: true,
+            keep_member_paths: false,
         }
     }
 }
 
+/// Compute the global symbols for a module.
+///
+/// Deep member paths are collapsed into their parent reference unless
+/// the `keep_member_paths` option is set and then joined into
+/// dot-delimited words.
+pub fn analyze_globals(
+    module: &Module,
+    options: GlobalOptions,
+) -> IndexSet<JsWord> {
+    let mut analyzer = GlobalAnalysis::new(options);
+    module.visit_children_with(&mut analyzer);
+    let globals = analyzer.compute_globals();
+    analyzer.flatten_join(globals)
+}
+
+/// Source location for the first reference to a global.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct GlobalLocation {
+    /// Line number (1-indexed).
+    pub line: usize,
+    /// Column number (0-indexed).
+    pub column: usize,
+}
+
+/// Compute the global symbols for a module with the location of the
+/// first reference to each global.
+///
+/// When deep member paths are collapsed into their parent reference
+/// the location is the first reference to any of the collapsed paths.
+pub fn analyze_globals_with_locations(
+    module: &Module,
+    options: GlobalOptions,
+    source_map: &SourceMap,
+) -> IndexMap<JsWord, GlobalLocation> {
+    let mut analyzer = GlobalAnalysis::new(options);
+    module.visit_children_with(&mut analyzer);
+    let spans = analyzer.compute_global_spans();
+    let paths = analyzer.flatten_paths(spans.keys().cloned().collect());
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            spans
+                .iter()
+                .filter(|(key, _)| key.starts_with(&path))
+                .map(|(_, span)| *span)
+                .min_by_key(|span| span.lo)
+                .map(|span| {
+                    let loc = span_to_location(source_map, span);
+                    let location = GlobalLocation {
+                        line: loc.line,
+                        column: loc.col_display,
+                    };
+                    (join_words(&path), location)
+                })
+        })
+        .collect()
+}
+
 /// Analyze the scopes for a module.
 #[derive(Debug)]
 pub struct GlobalAnalysis {
@@ -152,6 +236,15 @@ pub struct GlobalAnalysis {
 impl GlobalAnalysis {
     /// Create a scope analysis.
     pub fn new(options: GlobalOptions) -> Self {
+        Self::with_extra_builtins(options, Default::default())
+    }
+
+    /// Create a scope analysis that treats the extra builtins as
+    /// builtin modules in addition to the node builtin modules.
+    pub fn with_extra_builtins(
+        options: GlobalOptions,
+        extra_builtins: HashSet<String>,
+    ) -> Self {
         // Setting locals at the root scope allows us to
         // filter out certain symbols from being detected
         // as global.
@@ -190,7 +283,10 @@ impl GlobalAnalysis {
 
         Self {
             root: Scope::locals(Some(locals)),
-            builder: ScopeBuilder::new(options.ignore_node_global),
+            builder: ScopeBuilder::new(
+                options.ignore_node_global,
+                extra_builtins,
+            ),
             options,
         }
     }
@@ -212,23 +308,49 @@ impl GlobalAnalysis {
 
     /// Compute the global variables.
     pub fn compute_globals(&mut self) -> IndexSet<Vec<JsWord>> {
-        let mut global_symbols: IndexSet<Vec<JsWord>> = Default::default();
+        self.compute_global_spans()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    /// Compute the global variables mapped to the span of the
+    /// first reference in the source.
+    pub fn compute_global_spans(&mut self) -> IndexMap<Vec<JsWord>, Span> {
+        let mut global_symbols: IndexMap<Vec<JsWord>, Span> =
+            Default::default();
         self.walk_globals(&self.root, &mut global_symbols, &mut vec![]);
         global_symbols
     }
 
     /// Flatten words then join into a single dot-delimited word.
+    ///
+    /// When the `keep_member_paths` option is set the words are
+    /// sorted but deep member paths are not flattened.
     pub fn flatten_join(
         &self,
         words: IndexSet<Vec<JsWord>>,
     ) -> IndexSet<JsWord> {
-        join_keys(flatten(words))
+        join_keys(self.flatten_paths(words))
+    }
+
+    fn flatten_paths(
+        &self,
+        words: IndexSet<Vec<JsWord>>,
+    ) -> IndexSet<Vec<JsWord>> {
+        if self.options.keep_member_paths {
+            let mut words: Vec<Vec<JsWord>> = words.into_iter().collect();
+            words.sort();
+            words.into_iter().collect()
+        } else {
+            flatten(words)
+        }
     }
 
     fn walk_globals<'a>(
         &self,
         scope: &'a Scope,
-        global_symbols: &mut IndexSet<Vec<JsWord>>,
+        global_symbols: &mut IndexMap<Vec<JsWord>, Span>,
         scope_stack: &mut Vec<&'a Scope>,
     ) {
         scope_stack.push(scope);
@@ -246,23 +368,27 @@ impl GlobalAnalysis {
 
         // Build up the difference between the sets, cannot use difference()
         // as they are of different types.
-        let mut diff: IndexSet<&WordOrPath> = Default::default();
-        for ident in scope.idents.iter() {
+        let mut diff: IndexMap<&WordOrPath, Span> = Default::default();
+        for (ident, span) in scope.idents.iter() {
             match ident {
                 WordOrPath::Explicit(_, _) => {
-                    diff.insert(ident);
+                    diff.insert(ident, *span);
                 }
                 _ => {
                     let word: JsWord = ident.into();
                     if !combined_locals.contains(&word) {
-                        diff.insert(ident);
+                        diff.insert(ident, *span);
                     }
                 }
             }
         }
 
-        for sym in diff.drain(..) {
-            global_symbols.insert(sym.into());
+        for (sym, span) in diff.drain(..) {
+            // Keep the span that appears first in the source
+            let first = global_symbols.entry(sym.into()).or_insert(span);
+            if span.lo < first.lo {
+                *first = span;
+            }
         }
 
         for scope in scope.scopes.iter() {
@@ -279,7 +405,7 @@ impl Visit for GlobalAnalysis {
         match n {
             ModuleItem::ModuleDecl(decl) => match decl {
                 ModuleDecl::Import(import) => {
-                    self.builder.add_static_import(import);
+                    self.builder.add_static_import(import, scope);
                     for spec in import.specifiers.iter() {
                         let sym = match spec {
                             ImportSpecifier::Named(n) => &n.local.sym,
@@ -8,7 +8,11 @@ use swc_ecma_ast::*;
 
 /// Walk a member expression left to right.
 ///
-/// If a member expression is computed the property is not visited.
+/// If a member expression is computed the property is not visited
+/// unless it is a string literal.
+///
+/// Optional chains are unwrapped so that `a?.b?.c` yields the
+/// same expressions as `a.b.c`.
 pub fn walk<'a>(n: &'a MemberExpr, expressions: &mut Vec<&'a Expr>) {
     if let ExprOrSuper::Expr(n) = &n.obj {
         match &**n {
@@ -20,6 +24,11 @@ pub fn walk<'a>(n: &'a MemberExpr, expressions: &mut Vec<&'a Expr>) {
     }
 
     if n.computed {
+        // String literal properties are equivalent to dot access
+        // so `console['log']` is treated the same as `console.log`
+        if let Expr::Lit(Lit::Str(_)) = &*n.prop {
+            expressions.push(&*n.prop);
+        }
         return;
     };
 
@@ -31,6 +40,10 @@ fn walk_member_expr<'a>(n: &'a Expr, expressions: &mut Vec<&'a Expr>) {
         Expr::Member(n) => {
             walk(n, expressions);
         }
+        // Optional chaining wraps the member expression, eg: `a?.b?.c`
+        Expr::OptChain(n) => {
+            walk_member_expr(&*n.expr, expressions);
+        }
         _ => {
             expressions.push(n);
         }
@@ -50,6 +63,7 @@ fn walk_member_expressions<'a>(n: &'a MemberExpr, words: &mut Vec<&'a JsWord>) {
     for expr in expressions.iter() {
         match expr {
             Expr::Ident(n) => words.push(&n.sym),
+            Expr::Lit(Lit::Str(n)) if !words.is_empty() => words.push(&n.value),
             Expr::Member(n) => walk_member_expressions(n, words),
             Expr::Call(n) => match &n.callee {
                 ExprOrSuper::Expr(expr) => match &**expr {
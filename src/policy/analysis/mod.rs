@@ -1,14 +1,128 @@
 //! Visitors and helpers for module analysis.
 
-use indexmap::IndexSet;
+use std::collections::HashSet;
+
+use indexmap::{IndexMap, IndexSet};
 use swc_atoms::JsWord;
+use swc_common::{comments::SingleThreadedComments, Span};
+use swc_ecma_ast::Module;
+use swc_ecma_dep_graph::{analyze_dependencies, DependencyDescriptor};
+use swc_ecma_visit::VisitWith;
+
+use crate::{
+    access::Access, helpers::normalize_specifier,
+    module::dependencies::is_dependent_module,
+};
+
+use builtin::{builtin_access, global_access};
+use globals_scope::GlobalAnalysis;
 
 pub mod builtin;
+pub mod dangerous;
 pub mod dynamic_import;
 pub mod globals_scope;
 pub mod member_expr;
 pub mod scope_builder;
 
+/// Options for analyzing a module.
+#[derive(Debug, Default, Clone)]
+pub struct AnalysisOptions {
+    /// Additional module names to treat as builtins.
+    pub extra_builtins: HashSet<String>,
+    /// Compute read, write and execute access for globals and builtins.
+    pub access_detail: bool,
+}
+
+/// Globals, builtins and dependent packages for a single module.
+#[derive(Debug, Default)]
+pub struct ModuleAnalysis {
+    /// Global symbol paths.
+    pub globals: IndexSet<Vec<JsWord>>,
+    /// Builtin symbol paths.
+    pub builtin: IndexSet<Vec<JsWord>>,
+    /// Normalized specifiers for dependent packages.
+    pub packages: IndexSet<String>,
+    /// Access for the globals when access detail is enabled.
+    pub globals_access: IndexMap<Vec<JsWord>, Access>,
+    /// Access for the builtins when access detail is enabled.
+    pub builtin_access: IndexMap<Vec<JsWord>, Access>,
+    /// Spans for any `with` statements in the module.
+    pub with_statements: Vec<Span>,
+    /// Spans for calls to `require()` that cannot be resolved statically.
+    pub dynamic_requires: Vec<Span>,
+    /// Names and spans of functions called to evaluate code.
+    pub dangerous: Vec<(JsWord, Span)>,
+    /// Module specifiers passed to `require.resolve()`, these do not
+    /// grant access to the module.
+    pub resolved: IndexSet<JsWord>,
+}
+
+/// Analyze a module for the globals, builtins and dependent packages.
+pub fn analyze_module(
+    module: &Module,
+    options: &AnalysisOptions,
+) -> ModuleAnalysis {
+    let comments: SingleThreadedComments = Default::default();
+    let dependencies = analyze_dependencies(module, &comments);
+    analyze_module_dependencies(module, &dependencies, options)
+}
+
+/// Analyze a module using dependencies that have already been parsed.
+pub(crate) fn analyze_module_dependencies(
+    module: &Module,
+    dependencies: &[DependencyDescriptor],
+    options: &AnalysisOptions,
+) -> ModuleAnalysis {
+    // Compute globals
+    let mut globals_scope = GlobalAnalysis::with_extra_builtins(
+        Default::default(),
+        options.extra_builtins.clone(),
+    );
+    module.visit_children_with(&mut globals_scope);
+    let globals = globals_scope.compute_globals();
+
+    // Compute access before the builtin candidates are consumed
+    let (globals_access, builtin_access) = if options.access_detail {
+        (
+            global_access(module, &globals),
+            builtin_access(module, globals_scope.builder.candidates.clone()),
+        )
+    } else {
+        Default::default()
+    };
+
+    // Compute builtins
+    let builtin = globals_scope.compute_builtins();
+    let with_statements =
+        globals_scope.builder.with_statements.drain(..).collect();
+    let dynamic_requires =
+        std::mem::take(&mut globals_scope.builder.dynamic_requires);
+    let dangerous = std::mem::take(&mut globals_scope.builder.dangerous);
+    let resolved = std::mem::take(&mut globals_scope.builder.resolved);
+
+    // Compute dependent packages
+    let packages = dependencies
+        .iter()
+        .filter(|dep| {
+            is_dependent_module(dep.specifier.as_ref())
+                && !options.extra_builtins.contains(&*dep.specifier)
+        })
+        .map(|dep| normalize_specifier(dep.specifier.as_ref()))
+        .collect();
+
+    ModuleAnalysis {
+        globals,
+        builtin,
+        packages,
+        globals_access,
+        builtin_access,
+        with_statements,
+        dynamic_requires,
+        dangerous,
+        resolved,
+    }
+}
+
 /// Join the keys of a set into a single dot-delimited word.
 pub fn join_keys(set: IndexSet<Vec<JsWord>>) -> IndexSet<JsWord> {
     set.iter().map(|words| join_words(words)).collect()
@@ -26,18 +140,25 @@ pub fn join_words(words: &Vec<JsWord>) -> JsWord {
 ///
 /// For example, if we have `Buffer` and `Buffer.alloc` the `Buffer.alloc`
 /// entry is removed and we defer to the parent `Buffer`.
+///
+/// Paths are sorted before filtering so the result is stable regardless
+/// of the order in which symbols were detected; sibling paths that share
+/// a prefix (`a.b` and `a.c`) are both retained unless the prefix itself
+/// (`a`) is also in the set.
 pub fn flatten(set: IndexSet<Vec<JsWord>>) -> IndexSet<Vec<JsWord>> {
-    let compare = set.clone();
-    set.into_iter()
-        .filter(|k| {
-            for key in compare.iter() {
-                if key.len() < k.len() {
-                    if k.starts_with(&key) {
-                        return false;
-                    }
-                }
+    let mut paths: Vec<Vec<JsWord>> = set.into_iter().collect();
+    paths.sort();
+
+    // After sorting any descendants of a path immediately follow it
+    // so we only need to compare with the last retained path.
+    let mut result: IndexSet<Vec<JsWord>> = IndexSet::new();
+    for path in paths {
+        if let Some(parent) = result.last() {
+            if parent.len() < path.len() && path.starts_with(parent) {
+                continue;
             }
-            true
-        })
-        .collect()
+        }
+        result.insert(path);
+    }
+    result
 }
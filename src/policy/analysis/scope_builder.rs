@@ -4,18 +4,24 @@
 //!
 
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 
 use swc_atoms::JsWord;
+use swc_common::{Span, Spanned};
 use swc_ecma_ast::*;
 
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 
 use crate::{
     helpers::{is_module_exports, pattern_words, var_symbol_words},
     module::dependencies::is_builtin_module,
     policy::analysis::{
-        dynamic_import::{is_require_expr, DynamicCall},
+        dangerous::{is_dangerous_call, is_dangerous_new},
+        dynamic_import::{
+            is_dynamic_require_expr, is_import_expr, is_require_expr,
+            is_require_resolve_expr, DynamicCall,
+        },
         member_expr::walk,
     },
 };
@@ -26,6 +32,18 @@ const GLOBAL_THIS: &str = "globalThis";
 const FUNCTION_METHODS: [&str; 5] =
     ["call", "apply", "bind", "toSource", "toString"];
 
+/// Strip function methods like `call`, `apply` and `bind` etc.
+///
+/// Only applies to the callee of a call (execute access) so that
+/// a reference to a property such as `obj.call` is preserved.
+fn strip_function_method(words_key: &mut Vec<JsWord>) {
+    if let Some(last) = words_key.last() {
+        if FUNCTION_METHODS.contains(&last.as_ref()) {
+            words_key.pop();
+        }
+    }
+}
+
 /// Reference to a built in module.
 ///
 /// May be from an import specifier, call to `require()` or a dynamic `import()`.
@@ -69,9 +87,13 @@ impl Builtin {
                         Local::Named(name) => {
                             symbols.insert(vec![self.source.clone(), name]);
                         }
-                        Local::Alias(_, prop) => {
+                        Local::Alias(_, prop)
+                        | Local::Member(_, Some(prop)) => {
                             symbols.insert(vec![self.source.clone(), prop]);
                         }
+                        Local::Member(_, None) => {
+                            symbols.insert(vec![self.source.clone()]);
+                        }
                     }
                 }
             }
@@ -89,6 +111,12 @@ pub(crate) enum Local {
 
     // The local symbol is the first word and the alias is the second.
     Alias(JsWord, JsWord),
+
+    // Member expression path that a builtin was assigned to and an
+    // optional property of the builtin module, eg:
+    //
+    // Foo.prototype.util = require('util');
+    Member(Vec<JsWord>, Option<JsWord>),
 }
 
 /// Enumeration of function variants in the AST.
@@ -174,14 +202,17 @@ pub struct Scope {
     pub scopes: Vec<Scope>,
     /// Identifiers local to this scope.
     pub locals: IndexSet<JsWord>,
-    /// Identifiers that are references.
+    /// Identifiers that are references mapped to the span of
+    /// the first reference.
     ///
     /// These could be local or global symbols and we need
     /// to combine all parent scopes to detect if a symbol should
     /// be considered global.
-    pub idents: IndexSet<WordOrPath>,
+    pub idents: IndexMap<WordOrPath, Span>,
     /// Hoisted variable declarations.
     pub hoisted_vars: Rc<RefCell<IndexSet<JsWord>>>,
+    /// Builtin candidates declared in this scope.
+    candidates: Rc<RefCell<CandidateScope>>,
 }
 
 impl Scope {
@@ -195,24 +226,44 @@ impl Scope {
             locals: locals.unwrap_or(Default::default()),
             idents: Default::default(),
             hoisted_vars,
+            candidates: Default::default(),
         }
     }
 
     /// Create a scope with locals and owned hoisted variables.
     pub fn locals(locals: Option<IndexSet<JsWord>>) -> Self {
-        Self {
-            scopes: Default::default(),
-            locals: locals.unwrap_or(Default::default()),
-            idents: Default::default(),
-            hoisted_vars: Rc::new(RefCell::new(Default::default())),
-        }
+        Scope::new(locals, Rc::new(RefCell::new(Default::default())))
+    }
+
+    /// Create a child scope that can see the builtin candidates
+    /// declared in the parent scope.
+    fn child(parent: &Scope, locals: Option<IndexSet<JsWord>>) -> Self {
+        parent.candidates.borrow_mut().locals = parent.locals.clone();
+        let mut scope = Scope::new(locals, Rc::clone(&parent.hoisted_vars));
+        scope.candidates = Rc::new(RefCell::new(CandidateScope {
+            parent: Some(Rc::clone(&parent.candidates)),
+            ..Default::default()
+        }));
+        scope
     }
 
     fn from_parent(parent: &mut Scope) -> Self {
-        Scope::new(None, Rc::clone(&parent.hoisted_vars))
+        Scope::child(parent, None)
     }
 }
 
+/// Builtin candidates declared in a lexical scope.
+#[derive(Debug, Default)]
+struct CandidateScope {
+    /// Indices into the builder candidates.
+    candidates: Vec<usize>,
+    /// Locals of the scope when the last child scope was created,
+    /// used to determine if a candidate in a parent scope is shadowed.
+    locals: IndexSet<JsWord>,
+    /// Candidates for the parent scope.
+    parent: Option<Rc<RefCell<CandidateScope>>>,
+}
+
 /// Scope builder creates a tree of scopes.
 #[derive(Debug, Default)]
 pub struct ScopeBuilder {
@@ -222,21 +273,66 @@ pub struct ScopeBuilder {
     pub builtins: IndexSet<Vec<JsWord>>,
     /// Whether to ignore the `global` keyword exposed by node.
     ignore_node_global: bool,
+    /// Additional module names to treat as builtins.
+    extra_builtins: HashSet<String>,
+    /// Locations of `with` statements which may hide access
+    /// to globals and builtins.
+    pub with_statements: Vec<Span>,
+    /// Locations of `require()` calls with an argument that is not
+    /// a string literal so the module cannot be determined.
+    pub dynamic_requires: Vec<Span>,
+    /// Names and locations of functions called to evaluate code,
+    /// for example `eval` or the `Function` constructor.
+    pub dangerous: Vec<(JsWord, Span)>,
+    /// Module specifiers passed to `require.resolve()`.
+    ///
+    /// These are recorded separately from builtin candidates as
+    /// resolving a module does not access the module value.
+    pub resolved: IndexSet<JsWord>,
+    /// Whether the next expression visited is the callee of a call.
+    callee: bool,
 }
 
 impl ScopeBuilder {
     /// Create a scope tree.
-    pub fn new(ignore_node_global: bool) -> Self {
+    ///
+    /// The extra builtins are module names provided by the host
+    /// environment (for example, `electron`) that should be treated
+    /// as builtins in addition to the node builtin modules; they extend
+    /// the node list and cannot remove a node builtin.
+    pub fn new(
+        ignore_node_global: bool,
+        extra_builtins: HashSet<String>,
+    ) -> Self {
         Self {
             candidates: Default::default(),
             builtins: Default::default(),
             ignore_node_global,
+            extra_builtins,
+            with_statements: Default::default(),
+            dynamic_requires: Default::default(),
+            dangerous: Default::default(),
+            resolved: Default::default(),
+            callee: false,
         }
     }
 
+    /// Determine if a module specifier is a builtin module.
+    fn is_builtin(&self, s: &str) -> bool {
+        is_builtin_module(s) || self.extra_builtins.contains(s)
+    }
+
     /// Add a static import declaration.
-    pub fn add_static_import(&mut self, n: &ImportDecl) {
-        if is_builtin_module(n.src.value.as_ref()) {
+    ///
+    /// An import without specifiers of a builtin module, eg:
+    /// `import 'fs';` is a side-effect import of the builtin.
+    pub fn add_static_import(&mut self, n: &ImportDecl, scope: &Scope) {
+        if n.specifiers.is_empty() && self.is_builtin(n.src.value.as_ref()) {
+            self.insert_builtin(vec![n.src.value.clone()]);
+            return;
+        }
+
+        if self.is_builtin(n.src.value.as_ref()) {
             let mut builtin = Builtin {
                 static_assign: true,
                 source: n.src.value.clone(),
@@ -259,8 +355,78 @@ impl ScopeBuilder {
                     builtin.locals.push(local);
                 }
             }
-            self.candidates.push(builtin);
+            self.insert_candidate(builtin, scope);
+        }
+    }
+
+    /// Add a builtin candidate declared in a scope.
+    fn insert_candidate(&mut self, builtin: Builtin, scope: &Scope) {
+        scope
+            .candidates
+            .borrow_mut()
+            .candidates
+            .push(self.candidates.len());
+        self.candidates.push(builtin);
+    }
+
+    /// Add a builtin candidate assigned to an identifier.
+    ///
+    /// The candidate belongs to the innermost scope that declares the
+    /// identifier; when no scope declares it as a local (for example,
+    /// a hoisted `var`) it is added to the outermost scope.
+    fn insert_assigned_candidate(
+        &mut self,
+        builtin: Builtin,
+        sym: &JsWord,
+        scope: &Scope,
+    ) {
+        let mut target = Rc::clone(&scope.candidates);
+        if !scope.locals.contains(sym) {
+            loop {
+                let parent = target.borrow().parent.clone();
+                if let Some(parent) = parent {
+                    let declared = parent.borrow().locals.contains(sym);
+                    target = parent;
+                    if declared {
+                        break;
+                    }
+                } else {
+                    break;
+                }
+            }
         }
+        target.borrow_mut().candidates.push(self.candidates.len());
+        self.candidates.push(builtin);
+    }
+
+    /// Find the candidate with a local symbol visible from a scope.
+    ///
+    /// Candidates declared in the scope or a parent scope are visible
+    /// unless a scope between declares a local with the same name.
+    fn find_candidate(&self, sym: &JsWord, scope: &Scope) -> Option<usize> {
+        let mut current = Some(Rc::clone(&scope.candidates));
+        let mut innermost = true;
+        while let Some(candidate_scope) = current {
+            let candidate_scope = candidate_scope.borrow();
+            for index in candidate_scope.candidates.iter().rev() {
+                if builtin_local(&self.candidates[*index], sym).is_some() {
+                    return Some(*index);
+                }
+            }
+
+            let locals = if innermost {
+                &scope.locals
+            } else {
+                &candidate_scope.locals
+            };
+            if locals.contains(sym) {
+                return None;
+            }
+
+            innermost = false;
+            current = candidate_scope.parent.clone();
+        }
+        None
     }
 
     /// Determine if a word matches a previously located builtin module local
@@ -268,21 +434,39 @@ impl ScopeBuilder {
     fn is_builtin_match(
         &mut self,
         sym: &JsWord,
+        scope: &Scope,
     ) -> Option<(&Local, JsWord, &Builtin)> {
-        // Note reversing is a hack until we have builtin logic
-        // that respects scopes!
+        let index = self.find_candidate(sym, scope)?;
+        self.candidates[index].matched = true;
+        let builtin = &self.candidates[index];
+        builtin_local(builtin, sym)
+            .map(|local| (local, builtin.source.clone(), builtin))
+    }
+
+    /// Determine if the words for a member expression start with the
+    /// path of a builtin module that was assigned to a member expression.
+    ///
+    /// Properties outlive the scope of the assignment so member paths
+    /// are matched regardless of the scope the builtin was assigned in.
+    ///
+    /// Returns the words key for the builtin when a match is found.
+    fn is_builtin_member_match(
+        &mut self,
+        members: &[JsWord],
+    ) -> Option<Vec<JsWord>> {
         for builtin in self.candidates.iter_mut().rev() {
-            let mut matched = builtin.locals.iter().find(|local| {
-                let word = match local {
-                    Local::Default(word) => word,
-                    Local::Named(word) => word,
-                    Local::Alias(word, _) => word,
-                };
-                word == sym
-            });
-            if let Some(local) = matched.take() {
-                builtin.matched = true;
-                return Some((local, builtin.source.clone(), builtin));
+            for local in builtin.locals.iter() {
+                if let Local::Member(path, prop) = local {
+                    if members.starts_with(path) {
+                        builtin.matched = true;
+                        let mut words_key = vec![builtin.source.clone()];
+                        if let Some(prop) = prop {
+                            words_key.push(prop.clone());
+                        }
+                        words_key.extend(members[path.len()..].iter().cloned());
+                        return Some(words_key);
+                    }
+                }
             }
         }
         None
@@ -328,6 +512,7 @@ impl ScopeBuilder {
                 };
             }
             Stmt::With(n) => {
+                self.with_statements.push(n.span);
                 let mut next_scope = Scope::from_parent(scope);
                 self.visit_stmt(&*n.body, &mut next_scope, None);
                 scope.scopes.push(next_scope);
@@ -381,7 +566,12 @@ impl ScopeBuilder {
                             let mut names = Vec::new();
                             pattern_words(pat, &mut names);
                             for sym in names {
-                                self.insert_ident(sym.clone(), scope, None);
+                                self.insert_ident(
+                                    sym.clone(),
+                                    scope,
+                                    None,
+                                    pat.span(),
+                                );
                             }
                         }
                     },
@@ -406,7 +596,12 @@ impl ScopeBuilder {
                             let mut names = Vec::new();
                             pattern_words(pat, &mut names);
                             for sym in names {
-                                self.insert_ident(sym.clone(), scope, None);
+                                self.insert_ident(
+                                    sym.clone(),
+                                    scope,
+                                    None,
+                                    pat.span(),
+                                );
                             }
                         }
                     },
@@ -450,8 +645,7 @@ impl ScopeBuilder {
                         None
                     };
 
-                    let mut next_scope =
-                        Scope::new(locals, Rc::clone(&scope.hoisted_vars));
+                    let mut next_scope = Scope::child(scope, locals);
                     self.visit_block_stmt(&catch_clause.body, &mut next_scope);
                     scope.scopes.push(next_scope);
                 }
@@ -472,8 +666,7 @@ impl ScopeBuilder {
                 }
             }
             Stmt::Block(n) => {
-                let mut next_scope =
-                    Scope::new(locals, Rc::clone(&scope.hoisted_vars));
+                let mut next_scope = Scope::child(scope, locals);
                 for stmt in n.stmts.iter() {
                     self.visit_stmt(stmt, &mut next_scope, None);
                 }
@@ -493,11 +686,13 @@ impl ScopeBuilder {
     }
 
     fn visit_expr(&mut self, n: &Expr, scope: &mut Scope) {
+        // Only the outermost callee expression is executed
+        let callee = std::mem::take(&mut self.callee);
         match n {
             Expr::Ident(n) => {
-                self.insert_ident(n.sym.clone(), scope, None);
+                self.insert_ident(n.sym.clone(), scope, None, n.span);
                 if let Some((local, source, builtin)) =
-                    self.is_builtin_match(&n.sym)
+                    self.is_builtin_match(&n.sym, scope)
                 {
                     let words_key = if let Local::Alias(_, alias) = local {
                         vec![source, alias.clone()]
@@ -517,7 +712,12 @@ impl ScopeBuilder {
                 }
             }
             Expr::PrivateName(n) => {
-                self.insert_ident(private_name_prefix(&n.id.sym), scope, None);
+                self.insert_ident(
+                    private_name_prefix(&n.id.sym),
+                    scope,
+                    None,
+                    n.span,
+                );
             }
             Expr::Bin(n) => {
                 self.visit_expr(&*n.left, scope);
@@ -529,6 +729,8 @@ impl ScopeBuilder {
                 }
             }
             Expr::TaggedTpl(n) => {
+                // The tag is called with the template
+                self.callee = true;
                 self.visit_expr(&*n.tag, scope);
                 for expr in n.tpl.exprs.iter() {
                     self.visit_expr(&*expr, scope);
@@ -554,7 +756,12 @@ impl ScopeBuilder {
                         }
                         PropOrSpread::Prop(n) => match &**n {
                             Prop::Shorthand(id) => {
-                                self.insert_ident(id.sym.clone(), scope, None);
+                                self.insert_ident(
+                                    id.sym.clone(),
+                                    scope,
+                                    None,
+                                    id.span,
+                                );
                             }
                             Prop::KeyValue(n) => {
                                 self.visit_expr(&*n.value, scope);
@@ -583,10 +790,8 @@ impl ScopeBuilder {
                                         .map(|n| n.clone())
                                         .collect();
 
-                                    let mut next_scope = Scope::new(
-                                        Some(locals),
-                                        Rc::clone(&scope.hoisted_vars),
-                                    );
+                                    let mut next_scope =
+                                        Scope::child(scope, Some(locals));
                                     self.visit_block_stmt(
                                         body,
                                         &mut next_scope,
@@ -617,8 +822,26 @@ impl ScopeBuilder {
             Expr::Arrow(n) => {
                 self.visit_function(Func::Arrow(n), scope, None);
             }
-            Expr::Call(n) => {
-                self.visit_caller(Caller::Call(n), scope);
+            Expr::Call(call) => {
+                self.visit_caller(Caller::Call(call), scope);
+
+                // Computed module, eg: `require(name)`
+                if is_dynamic_require_expr(n) {
+                    self.dynamic_requires.push(call.span);
+                }
+
+                // Path resolution, eg: `require.resolve('fs')`
+                if let Some(dynamic_call) = is_require_resolve_expr(n) {
+                    self.resolved.insert(dynamic_call.arg.clone());
+                }
+
+                // Dynamic import of a builtin module, eg: `import('fs')`
+                // is treated like a side-effect import
+                if let Some(dynamic_call) = is_import_expr(n) {
+                    if self.is_builtin(dynamic_call.arg.as_ref()) {
+                        self.insert_side_effect_builtin(&dynamic_call);
+                    }
+                }
             }
             Expr::Update(n) => {
                 self.visit_expr(&n.arg, scope);
@@ -634,13 +857,14 @@ impl ScopeBuilder {
                     PatOrExpr::Pat(pat) => match &**pat {
                         Pat::Ident(ident) => {
                             if let Some((local, source, _)) =
-                                self.is_builtin_match(&ident.id.sym)
+                                self.is_builtin_match(&ident.id.sym, scope)
                             {
                                 let words_key = match local {
                                     Local::Named(word) => {
                                         vec![source, word.clone()]
                                     }
-                                    Local::Default(_word) => vec![source],
+                                    Local::Default(_word)
+                                    | Local::Member(_, _) => vec![source],
                                     Local::Alias(_word, alias) => {
                                         vec![source, alias.clone()]
                                     }
@@ -653,6 +877,7 @@ impl ScopeBuilder {
                                 ident.id.sym.clone(),
                                 scope,
                                 None,
+                                ident.id.span,
                             );
                         }
                         Pat::Expr(expr) => self.visit_expr(expr, scope),
@@ -663,7 +888,7 @@ impl ScopeBuilder {
 
                 // Dynamic require on RHS of assignment
                 if let Some(dynamic_call) = is_require_expr(&*assign.right) {
-                    if is_builtin_module(dynamic_call.arg.as_ref()) {
+                    if self.is_builtin(dynamic_call.arg.as_ref()) {
                         let mut builtin = Builtin {
                             static_assign: false,
                             source: dynamic_call.arg.clone(),
@@ -694,13 +919,36 @@ impl ScopeBuilder {
                                             }
                                         });
                                     }
-                                    // TODO: handle assignment to member expression:
-                                    //
                                     // Foo.prototype.util = require('util');
-                                    //
+                                    Expr::Member(n) => {
+                                        if let Some(path) =
+                                            assign_member_words(n)
+                                        {
+                                            builtin.locals.push(Local::Member(
+                                                path,
+                                                dynamic_call.member.cloned(),
+                                            ));
+                                        }
+                                    }
                                     _ => {}
                                 },
                                 PatOrExpr::Pat(pat) => match &**pat {
+                                    Pat::Expr(expr) => {
+                                        if let Expr::Member(n) = &**expr {
+                                            if let Some(path) =
+                                                assign_member_words(n)
+                                            {
+                                                builtin.locals.push(
+                                                    Local::Member(
+                                                        path,
+                                                        dynamic_call
+                                                            .member
+                                                            .cloned(),
+                                                    ),
+                                                );
+                                            }
+                                        }
+                                    }
                                     Pat::Ident(ident) => {
                                         builtin.locals.push({
                                             if let Some(member) =
@@ -721,7 +969,13 @@ impl ScopeBuilder {
                                 },
                             }
 
-                            self.candidates.push(builtin);
+                            if let Some(sym) = assign_ident(&assign.left) {
+                                self.insert_assigned_candidate(
+                                    builtin, sym, scope,
+                                );
+                            } else {
+                                self.insert_candidate(builtin, scope);
+                            }
                         }
                     }
                 }
@@ -730,10 +984,21 @@ impl ScopeBuilder {
                 self.visit_expr(&n.expr, scope);
             }
             Expr::Member(member) => {
+                // import('fs').then(...);
+                if let ExprOrSuper::Expr(expr) = &member.obj {
+                    if let Expr::Call(_) = &**expr {
+                        if let Some(dynamic_call) = is_import_expr(expr) {
+                            if self.is_builtin(dynamic_call.arg.as_ref()) {
+                                self.insert_side_effect_builtin(&dynamic_call);
+                            }
+                        }
+                    }
+                }
+
                 // require('os').platform();
                 if let ExprOrSuper::Expr(expr) = &member.obj {
                     if let Some(dynamic_call) = is_require_expr(expr) {
-                        if is_builtin_module(dynamic_call.arg.as_ref()) {
+                        if self.is_builtin(dynamic_call.arg.as_ref()) {
                             let mut builtin = Builtin {
                                 static_assign: true,
                                 source: dynamic_call.arg.clone(),
@@ -744,7 +1009,7 @@ impl ScopeBuilder {
                                 builtin.locals =
                                     vec![Local::Named(id.sym.clone())];
                             }
-                            self.candidates.push(builtin);
+                            self.insert_candidate(builtin, scope);
                         }
                     }
                 }
@@ -761,7 +1026,7 @@ impl ScopeBuilder {
                     };
 
                 for (word, parts) in members {
-                    self.insert_ident(word, scope, Some(parts));
+                    self.insert_ident(word, scope, Some(parts), member.span);
                 }
 
                 // Builtin handling
@@ -777,9 +1042,16 @@ impl ScopeBuilder {
                         .flatten()
                         .collect();
 
-                    if let Some(word) = members.get(0) {
+                    if let Some(mut words_key) =
+                        self.is_builtin_member_match(&members)
+                    {
+                        if callee {
+                            strip_function_method(&mut words_key);
+                        }
+                        self.insert_builtin(words_key);
+                    } else if let Some(word) = members.get(0) {
                         if let Some((local, source, _)) =
-                            self.is_builtin_match(word)
+                            self.is_builtin_match(word, scope)
                         {
                             let mut words_key: Vec<JsWord> =
                                 members.into_iter().collect();
@@ -796,15 +1068,9 @@ impl ScopeBuilder {
                                 words_key = vec![source, alias.clone()];
                             }
 
-                            // FIXME: only apply this logic for function calls (execute access)
-
-                            // Strip function methods like `call`, `apply` and `bind` etc.
-                            if let Some(last) = words_key.last() {
-                                if FUNCTION_METHODS.contains(&last.as_ref()) {
-                                    words_key.pop();
-                                }
+                            if callee {
+                                strip_function_method(&mut words_key);
                             }
-
                             self.insert_builtin(words_key);
                         }
                     }
@@ -838,17 +1104,99 @@ impl ScopeBuilder {
                 });
                 self.visit_class(&n.class, scope, locals);
             }
+            // Meta properties such as `new.target` and `import.meta`
+            // are not references to a global
+            Expr::MetaProp(_) => {}
+            Expr::JSXElement(n) => {
+                self.visit_jsx_element(n, scope);
+            }
+            Expr::JSXFragment(n) => {
+                self.visit_jsx_children(&n.children, scope);
+            }
             _ => {}
         }
     }
 
+    fn visit_jsx_element(&mut self, n: &JSXElement, scope: &mut Scope) {
+        // Component names are references, eg: `<Foo />` or
+        // `<Foo.Bar />` whilst lowercase names are intrinsic elements
+        match &n.opening.name {
+            JSXElementName::Ident(id) => {
+                if !is_intrinsic_element(&id.sym) {
+                    self.insert_ident(id.sym.clone(), scope, None, id.span);
+                }
+            }
+            JSXElementName::JSXMemberExpr(member) => {
+                let (word, parts) = jsx_member_words(member);
+                self.insert_ident(word, scope, Some(parts), member.prop.span);
+            }
+            JSXElementName::JSXNamespacedName(_) => {}
+        }
+
+        for attr in n.opening.attrs.iter() {
+            match attr {
+                JSXAttrOrSpread::SpreadElement(n) => {
+                    self.visit_expr(&*n.expr, scope);
+                }
+                JSXAttrOrSpread::JSXAttr(n) => match &n.value {
+                    Some(JSXAttrValue::JSXExprContainer(n)) => {
+                        self.visit_jsx_expr_container(n, scope);
+                    }
+                    Some(JSXAttrValue::JSXElement(n)) => {
+                        self.visit_jsx_element(n, scope);
+                    }
+                    Some(JSXAttrValue::JSXFragment(n)) => {
+                        self.visit_jsx_children(&n.children, scope);
+                    }
+                    Some(JSXAttrValue::Lit(_)) | None => {}
+                },
+            }
+        }
+
+        self.visit_jsx_children(&n.children, scope);
+    }
+
+    fn visit_jsx_children(
+        &mut self,
+        children: &[JSXElementChild],
+        scope: &mut Scope,
+    ) {
+        for child in children.iter() {
+            match child {
+                JSXElementChild::JSXExprContainer(n) => {
+                    self.visit_jsx_expr_container(n, scope);
+                }
+                JSXElementChild::JSXSpreadChild(n) => {
+                    self.visit_expr(&*n.expr, scope);
+                }
+                JSXElementChild::JSXElement(n) => {
+                    self.visit_jsx_element(n, scope);
+                }
+                JSXElementChild::JSXFragment(n) => {
+                    self.visit_jsx_children(&n.children, scope);
+                }
+                JSXElementChild::JSXText(_) => {}
+            }
+        }
+    }
+
+    fn visit_jsx_expr_container(
+        &mut self,
+        n: &JSXExprContainer,
+        scope: &mut Scope,
+    ) {
+        if let JSXExpr::Expr(expr) = &n.expr {
+            self.visit_expr(expr, scope);
+        }
+    }
+
     fn visit_class(
         &mut self,
         n: &Class,
         scope: &mut Scope,
         locals: Option<IndexSet<JsWord>>,
     ) {
-        let mut next_scope = Scope::new(locals, Rc::clone(&scope.hoisted_vars));
+        let mut next_scope = Scope::child(scope, locals);
 
         // In case the super class reference is a global
         if let Some(ref super_class) = n.super_class {
@@ -864,36 +1212,34 @@ impl ScopeBuilder {
                         None,
                     );
                 }
+                // Static members are visited too as static method bodies
+                // and initializers may reference globals and builtins
+                //
+                // Accessors (`get x() {}` and `set x(v) {}`) are methods
+                // with a getter or setter kind so the setter parameter
+                // is declared as a local of the function scope
                 ClassMember::Method(n) => {
-                    if !n.is_static {
-                        self.visit_function(
-                            Func::Fn(&n.function),
-                            &mut next_scope,
-                            None,
-                        );
-                    }
+                    self.visit_function(
+                        Func::Fn(&n.function),
+                        &mut next_scope,
+                        None,
+                    );
                 }
                 ClassMember::PrivateMethod(n) => {
-                    if !n.is_static {
-                        self.visit_function(
-                            Func::Fn(&n.function),
-                            &mut next_scope,
-                            None,
-                        );
-                    }
+                    self.visit_function(
+                        Func::Fn(&n.function),
+                        &mut next_scope,
+                        None,
+                    );
                 }
                 ClassMember::ClassProp(n) => {
-                    if !n.is_static {
-                        if let Some(value) = &n.value {
-                            self.visit_expr(value, &mut next_scope);
-                        }
+                    if let Some(value) = &n.value {
+                        self.visit_expr(value, &mut next_scope);
                     }
                 }
                 ClassMember::PrivateProp(n) => {
-                    if !n.is_static {
-                        if let Some(value) = &n.value {
-                            self.visit_expr(value, &mut next_scope);
-                        }
+                    if let Some(value) = &n.value {
+                        self.visit_expr(value, &mut next_scope);
                     }
                 }
                 _ => {}
@@ -904,10 +1250,20 @@ impl ScopeBuilder {
     }
 
     fn visit_caller(&mut self, n: Caller, scope: &mut Scope) {
+        // Code evaluation, eg: `eval(code)` or `new Function(code)`
+        let dangerous = match &n {
+            Caller::Call(n) => is_dangerous_call(n).map(|name| (name, n.span)),
+            Caller::New(n) => is_dangerous_new(n).map(|name| (name, n.span)),
+        };
+        if let Some(dangerous) = dangerous {
+            self.dangerous.push(dangerous);
+        }
+
         let args = match n {
             Caller::Call(n) => {
                 match &n.callee {
                     ExprOrSuper::Expr(expr) => {
+                        self.callee = true;
                         self.visit_expr(expr, scope);
                     }
                     _ => {}
@@ -925,9 +1281,12 @@ impl ScopeBuilder {
                 self.visit_expr(&*arg.expr, scope);
 
                 // Sometimes calls to `require()` are passed as function
-                // arguments so we need to detect these too
+                // arguments so we need to detect these too, this includes
+                // CommonJS re-exports such as:
+                //
+                // Object.assign(module.exports, require('fs'));
                 if let Some(dynamic_call) = is_require_expr(&*arg.expr) {
-                    if is_builtin_module(&dynamic_call.arg) {
+                    if self.is_builtin(&dynamic_call.arg) {
                         self.insert_side_effect_builtin(&dynamic_call);
                     }
                 }
@@ -941,7 +1300,7 @@ impl ScopeBuilder {
         scope: &mut Scope,
         locals: Option<IndexSet<JsWord>>,
     ) {
-        let mut next_scope = Scope::new(locals, Rc::clone(&scope.hoisted_vars));
+        let mut next_scope = Scope::child(scope, locals);
 
         // Gether function parameters
         let params = match n {
@@ -1033,10 +1392,10 @@ impl ScopeBuilder {
         }
     }
 
-    fn visit_var_declarator(&mut self, n: &VarDeclarator, _scope: &mut Scope) {
+    fn visit_var_declarator(&mut self, n: &VarDeclarator, scope: &mut Scope) {
         if let Some(init) = &n.init {
             if let Some(dynamic_call) = is_require_expr(init) {
-                if is_builtin_module(dynamic_call.arg.as_ref()) {
+                if self.is_builtin(dynamic_call.arg.as_ref()) {
                     let mut builtin = Builtin {
                         static_assign: false,
                         source: dynamic_call.arg.clone(),
@@ -1071,7 +1430,7 @@ impl ScopeBuilder {
                         }
                     };
 
-                    self.candidates.push(builtin);
+                    self.insert_candidate(builtin, scope);
                 }
             }
         }
@@ -1093,14 +1452,13 @@ impl ScopeBuilder {
 
         if let Some(first) = expressions.get(0) {
             match first {
-                Expr::This(_) => {
+                // Properties of meta properties, eg: `import.meta.url`
+                Expr::This(_) | Expr::MetaProp(_) => {
                     return members;
                 }
-                Expr::Ident(id) => {
-                    if id.sym.as_ref() == GLOBAL_THIS {
-                        expressions.remove(0);
-                    }
-                }
+                // The `globalThis` prefix is stripped once the
+                // member words have been computed
+                Expr::Ident(_) => {}
                 _ => {
                     let mut member_exprs = Vec::new();
 
@@ -1165,7 +1523,7 @@ impl ScopeBuilder {
         // FIXME: all the paths for nested member expressions should be declared!
         match n {
             Expr::Ident(n) => {
-                self.insert_ident(n.sym.clone(), scope, None);
+                self.insert_ident(n.sym.clone(), scope, None, n.span);
             }
             Expr::Bin(n) => {
                 self.visit_nested_expression(&*n.left, scope, members);
@@ -1192,6 +1550,10 @@ impl ScopeBuilder {
                 Expr::Ident(id) => {
                     words.push(id.sym.clone());
                 }
+                // Computed string literal property, eg: `console['log']`
+                Expr::Lit(Lit::Str(s)) if !words.is_empty() => {
+                    words.push(s.value.clone());
+                }
                 Expr::Call(call) => {
                     if let ExprOrSuper::Expr(expr) = &call.callee {
                         match &**expr {
@@ -1214,6 +1576,15 @@ impl ScopeBuilder {
             }
         }
 
+        // Strip the `globalThis` prefix after the words are computed so
+        // that computed properties such as `globalThis['process'].env`
+        // keep the remaining path
+        if let Some(word) = words.get(0) {
+            if word.as_ref() == GLOBAL_THIS {
+                words.remove(0);
+            }
+        }
+
         if words.is_empty() {
             None
         } else {
@@ -1227,6 +1598,7 @@ impl ScopeBuilder {
         mut sym: JsWord,
         scope: &mut Scope,
         mut path: Option<Vec<JsWord>>,
+        span: Span,
     ) {
         let mut explicit = false;
         if self.ignore_node_global && sym.as_ref() == GLOBAL {
@@ -1256,7 +1628,71 @@ impl ScopeBuilder {
             }
         };
 
-        scope.idents.insert(word_or_path);
+        scope.idents.entry(word_or_path).or_insert(span);
+    }
+}
+
+// Determine if a JSX element name is an intrinsic element such as
+// `div` rather than a reference to a component.
+fn is_intrinsic_element(sym: &JsWord) -> bool {
+    sym.starts_with(|c: char| c.is_ascii_lowercase()) || sym.contains('-')
+}
+
+// Get the root object and property path for a JSX member expression.
+fn jsx_member_words(member: &JSXMemberExpr) -> (JsWord, Vec<JsWord>) {
+    let (word, mut parts) = match &member.obj {
+        JSXObject::Ident(id) => (id.sym.clone(), Vec::new()),
+        JSXObject::JSXMemberExpr(member) => jsx_member_words(member),
+    };
+    parts.push(member.prop.sym.clone());
+    (word, parts)
+}
+
+// Find the local for a builtin that declares the symbol; member
+// paths are matched using the full path.
+fn builtin_local<'a>(builtin: &'a Builtin, sym: &JsWord) -> Option<&'a Local> {
+    builtin.locals.iter().find(|local| {
+        let word = match local {
+            Local::Default(word) => word,
+            Local::Named(word) => word,
+            Local::Alias(word, _) => word,
+            Local::Member(_, _) => return false,
+        };
+        word == sym
+    })
+}
+
+// Get the identifier that is the target of an assignment.
+fn assign_ident(n: &PatOrExpr) -> Option<&JsWord> {
+    match n {
+        PatOrExpr::Expr(expr) => match &**expr {
+            Expr::Ident(id) => Some(&id.sym),
+            _ => None,
+        },
+        PatOrExpr::Pat(pat) => match &**pat {
+            Pat::Ident(ident) => Some(&ident.id.sym),
+            _ => None,
+        },
+    }
+}
+
+// Get the words for a member expression that is the target of
+// an assignment; the member expression must start with an identifier.
+fn assign_member_words(n: &MemberExpr) -> Option<Vec<JsWord>> {
+    let mut expressions = Vec::new();
+    walk(n, &mut expressions);
+    let mut words = Vec::new();
+    for (index, expr) in expressions.iter().enumerate() {
+        match expr {
+            Expr::Ident(id) => words.push(id.sym.clone()),
+            Expr::Lit(Lit::Str(s)) if index > 0 => words.push(s.value.clone()),
+            _ => return None,
+        }
+    }
+    if words.is_empty() {
+        None
+    } else {
+        Some(words)
     }
 }
 
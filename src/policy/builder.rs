@@ -1,38 +1,105 @@
 //! Build a package policy.
 
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::{bail, Result};
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 
 use swc_atoms::JsWord;
-use swc_common::{FileName, SourceMap};
+use swc_common::{FileName, SourceMap, Span};
 use swc_ecma_ast::TargetEnv;
-use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
-use swc_ecma_visit::VisitWith;
+use swc_ecma_loader::resolve::Resolve;
 
 use rayon::prelude::*;
 
-use super::{PackagePolicy, Policy, PolicyAccess};
+use super::{cache::AnalysisCache, PackagePolicy, Policy, PolicyAccess};
 use crate::{
+    access::Access,
+    error::BasaltError,
     helpers::normalize_specifier,
     module::{
-        base::module_base_directory,
+        base::{
+            module_base_directory, module_package_name, module_package_version,
+            root_relative,
+        },
         dependencies::is_dependent_module,
-        node::{cached_modules, parse_file, VisitedDependency, VisitedModule},
+        node::{
+            cached_modules, parse_file_with_timings, parse_source, ModuleNode,
+            VisitedDependency, VisitedModule,
+        },
+        resolver::ExportsResolver,
     },
-    policy::analysis::{flatten, globals_scope::GlobalAnalysis, join_words},
+    policy::analysis::{
+        analyze_module_dependencies, builtin::access_for, flatten, join_words,
+        AnalysisOptions, ModuleAnalysis,
+    },
+    static_module_record::TransformSource,
+    stats::{time, Phase, Timings},
+    swc_utils::span_to_location,
 };
 
+const WITH_STATEMENT: &str =
+    "with statement may hide access to globals and builtins";
+const DYNAMIC_REQUIRE: &str =
+    "require() with a computed module specifier cannot be analyzed";
+const EVAL_CALL: &str = "eval() evaluates code that cannot be analyzed";
+const FUNCTION_CALL: &str =
+    "Function constructor evaluates code that cannot be analyzed";
+const TIMER_CALL: &str =
+    "timer with a string argument evaluates code that cannot be analyzed";
+
+/// Granularity of the builtin entries in a generated policy.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BuiltinGranularity {
+    /// Grant access to the whole module, eg: `fs`.
+    Module,
+    /// Grant access to each member of the module that is
+    /// used, eg: `fs.readFile` and `fs.writeFile`.
+    Member,
+}
+
+impl Default for BuiltinGranularity {
+    fn default() -> Self {
+        BuiltinGranularity::Member
+    }
+}
+
+impl FromStr for BuiltinGranularity {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "module" => Ok(BuiltinGranularity::Module),
+            "member" => Ok(BuiltinGranularity::Member),
+            _ => bail!("unknown builtin granularity {}", s),
+        }
+    }
+}
+
+/// Warning generated when analyzing the modules for a policy.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PolicyWarning {
+    /// Package specifier.
+    pub package: String,
+    /// Path to the module file.
+    pub file: PathBuf,
+    /// Line number (1-indexed) when known.
+    pub line: Option<usize>,
+    /// Column number (0-indexed) when known.
+    pub column: Option<usize>,
+    /// Warning message.
+    pub message: String,
+}
+
 /// Generate a policy.
 ///
 /// This needs to determine a base path for each module so that we
 /// can group modules to the package that they belong to in order
 /// to convert a list of all modules into a collection of packages.
 pub struct PolicyBuilder {
-    entry: PathBuf,
+    entry: TransformSource,
     resolver: Box<dyn Resolve>,
     source_map: Arc<SourceMap>,
     /// Package buckets used the module specifier and the base path
@@ -46,33 +113,183 @@ pub struct PolicyBuilder {
     /// Cumulative analysis for a package by merging the analysis for
     /// each module in the package.
     package_analysis: Policy,
+
+    /// Additional module names to treat as builtins.
+    extra_builtins: HashSet<String>,
+
+    /// Emit read, write and execute permissions for globals and builtins.
+    access_detail: bool,
+
+    /// Granularity of the builtin entries.
+    builtin_granularity: BuiltinGranularity,
+
+    /// Omit packages that are not granted any capabilities.
+    prune_empty: bool,
+
+    /// Include the version of each package in the policy.
+    package_versions: bool,
+
+    /// Project root for the paths in warnings.
+    root: Option<PathBuf>,
+
+    /// Directory used to cache the analysis for each module.
+    cache_dir: Option<PathBuf>,
+
+    /// Versions found for each package specifier.
+    versions: HashMap<String, BTreeSet<String>>,
+
+    /// Warnings generated during analysis.
+    warnings: Vec<PolicyWarning>,
+
+    /// Collector for the time spent in each phase.
+    timings: Option<Arc<Timings>>,
 }
 
 impl PolicyBuilder {
     /// Create a package builder.
     pub fn new(entry: PathBuf) -> Self {
+        Self::from_entry(TransformSource::File(entry))
+    }
+
+    /// Create a package builder for source code that does not
+    /// need to exist on disc.
+    ///
+    /// The file name is used to resolve the dependencies of the source.
+    pub fn from_source(content: String, file_name: String) -> Self {
+        Self::from_entry(TransformSource::Str { content, file_name })
+    }
+
+    fn from_entry(entry: TransformSource) -> Self {
         Self {
             entry,
-            resolver: Box::new(NodeModulesResolver::new(
-                TargetEnv::Node,
-                Default::default(),
-            )),
+            resolver: Box::new(ExportsResolver::new(TargetEnv::Node)),
             source_map: Arc::new(Default::default()),
             package_buckets: Default::default(),
             package_groups: Default::default(),
             package_analysis: Default::default(),
+            extra_builtins: Default::default(),
+            access_detail: false,
+            builtin_granularity: Default::default(),
+            prune_empty: true,
+            package_versions: false,
+            root: None,
+            cache_dir: None,
+            versions: Default::default(),
+            warnings: Default::default(),
+            timings: None,
         }
     }
 
+    /// Set the source map used to parse modules.
+    ///
+    /// Parsed modules are cached for the process so pass the same
+    /// source map to the bundler to share the modules and keep the
+    /// spans of shared modules valid for both builders.
+    pub fn source_map(mut self, source_map: Arc<SourceMap>) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Set module names to treat as builtins in addition to the
+    /// node builtin modules.
+    ///
+    /// Use this for modules provided by the host environment such as
+    /// `electron`; they are added to the `builtin` policy entries and are
+    /// not analyzed as dependent packages.
+    pub fn extra_builtins(mut self, extra_builtins: HashSet<String>) -> Self {
+        self.extra_builtins = extra_builtins;
+        self
+    }
+
+    /// Emit the read, write and execute permissions for globals and
+    /// builtins rather than a boolean flag.
+    pub fn access_detail(mut self, access_detail: bool) -> Self {
+        self.access_detail = access_detail;
+        self
+    }
+
+    /// Set whether builtin access is granted for the whole module
+    /// or for each member, defaults to [BuiltinGranularity::Member].
+    ///
+    /// When the access detail is enabled the access for a module is
+    /// the merged access for all the members of the module.
+    pub fn builtin_granularity(
+        mut self,
+        builtin_granularity: BuiltinGranularity,
+    ) -> Self {
+        self.builtin_granularity = builtin_granularity;
+        self
+    }
+
+    /// Omit packages without any builtins, globals or packages from
+    /// the finalized policy, defaults to `true`.
+    pub fn prune_empty(mut self, prune_empty: bool) -> Self {
+        self.prune_empty = prune_empty;
+        self
+    }
+
+    /// Include the `version` from the `package.json` for each package
+    /// in the policy.
+    ///
+    /// When multiple versions of a package are installed they are
+    /// merged into a single policy so the versions are comma separated.
+    pub fn package_versions(mut self, package_versions: bool) -> Self {
+        self.package_versions = package_versions;
+        self
+    }
+
+    /// Set the project root so that the file paths for warnings are
+    /// relative to the root; paths outside the root are absolute.
+    pub fn root(mut self, root: PathBuf) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Set a directory to cache the analysis for each module.
+    ///
+    /// Modules are keyed by a hash of their content so only modules
+    /// that changed since a previous run are analyzed; the generated
+    /// policy is the same as when the cache is not used.
+    pub fn cache_dir(mut self, cache_dir: PathBuf) -> Self {
+        self.cache_dir = Some(cache_dir);
+        self
+    }
+
+    /// Set a collector for the time spent in each phase.
+    pub fn timings(mut self, timings: Arc<Timings>) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+
     /// Load the entry point module and all dependencies grouping modules
     /// into dependent package buckets.
     pub fn load(mut self) -> Result<Self> {
         let sm = Arc::clone(&self.source_map);
-        let module = parse_file(
-            &self.entry,
-            &self.resolver,
-            Arc::clone(&self.source_map),
-        )?;
+        let extra_builtins = self.extra_builtins.clone();
+        let timings = self.timings.clone();
+        let content = self.entry.read()?;
+        let module = match &self.entry {
+            TransformSource::File(path) => parse_file_with_timings(
+                path,
+                &self.resolver,
+                Arc::clone(&self.source_map),
+                timings.as_deref(),
+            )?,
+            TransformSource::Str { content, file_name } => parse_source(
+                content,
+                file_name,
+                &self.resolver,
+                Arc::clone(&self.source_map),
+                timings.as_deref(),
+            )?,
+            TransformSource::Reader { file_name, .. } => parse_source(
+                content.as_ref().unwrap(),
+                file_name,
+                &self.resolver,
+                Arc::clone(&self.source_map),
+                timings.as_deref(),
+            )?,
+        };
 
         let node = match &*module {
             VisitedModule::Module(_, node) => Some(node),
@@ -81,7 +298,9 @@ impl PolicyBuilder {
         };
 
         let mut visitor = |dep: VisitedDependency| {
-            if is_dependent_module(&dep.spec) {
+            if is_dependent_module(&dep.spec)
+                && !extra_builtins.contains(&dep.spec)
+            {
                 match dep.file_name {
                     FileName::Real(path) => {
                         if let Some(module_base) = module_base_directory(&path)
@@ -91,11 +310,27 @@ impl PolicyBuilder {
                                 &dep.spec,
                                 module_base.display()
                             );
+                            // Key on the name of the owning package so
+                            // the policy does not depend on how the
+                            // package was imported or installed
+                            let key = module_package_name(&path)
+                                .unwrap_or_else(|| dep.spec.clone());
+                            if let Some(version) = module_package_version(&path)
+                            {
+                                self.versions
+                                    .entry(normalize_specifier(&key))
+                                    .or_default()
+                                    .insert(version);
+                            }
                             self.package_buckets
-                                .entry((dep.spec.clone(), module_base))
+                                .entry((key, module_base))
                                 .or_insert(Default::default());
                         } else {
-                            bail!("Failed to resolve module base for specifier {}", &dep.spec);
+                            return Err(BasaltError::Resolve(format!(
+                                "Failed to resolve module base for specifier {}",
+                                &dep.spec
+                            ))
+                            .into());
                         }
                     }
                     _ => {}
@@ -106,7 +341,7 @@ impl PolicyBuilder {
         };
 
         if let Some(node) = node {
-            node.visit(sm, &mut visitor)?;
+            node.visit_with_timings(sm, timings.as_deref(), &mut visitor)?;
         }
 
         // Sort the module base keys as we need to find the deepest match
@@ -176,112 +411,301 @@ impl PolicyBuilder {
     }
 
     /// Analyze and aggregate the modules for all dependent packages.
+    ///
+    /// Packages are analyzed in order of their specifiers so the
+    /// warnings are in the same order for every run.
     pub fn analyze(mut self) -> Result<Self> {
-        let groups = std::mem::take(&mut self.package_groups);
+        let mut groups: Vec<_> = std::mem::take(&mut self.package_groups)
+            .into_iter()
+            .collect();
+        groups.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let options = AnalysisOptions {
+            extra_builtins: self.extra_builtins.clone(),
+            access_detail: self.access_detail,
+        };
+        let cache = match &self.cache_dir {
+            Some(dir) => Some(AnalysisCache::new(dir.clone())?),
+            None => None,
+        };
 
         let analyzed: Vec<_> = groups
             .into_par_iter()
             .map(|(spec, modules)| {
-                let result = analyze_modules(&spec, modules);
+                let result =
+                    time(self.timings.as_deref(), Phase::Analyze, || {
+                        analyze_modules(
+                            &spec,
+                            modules,
+                            &options,
+                            self.builtin_granularity,
+                            cache.as_ref(),
+                        )
+                    });
                 (spec, result)
             })
             .collect();
 
         for (spec, policy) in analyzed {
-            let analysis = policy?;
-            if !analysis.is_empty() {
-                self.package_analysis.insert(spec, analysis);
+            let (mut analysis, warnings) = policy?;
+            for (file, line, column, message) in warnings {
+                let warning = PolicyWarning {
+                    package: spec.clone(),
+                    file: root_relative(&file, self.root.as_deref()),
+                    line: Some(line),
+                    column: Some(column),
+                    message: String::from(message),
+                };
+                log::warn!(
+                    "{}:{}:{} {} ({})",
+                    warning.file.display(),
+                    warning.line.unwrap_or_default(),
+                    warning.column.unwrap_or_default(),
+                    warning.message,
+                    warning.package,
+                );
+                self.warnings.push(warning);
             }
+            if self.package_versions {
+                analysis.version = self.versions.get(&spec).map(|versions| {
+                    versions.iter().cloned().collect::<Vec<_>>().join(", ")
+                });
+            }
+            self.package_analysis.insert(spec, analysis);
         }
 
         Ok(self)
     }
 
+    /// Warnings generated by the analysis.
+    ///
+    /// A `with` statement changes how symbols are resolved and the
+    /// module for a `require()` call with a computed argument is not
+    /// known so the generated policy may be incomplete for the package.
+    pub fn warnings(&self) -> &[PolicyWarning] {
+        &self.warnings
+    }
+
     /// Generate a package policy file.
-    pub fn finalize(self) -> Policy {
+    ///
+    /// All the collections in the policy are ordered by key so
+    /// generating a policy for the same input is idempotent.
+    pub fn finalize(mut self) -> Policy {
+        if self.prune_empty {
+            self.package_analysis.prune_empty();
+        }
         self.package_analysis
     }
 }
 
+/// Get the policy access for a path.
+fn policy_access(
+    words: &[JsWord],
+    access: &IndexMap<Vec<JsWord>, Access>,
+    access_detail: bool,
+) -> PolicyAccess {
+    if access_detail {
+        access_for(words, access).into()
+    } else {
+        true.into()
+    }
+}
+
+/// Analyze a module reusing the analysis from the cache when
+/// the module content has not changed.
+fn analyze_node(
+    node: &ModuleNode,
+    options: &AnalysisOptions,
+    analysis_cache: Option<&AnalysisCache>,
+) -> Result<ModuleAnalysis> {
+    let deps = node.dependencies.as_deref().unwrap_or_default();
+    let analysis_cache = match analysis_cache {
+        Some(analysis_cache) => analysis_cache,
+        None => {
+            return Ok(analyze_module_dependencies(&node.module, deps, options))
+        }
+    };
+
+    // Key on the source that was parsed, the file on disc may have
+    // changed or the module may have been parsed from memory
+    let source_file = span_to_location(&node.source_map, node.module.span).file;
+    let key = AnalysisCache::key(source_file.src.as_bytes(), options);
+    let base = node.module.span.lo;
+    if let Some(analysis) = analysis_cache.get(&key, base) {
+        return Ok(analysis);
+    }
+
+    let analysis = analyze_module_dependencies(&node.module, deps, options);
+    analysis_cache.insert(&key, base, &analysis)?;
+    Ok(analysis)
+}
+
+/// Get the policy key for a dependent package of a module.
+///
+/// Packages are keyed on the name of the owning package of the
+/// resolved module so an aliased install is granted by the same
+/// name as the package resources.
+fn package_key(node: &ModuleNode, pkg: String) -> String {
+    node.all_resolved
+        .iter()
+        .find(|(spec, _)| normalize_specifier(spec) == pkg)
+        .and_then(|(_, file_name)| match file_name {
+            FileName::Real(path) => module_package_name(path),
+            _ => None,
+        })
+        .map(normalize_specifier)
+        .unwrap_or(pkg)
+}
+
+/// Get the warning message for a function called to evaluate code.
+fn dangerous_message(name: &str) -> &'static str {
+    match name {
+        "eval" => EVAL_CALL,
+        "Function" => FUNCTION_CALL,
+        _ => TIMER_CALL,
+    }
+}
+
+/// Line, column and message for a warning.
+type Location = (usize, usize, &'static str);
+
+/// Get the locations of the spans for a module.
+///
+/// Spans are positions in the source map used to parse the module
+/// which is not the source map for the builder when the module was
+/// cached by another builder.
+fn span_locations(
+    node: &ModuleNode,
+    spans: &[Span],
+    message: &'static str,
+) -> Vec<Location> {
+    spans
+        .iter()
+        .map(|span| {
+            let loc = span_to_location(&node.source_map, *span);
+            (loc.line, loc.col_display, message)
+        })
+        .collect()
+}
+
 /// Walk all the modules in a package and perform a cumulative analysis.
 fn analyze_modules(
     spec: &str,
     modules: HashSet<PathBuf>,
-) -> Result<PackagePolicy> {
+    options: &AnalysisOptions,
+    builtin_granularity: BuiltinGranularity,
+    analysis_cache: Option<&AnalysisCache>,
+) -> Result<(PackagePolicy, Vec<(PathBuf, usize, usize, &'static str)>)> {
     let cache = cached_modules();
 
     // Aggregated analysis data
     let mut analysis: PackagePolicy = Default::default();
 
-    let data: Vec<(
-        IndexSet<Vec<JsWord>>,
-        IndexSet<Vec<JsWord>>,
-        IndexSet<String>,
-    )> = modules
-        .into_par_iter()
-        .map(|module_key| {
-            let cached_module = cache.get(&module_key).unwrap();
-            let visited_module = cached_module.value();
-            if let VisitedModule::Module(_, node) = &**visited_module {
-                // Compute globals
-                let mut globals_scope = GlobalAnalysis::new(Default::default());
-                node.module.visit_children_with(&mut globals_scope);
-                let globals = globals_scope.compute_globals();
-
-                // Compute builtins
-                let builtin = globals_scope.compute_builtins();
-
-                // Compute dependent packages
-                let packages = if let Some(deps) = &node.dependencies {
-                    deps.iter()
-                        .filter_map(|dep| {
-                            let normalized =
-                                normalize_specifier(dep.specifier.as_ref());
-                            // Some packages such as @babel/runtime can end up with
-                            // themselves in the dependency list so we explicitly disallow this
-                            if spec != &normalized
-                                && is_dependent_module(dep.specifier.as_ref())
-                            {
-                                Some(normalized)
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<IndexSet<String>>()
-                } else {
-                    IndexSet::new()
-                };
-
-                return (globals, builtin, packages);
-            }
-            (IndexSet::new(), IndexSet::new(), IndexSet::new())
-        })
-        .collect();
+    // Visit modules in a stable order as the iteration order of the
+    // set changes between runs
+    let mut modules: Vec<PathBuf> = modules.into_iter().collect();
+    modules.sort();
 
+    let data: Vec<(PathBuf, ModuleAnalysis, Vec<Location>)> = modules
+        .into_par_iter()
+        .map(
+            |module_key| -> Result<(PathBuf, ModuleAnalysis, Vec<Location>)> {
+                let cached_module = cache.get(&module_key).unwrap();
+                let visited_module = cached_module.value();
+                let (mut analysis, locations) =
+                    if let VisitedModule::Module(_, node) = &**visited_module {
+                        let mut analysis =
+                            analyze_node(node, options, analysis_cache)?;
+                        // Grant the packages using the same key as the
+                        // package resources
+                        analysis.packages = analysis
+                            .packages
+                            .into_iter()
+                            .map(|pkg| package_key(node, pkg))
+                            .collect();
+                        let mut locations = span_locations(
+                            node,
+                            &analysis.with_statements,
+                            WITH_STATEMENT,
+                        );
+                        locations.extend(span_locations(
+                            node,
+                            &analysis.dynamic_requires,
+                            DYNAMIC_REQUIRE,
+                        ));
+                        for (name, span) in analysis.dangerous.iter() {
+                            locations.extend(span_locations(
+                                node,
+                                &[*span],
+                                dangerous_message(name),
+                            ));
+                        }
+                        (analysis, locations)
+                    } else {
+                        (Default::default(), vec![])
+                    };
+                // Some packages such as @babel/runtime can end up with
+                // themselves in the dependency list so we explicitly disallow this
+                analysis.packages.retain(|pkg| pkg != spec);
+                Ok((module_key, analysis, locations))
+            },
+        )
+        .collect::<Result<_>>()?;
     // Group the computations for each package
     let mut pkg_globals = IndexSet::new();
     let mut pkg_builtin = IndexSet::new();
     let mut pkg_packages = IndexSet::new();
-
-    for (globals, builtin, packages) in data {
-        pkg_globals = pkg_globals.union(&globals).cloned().collect();
-        pkg_builtin = pkg_builtin.union(&builtin).cloned().collect();
-        pkg_packages = pkg_packages.union(&packages).cloned().collect();
+    let mut pkg_globals_access: IndexMap<Vec<JsWord>, Access> = IndexMap::new();
+    let mut pkg_builtin_access: IndexMap<Vec<JsWord>, Access> = IndexMap::new();
+    let mut warnings = Vec::new();
+
+    for (file, item, locations) in data {
+        pkg_globals = pkg_globals.union(&item.globals).cloned().collect();
+        pkg_builtin = pkg_builtin.union(&item.builtin).cloned().collect();
+        pkg_packages = pkg_packages.union(&item.packages).cloned().collect();
+        for (k, v) in item.globals_access {
+            pkg_globals_access.entry(k).or_default().merge(&v);
+        }
+        for (k, v) in item.builtin_access {
+            pkg_builtin_access.entry(k).or_default().merge(&v);
+        }
+        warnings.extend(locations.into_iter().map(
+            |(line, column, message)| (file.clone(), line, column, message),
+        ));
     }
 
     // Flatten globals and builtins
     pkg_globals = flatten(pkg_globals);
     pkg_builtin = flatten(pkg_builtin);
+    if let BuiltinGranularity::Module = builtin_granularity {
+        pkg_builtin = pkg_builtin
+            .into_iter()
+            .map(|words| words.into_iter().take(1).collect())
+            .collect();
+    }
 
     // Build the maps for the policy file
     let mut globals_map: BTreeMap<String, PolicyAccess> = pkg_globals
         .into_iter()
-        .map(|words| (join_words(&words).as_ref().to_string(), true.into()))
+        .map(|words| {
+            let access = policy_access(
+                &words,
+                &pkg_globals_access,
+                options.access_detail,
+            );
+            (join_words(&words).as_ref().to_string(), access)
+        })
         .collect();
 
     let mut builtin_map: BTreeMap<String, PolicyAccess> = pkg_builtin
         .into_iter()
-        .map(|words| (join_words(&words).as_ref().to_string(), true.into()))
+        .map(|words| {
+            let access = policy_access(
+                &words,
+                &pkg_builtin_access,
+                options.access_detail,
+            );
+            (join_words(&words).as_ref().to_string(), access)
+        })
         .collect();
 
     let mut packages_map: BTreeMap<String, PolicyAccess> = pkg_packages
@@ -293,5 +717,5 @@ fn analyze_modules(
     analysis.builtin.append(&mut builtin_map);
     analysis.packages.append(&mut packages_map);
 
-    Ok(analysis)
+    Ok((analysis, warnings))
 }
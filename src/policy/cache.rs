@@ -0,0 +1,181 @@
+//! Cache the analysis for modules on disc.
+//!
+//! Entries are keyed by a SHA-256 hash of the module content and the
+//! analysis options so that modules which have not changed since a
+//! previous run do not need to be analyzed again. Entries that cannot
+//! be read are treated as a cache miss.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use swc_atoms::JsWord;
+use swc_common::{BytePos, Span};
+
+use crate::{
+    access::Access,
+    policy::analysis::{AnalysisOptions, ModuleAnalysis},
+};
+
+/// Changes when the format of the cache entries changes.
+const CACHE_VERSION: &[u8] = b"5";
+
+/// Module analysis stored in the cache.
+///
+/// Spans for warnings are stored as offsets from the start of the
+/// module as byte positions depend upon the source map.
+#[derive(Serialize, Deserialize)]
+struct CachedAnalysis {
+    globals: Vec<Vec<String>>,
+    builtin: Vec<Vec<String>>,
+    packages: Vec<String>,
+    globals_access: Vec<(Vec<String>, Access)>,
+    builtin_access: Vec<(Vec<String>, Access)>,
+    with_statements: Vec<(u32, u32)>,
+    dynamic_requires: Vec<(u32, u32)>,
+    dangerous: Vec<(String, (u32, u32))>,
+    resolved: Vec<String>,
+}
+
+/// Directory of module analysis results keyed by content hash.
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    dir: PathBuf,
+}
+
+impl AnalysisCache {
+    /// Create a cache in a directory, the directory is created
+    /// if it does not exist.
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).context(format!(
+            "unable to create cache directory {}",
+            dir.display()
+        ))?;
+        Ok(Self { dir })
+    }
+
+    /// Compute the cache key for module content analyzed
+    /// using the given options.
+    pub fn key(content: &[u8], options: &AnalysisOptions) -> String {
+        let mut extra_builtins: Vec<&String> =
+            options.extra_builtins.iter().collect();
+        extra_builtins.sort();
+
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_VERSION);
+        hasher.update([options.access_detail as u8]);
+        for name in extra_builtins {
+            hasher.update([0u8]);
+            hasher.update(name.as_bytes());
+        }
+        hasher.update([0u8]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Get the analysis for a key.
+    ///
+    /// The base is the start position of the module used to
+    /// compute the spans for warnings.
+    pub fn get(&self, key: &str, base: BytePos) -> Option<ModuleAnalysis> {
+        let contents = std::fs::read(self.path(key)).ok()?;
+        let cached: CachedAnalysis = serde_json::from_slice(&contents).ok()?;
+        Some(ModuleAnalysis {
+            globals: cached.globals.into_iter().map(words).collect(),
+            builtin: cached.builtin.into_iter().map(words).collect(),
+            packages: cached.packages.into_iter().collect(),
+            globals_access: cached
+                .globals_access
+                .into_iter()
+                .map(|(k, v)| (words(k), v))
+                .collect(),
+            builtin_access: cached
+                .builtin_access
+                .into_iter()
+                .map(|(k, v)| (words(k), v))
+                .collect(),
+            with_statements: spans(cached.with_statements, base),
+            dynamic_requires: spans(cached.dynamic_requires, base),
+            dangerous: cached
+                .dangerous
+                .into_iter()
+                .map(|(name, (lo, hi))| {
+                    (JsWord::from(name), span(lo, hi, base))
+                })
+                .collect(),
+            resolved: cached.resolved.into_iter().map(JsWord::from).collect(),
+        })
+    }
+
+    /// Store the analysis for a key.
+    pub fn insert(
+        &self,
+        key: &str,
+        base: BytePos,
+        analysis: &ModuleAnalysis,
+    ) -> Result<()> {
+        let cached = CachedAnalysis {
+            globals: analysis.globals.iter().map(|w| strings(w)).collect(),
+            builtin: analysis.builtin.iter().map(|w| strings(w)).collect(),
+            packages: analysis.packages.iter().cloned().collect(),
+            globals_access: analysis
+                .globals_access
+                .iter()
+                .map(|(k, v)| (strings(k), *v))
+                .collect(),
+            builtin_access: analysis
+                .builtin_access
+                .iter()
+                .map(|(k, v)| (strings(k), *v))
+                .collect(),
+            with_statements: offsets(&analysis.with_statements, base),
+            dynamic_requires: offsets(&analysis.dynamic_requires, base),
+            dangerous: analysis
+                .dangerous
+                .iter()
+                .map(|(name, span)| (name.to_string(), offset(span, base)))
+                .collect(),
+            resolved: analysis.resolved.iter().map(|w| w.to_string()).collect(),
+        };
+        std::fs::write(self.path(key), serde_json::to_vec(&cached)?)?;
+        Ok(())
+    }
+
+    /// Directory for the cache entries.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+fn words(path: Vec<String>) -> Vec<JsWord> {
+    path.into_iter().map(JsWord::from).collect()
+}
+
+fn strings(words: &[JsWord]) -> Vec<String> {
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+fn span(lo: u32, hi: u32, base: BytePos) -> Span {
+    Span::new(base + BytePos(lo), base + BytePos(hi), Default::default())
+}
+
+fn offset(span: &Span, base: BytePos) -> (u32, u32) {
+    ((span.lo - base).0, (span.hi - base).0)
+}
+
+fn spans(offsets: Vec<(u32, u32)>, base: BytePos) -> Vec<Span> {
+    offsets
+        .into_iter()
+        .map(|(lo, hi)| span(lo, hi, base))
+        .collect()
+}
+
+fn offsets(spans: &[Span], base: BytePos) -> Vec<(u32, u32)> {
+    spans.iter().map(|span| offset(span, base)).collect()
+}
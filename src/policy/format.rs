@@ -0,0 +1,110 @@
+//! Formats for policy files.
+//!
+//! Policy files are JSON by default; YAML and TOML are supported
+//! when the `yaml` and `toml` features are enabled. The format for
+//! a file is determined by the file extension.
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
+use super::Policy;
+use crate::{
+    error::BasaltError,
+    json::{self, Indent},
+};
+
+/// Format of a policy file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PolicyFormat {
+    /// JSON policy file, the `.json` extension.
+    Json,
+    /// YAML policy file, the `.yaml` or `.yml` extensions.
+    Yaml,
+    /// TOML policy file, the `.toml` extension.
+    Toml,
+}
+
+impl Default for PolicyFormat {
+    fn default() -> Self {
+        PolicyFormat::Json
+    }
+}
+
+impl fmt::Display for PolicyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyFormat::Json => write!(f, "JSON"),
+            PolicyFormat::Yaml => write!(f, "YAML"),
+            PolicyFormat::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+impl FromStr for PolicyFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(PolicyFormat::Json),
+            "yaml" | "yml" => Ok(PolicyFormat::Yaml),
+            "toml" => Ok(PolicyFormat::Toml),
+            _ => bail!(
+                "invalid policy format {}, expected json, yaml or toml",
+                s
+            ),
+        }
+    }
+}
+
+impl PolicyFormat {
+    /// Get the format for a policy file from the file extension.
+    ///
+    /// Files without a known extension are treated as JSON.
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.to_lowercase().parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Parse a policy in this format.
+    pub fn parse(&self, source: &str) -> Result<Policy> {
+        match self {
+            PolicyFormat::Json => Ok(serde_json::from_str(source)?),
+            #[cfg(feature = "yaml")]
+            PolicyFormat::Yaml => Ok(serde_yaml::from_str(source)?),
+            #[cfg(feature = "toml")]
+            PolicyFormat::Toml => Ok(toml_rs::from_str(source)?),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported().into()),
+        }
+    }
+
+    /// Serialize a policy in this format.
+    ///
+    /// The indentation only applies to JSON.
+    pub fn serialize(&self, policy: &Policy, indent: Indent) -> Result<String> {
+        match self {
+            PolicyFormat::Json => json::to_string_pretty(policy, indent),
+            #[cfg(feature = "yaml")]
+            PolicyFormat::Yaml => Ok(serde_yaml::to_string(policy)?),
+            // Convert to a value first as the value serializer
+            // writes the plain keys before any nested tables.
+            #[cfg(feature = "toml")]
+            PolicyFormat::Toml => Ok(toml_rs::to_string_pretty(
+                &toml_rs::Value::try_from(policy)?,
+            )?),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported().into()),
+        }
+    }
+
+    fn unsupported(&self) -> BasaltError {
+        BasaltError::Policy(format!(
+            "{} policy files require the {} feature",
+            self,
+            format!("{}", self).to_lowercase()
+        ))
+    }
+}
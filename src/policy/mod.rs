@@ -1,14 +1,23 @@
 //! Types for LavaMoat policy files.
 
+use anyhow::{Context, Result};
 use serde::{
-    de::{self, Visitor},
+    de::{self, MapAccess, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
+use serde_json::Value;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::{access::Access, error::BasaltError};
 
 pub mod analysis;
 pub mod builder;
+pub mod cache;
+pub mod format;
+
+pub use format::PolicyFormat;
 
 /// Trait for the merge operation.
 pub trait Merge {
@@ -23,11 +32,183 @@ pub struct Policy {
     pub resources: BTreeMap<String, PackagePolicy>,
 }
 
+/// Keys allowed at the top level of a policy.
+const POLICY_KEYS: &[&str] = &["resources"];
+
+/// Keys allowed in a package policy.
+const PACKAGE_POLICY_KEYS: &[&str] =
+    &["version", "native", "env", "builtin", "globals", "packages"];
+
 impl Policy {
     /// Insert a policy into the package resources.
     pub fn insert<S: AsRef<str>>(&mut self, key: S, value: PackagePolicy) {
         self.resources.insert(key.as_ref().into(), value);
     }
+
+    /// Remove packages that are not granted any capabilities.
+    ///
+    /// Packages that set the `native` flag or a non-default `env`
+    /// are retained.
+    pub fn prune_empty(&mut self) {
+        self.resources.retain(|_, pkg| {
+            !pkg.is_empty() || pkg.native || !EnvPolicy::is_default(&pkg.env)
+        });
+    }
+
+    /// Normalize the policy to a canonical form.
+    ///
+    /// Maps are already ordered by key so normalizing removes the
+    /// entries that do not grant access, an access object without
+    /// any flags set is equivalent to `false`; removal markers are
+    /// retained so override policies keep their meaning.
+    pub fn normalize(&mut self) {
+        for pkg in self.resources.values_mut() {
+            pkg.builtin.normalize();
+            pkg.globals.normalize();
+            pkg.packages.normalize();
+        }
+    }
+
+    /// Parse a policy and error on unknown keys.
+    ///
+    /// Deserializing with `serde` ignores unknown keys so a misspelled
+    /// key such as `gobals` would silently grant nothing; this function
+    /// reports the path to the first unknown key instead.
+    pub fn from_str_strict(source: &str) -> Result<Policy> {
+        let value: Value = serde_json::from_str(source)?;
+        validate_keys(&value, POLICY_KEYS, "policy")?;
+        if let Some(resources) = value.get("resources") {
+            if let Value::Object(packages) = resources {
+                for (name, package) in packages {
+                    validate_keys(
+                        package,
+                        PACKAGE_POLICY_KEYS,
+                        &format!("resources.{}", name),
+                    )?;
+                }
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Load a policy file.
+    ///
+    /// The format of the file is determined by the file extension.
+    pub fn load_file(file: &Path) -> Result<Policy> {
+        let format = PolicyFormat::from_path(file);
+        let contents = std::fs::read_to_string(file).context(format!(
+            "Unable to read policy file {}",
+            file.display()
+        ))?;
+        format.parse(&contents).context(format!(
+            "Failed to parse {} in {}",
+            format,
+            file.display()
+        ))
+    }
+
+    /// Load policy files and merge them in order.
+    ///
+    /// The files may use a mix of formats.
+    pub fn load_files(files: &[PathBuf]) -> Result<Policy> {
+        let mut result: Policy = Default::default();
+        for file in files {
+            result.merge(&Policy::load_file(file)?);
+        }
+        Ok(result)
+    }
+
+    /// Find the capabilities used by packages in another policy
+    /// that are not granted by this policy.
+    ///
+    /// Globals and builtins are granted when the policy grants the
+    /// entry or a parent of the entry, eg: `process` grants `process.env`.
+    pub fn violations(&self, used: &Policy) -> Vec<Violation> {
+        let empty: PackagePolicy = Default::default();
+        let mut result = Vec::new();
+        for (package, usage) in used.resources.iter() {
+            let granted = self.resources.get(package).unwrap_or(&empty);
+            let groups = [
+                (ViolationKind::Builtin, &usage.builtin, &granted.builtin),
+                (ViolationKind::Global, &usage.globals, &granted.globals),
+                (ViolationKind::Package, &usage.packages, &granted.packages),
+            ];
+            for (kind, used_group, granted_group) in groups.iter() {
+                let parents = *kind != ViolationKind::Package;
+                for (name, access) in used_group.map.iter() {
+                    if access.flag && !granted_group.is_granted(name, parents) {
+                        result.push(Violation {
+                            package: package.to_string(),
+                            kind: *kind,
+                            name: name.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Find the entries granted by this policy that are not used by
+    /// the packages in another policy.
+    ///
+    /// Globals and builtins are used when the other policy uses the
+    /// entry or a member path related to the entry, eg: a grant for
+    /// `process` is used by `process.env`.
+    pub fn unused(&self, used: &Policy) -> BTreeMap<String, UnusedGrants> {
+        let empty: PackagePolicy = Default::default();
+        let mut result = BTreeMap::new();
+        for (package, granted) in self.resources.iter() {
+            let usage = used.resources.get(package).unwrap_or(&empty);
+            let grants = UnusedGrants {
+                builtin: granted.builtin.unused(&usage.builtin, true),
+                globals: granted.globals.unused(&usage.globals, true),
+                packages: granted.packages.unused(&usage.packages, false),
+            };
+            if !grants.is_empty() {
+                result.insert(package.to_string(), grants);
+            }
+        }
+        result
+    }
+
+    /// Compute the difference between this policy and another policy.
+    pub fn diff(&self, other: &Policy) -> PolicyDiff {
+        let mut result: PolicyDiff = Default::default();
+        for (k, v) in other.resources.iter() {
+            if let Some(pkg) = self.resources.get(k) {
+                let pkg_diff = pkg.diff(v);
+                if !pkg_diff.is_empty() {
+                    result.changed.insert(k.to_string(), pkg_diff);
+                }
+            } else {
+                result.added.push(k.to_string());
+            }
+        }
+        for k in self.resources.keys() {
+            if !other.resources.contains_key(k) {
+                result.removed.push(k.to_string());
+            }
+        }
+        result
+    }
+}
+
+fn validate_keys(value: &Value, expected: &[&str], path: &str) -> Result<()> {
+    if let Value::Object(map) = value {
+        for key in map.keys() {
+            if !expected.contains(&&key[..]) {
+                return Err(BasaltError::Policy(format!(
+                    "Unknown key \"{}\" in {}, expected one of: {}",
+                    key,
+                    path,
+                    expected.join(", ")
+                ))
+                .into());
+            }
+        }
+    }
+    Ok(())
 }
 
 impl Merge for Policy {
@@ -36,16 +217,194 @@ impl Merge for Policy {
             if let Some(pkg) = self.resources.get_mut(k) {
                 pkg.merge(v);
             } else {
-                self.resources.insert(k.to_string(), v.clone());
+                // Merge into an empty policy so that removal
+                // markers are not copied into the result.
+                let mut pkg: PackagePolicy = Default::default();
+                pkg.merge(v);
+                self.resources.insert(k.to_string(), pkg);
             }
         }
     }
 }
 
+/// Kind of capability for a policy violation.
+#[derive(Serialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ViolationKind {
+    /// Access to a builtin module.
+    Builtin,
+    /// Access to a global.
+    Global,
+    /// Access to another package.
+    Package,
+}
+
+/// Capability used by a package that is not granted by a policy.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    /// Name of the package that uses the capability.
+    pub package: String,
+    /// Kind of the capability.
+    pub kind: ViolationKind,
+    /// Name of the builtin, global or package.
+    pub name: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ViolationKind::Builtin => "builtin",
+            ViolationKind::Global => "global",
+            ViolationKind::Package => "package",
+        };
+        write!(
+            f,
+            "{} {} is not granted to {}",
+            kind, self.name, self.package
+        )
+    }
+}
+
+/// Entries granted by a package policy that are not used.
+#[derive(Serialize, Default, Debug, Eq, PartialEq)]
+pub struct UnusedGrants {
+    /// Builtin entries that are not used.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub builtin: Vec<String>,
+    /// Globals entries that are not used.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub globals: Vec<String>,
+    /// Package entries that are not used.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<String>,
+}
+
+impl UnusedGrants {
+    /// Determine if there are no unused entries.
+    pub fn is_empty(&self) -> bool {
+        self.builtin.is_empty()
+            && self.globals.is_empty()
+            && self.packages.is_empty()
+    }
+}
+
+/// Difference between two policies.
+///
+/// Entries are added when they are granted in the other policy but
+/// not in this policy and removed when they are granted in this policy
+/// but not in the other policy.
+#[derive(Serialize, Default, Debug, Eq, PartialEq)]
+pub struct PolicyDiff {
+    /// Packages only in the other policy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    /// Packages only in this policy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    /// Changes to packages in both policies.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed: BTreeMap<String, PackagePolicyDiff>,
+}
+
+impl PolicyDiff {
+    /// Determine if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// Determine if the other policy grants any capability
+    /// that was not granted by this policy.
+    pub fn has_added_capabilities(&self) -> bool {
+        !self.added.is_empty()
+            || self
+                .changed
+                .values()
+                .any(|pkg| pkg.has_added_capabilities())
+    }
+}
+
+/// Difference between two package policies.
+#[derive(Serialize, Default, Debug, Eq, PartialEq)]
+pub struct PackagePolicyDiff {
+    /// Change to the native flag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native: Option<Change<bool>>,
+    /// Change to the environment policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<Change<EnvPolicy>>,
+    /// Changes to the builtin entries.
+    #[serde(skip_serializing_if = "GroupDiff::is_empty")]
+    pub builtin: GroupDiff,
+    /// Changes to the globals entries.
+    #[serde(skip_serializing_if = "GroupDiff::is_empty")]
+    pub globals: GroupDiff,
+    /// Changes to the package entries.
+    #[serde(skip_serializing_if = "GroupDiff::is_empty")]
+    pub packages: GroupDiff,
+}
+
+impl PackagePolicyDiff {
+    /// Determine if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.native.is_none()
+            && self.env.is_none()
+            && self.builtin.is_empty()
+            && self.globals.is_empty()
+            && self.packages.is_empty()
+    }
+
+    /// Determine if any capability was granted.
+    pub fn has_added_capabilities(&self) -> bool {
+        self.native.as_ref().map(|c| c.to).unwrap_or(false)
+            || self
+                .env
+                .as_ref()
+                .map(|c| c.to == EnvPolicy::Unfrozen)
+                .unwrap_or(false)
+            || !self.builtin.added.is_empty()
+            || !self.globals.added.is_empty()
+            || !self.packages.added.is_empty()
+    }
+}
+
+/// Difference between two policy groups.
+#[derive(Serialize, Default, Debug, Eq, PartialEq)]
+pub struct GroupDiff {
+    /// Entries granted only in the other group or granted
+    /// wider access in the other group.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    /// Entries granted only in this group or granted
+    /// wider access in this group.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+}
+
+impl GroupDiff {
+    /// Determine if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Represents a changed value.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct Change<T> {
+    /// Value in this policy.
+    pub from: T,
+    /// Value in the other policy.
+    pub to: T,
+}
+
 /// Policy for a single package.
 #[derive(Serialize, Deserialize, Clone, Default, Debug, Eq, PartialEq)]
 #[serde(default)]
 pub struct PackagePolicy {
+    /// Version of the package that was analyzed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     /// Does this policy allow native bindings.
     #[serde(skip_serializing_if = "is_false")]
     pub native: bool,
@@ -70,10 +429,42 @@ impl PackagePolicy {
             && self.globals.map.is_empty()
             && self.packages.map.is_empty()
     }
+
+    /// Compute the difference between this package policy and another.
+    pub fn diff(&self, other: &PackagePolicy) -> PackagePolicyDiff {
+        PackagePolicyDiff {
+            native: if self.native != other.native {
+                Some(Change {
+                    from: self.native,
+                    to: other.native,
+                })
+            } else {
+                None
+            },
+            env: if self.env != other.env {
+                Some(Change {
+                    from: self.env,
+                    to: other.env,
+                })
+            } else {
+                None
+            },
+            builtin: self.builtin.diff(&other.builtin),
+            globals: self.globals.diff(&other.globals),
+            packages: self.packages.diff(&other.packages),
+        }
+    }
 }
 
 impl Merge for PackagePolicy {
+    /// Apply overrides from `from`.
+    ///
+    /// Entries in the builtin, globals and packages groups that
+    /// use the `"remove"` marker are deleted from this policy.
     fn merge(&mut self, from: &Self) {
+        if from.version.is_some() {
+            self.version = from.version.clone();
+        }
         self.native = from.native;
         self.env = from.env;
         self.builtin.merge(&from.builtin);
@@ -82,13 +473,43 @@ impl Merge for PackagePolicy {
     }
 }
 
+/// Marker used by override policies to remove an entry when merging.
+const REMOVE: &str = "remove";
+
 /// Represents a code access permission for a package policy entry.
 ///
-/// Currently this is just a boolean switch but later we may
-/// modify this to represent [read, write, execute] permissions.
+/// By default this is a boolean switch but it may also represent
+/// the [read, write, execute] permissions which serialize as an
+/// object, eg: `{ "read": true, "execute": true }`.
+///
+/// An override policy may use the string `"remove"` instead of a
+/// boolean to delete the entry from the policy it is merged into.
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
 pub struct PolicyAccess {
     flag: bool,
+    remove: bool,
+    access: Option<Access>,
+}
+
+impl PolicyAccess {
+    /// Create an access marker that removes an entry when merged.
+    pub fn remove() -> Self {
+        PolicyAccess {
+            flag: false,
+            remove: true,
+            access: None,
+        }
+    }
+
+    /// Get the read, write and execute permissions when available.
+    pub fn access(&self) -> Option<&Access> {
+        self.access.as_ref()
+    }
+
+    /// Determine if this access removes an entry when merged.
+    pub fn is_remove(&self) -> bool {
+        self.remove
+    }
 }
 
 impl Serialize for PolicyAccess {
@@ -96,7 +517,13 @@ impl Serialize for PolicyAccess {
     where
         S: Serializer,
     {
-        serializer.serialize_bool(self.flag)
+        if self.remove {
+            serializer.serialize_str(REMOVE)
+        } else if let Some(access) = &self.access {
+            access.serialize(serializer)
+        } else {
+            serializer.serialize_bool(self.flag)
+        }
     }
 }
 
@@ -105,32 +532,67 @@ impl<'de> Deserialize<'de> for PolicyAccess {
     where
         D: Deserializer<'de>,
     {
-        let flag = deserializer.deserialize_bool(PolicyAccessVisitor)?;
-        Ok(PolicyAccess { flag })
+        deserializer.deserialize_any(PolicyAccessVisitor)
     }
 }
 
 struct PolicyAccessVisitor;
 
 impl<'de> Visitor<'de> for PolicyAccessVisitor {
-    type Value = bool;
+    type Value = PolicyAccess;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter
-            .write_str("a boolean is required for policy access permissions")
+        formatter.write_str(concat!(
+            "a boolean, access object or \"remove\" ",
+            "is required for policy access permissions"
+        ))
     }
 
     fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        Ok(value)
+        Ok(value.into())
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if value == REMOVE {
+            Ok(PolicyAccess::remove())
+        } else {
+            Err(E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let access =
+            Access::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(access.into())
     }
 }
 
 impl From<bool> for PolicyAccess {
     fn from(value: bool) -> Self {
-        PolicyAccess { flag: value }
+        PolicyAccess {
+            flag: value,
+            remove: false,
+            access: None,
+        }
+    }
+}
+
+impl From<Access> for PolicyAccess {
+    fn from(value: Access) -> Self {
+        PolicyAccess {
+            flag: value.any(),
+            remove: false,
+            access: Some(value),
+        }
     }
 }
 
@@ -152,16 +614,101 @@ impl PolicyGroup {
         self.map.insert(key.as_ref().into(), value);
     }
 
+    /// Remove entries that do not grant access.
+    fn normalize(&mut self) {
+        self.map.retain(|_, access| access.flag || access.remove);
+    }
+
     /// Append a map of packages to this group.
     pub fn append(&mut self, other: &mut BTreeMap<String, PolicyAccess>) {
         self.map.append(other);
     }
+
+    /// Determine if an entry is granted by this group.
+    ///
+    /// When parents is set an entry is also granted when a parent
+    /// member path is granted.
+    fn is_granted(&self, key: &str, parents: bool) -> bool {
+        let granted = |key: &str| {
+            self.map.get(key).map(|access| access.flag).unwrap_or(false)
+        };
+        if granted(key) {
+            return true;
+        }
+        parents
+            && key
+                .match_indices('.')
+                .any(|(index, _)| granted(&key[..index]))
+    }
+
+    /// Find the granted entries in this group that are not used
+    /// by another group.
+    ///
+    /// When members is set an entry is also used when a parent or
+    /// child member path is used.
+    fn unused(&self, used: &PolicyGroup, members: bool) -> Vec<String> {
+        let is_member = |parent: &str, key: &str| {
+            key.len() > parent.len()
+                && key.starts_with(parent)
+                && key[parent.len()..].starts_with('.')
+        };
+        self.map
+            .iter()
+            .filter(|(key, access)| {
+                access.flag
+                    && !used.map.iter().any(|(name, access)| {
+                        access.flag
+                            && (name == *key
+                                || (members
+                                    && (is_member(key, name)
+                                        || is_member(name, key))))
+                    })
+            })
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+
+    /// Compute the difference between the granted entries
+    /// in this group and another group.
+    ///
+    /// An entry granted by both groups is added when the other group
+    /// grants wider access, eg: `{ "read": true }` to `true`, and
+    /// removed when the other group grants narrower access.
+    pub fn diff(&self, other: &PolicyGroup) -> GroupDiff {
+        // A boolean grant permits every kind of access
+        let granted = |group: &PolicyGroup, key: &str| {
+            group
+                .map
+                .get(key)
+                .filter(|access| access.flag)
+                .map(|access| access.access.unwrap_or_else(Access::all))
+                .unwrap_or_else(Access::none)
+        };
+        GroupDiff {
+            added: other
+                .map
+                .keys()
+                .filter(|k| !granted(self, k).includes(&granted(other, k)))
+                .cloned()
+                .collect(),
+            removed: self
+                .map
+                .keys()
+                .filter(|k| !granted(other, k).includes(&granted(self, k)))
+                .cloned()
+                .collect(),
+        }
+    }
 }
 
 impl Merge for PolicyGroup {
     fn merge(&mut self, from: &Self) {
         for (k, v) in from.map.iter() {
-            self.map.insert(k.to_string(), *v);
+            if v.is_remove() {
+                self.map.remove(k);
+            } else {
+                self.map.insert(k.to_string(), *v);
+            }
         }
     }
 }
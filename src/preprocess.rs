@@ -0,0 +1,163 @@
+//! Pre-process sources before they are parsed.
+//!
+//! Some file types embed Javascript in another format, for example
+//! the `<script>` block in Vue and Svelte single-file components.
+//! A pre-processor registered for the file extension extracts the
+//! script so that the module can be analyzed like any other module.
+//!
+//! Files without a registered pre-processor are parsed as is.
+use std::collections::HashMap;
+use std::lazy::SyncLazy;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{bail, Result};
+
+use swc_common::FileName;
+use swc_ecma_parser::{Syntax, TsConfig};
+
+use crate::swc_utils::get_syntax;
+
+const SCRIPT_OPEN: &str = "<script";
+const SCRIPT_CLOSE: &str = "</script>";
+
+/// Registry used when loading files.
+static REGISTRY: SyncLazy<RwLock<PreprocessorRegistry>> =
+    SyncLazy::new(|| RwLock::new(Default::default()));
+
+/// Extract the script from the source for a file.
+pub trait SourcePreprocessor: Send + Sync {
+    /// Get the script to parse and the syntax for the script.
+    fn extract(&self, path: &Path, content: &str) -> Result<(String, Syntax)>;
+}
+
+/// Pre-processors keyed by file extension.
+///
+/// The default registry extracts the `<script>` blocks from
+/// `.vue` and `.svelte` files.
+#[derive(Clone)]
+pub struct PreprocessorRegistry {
+    preprocessors: HashMap<String, Arc<dyn SourcePreprocessor>>,
+}
+
+impl PreprocessorRegistry {
+    /// Create a registry without any pre-processors.
+    pub fn new() -> Self {
+        Self {
+            preprocessors: Default::default(),
+        }
+    }
+
+    /// Register a pre-processor for a file extension.
+    ///
+    /// The extension does not include the leading period.
+    pub fn register<S: Into<String>>(
+        mut self,
+        extension: S,
+        preprocessor: Arc<dyn SourcePreprocessor>,
+    ) -> Self {
+        self.preprocessors.insert(extension.into(), preprocessor);
+        self
+    }
+
+    /// Get the pre-processor for a path.
+    pub fn get(&self, path: &Path) -> Option<Arc<dyn SourcePreprocessor>> {
+        let extension = path.extension()?.to_string_lossy();
+        self.preprocessors.get(extension.as_ref()).cloned()
+    }
+}
+
+impl Default for PreprocessorRegistry {
+    fn default() -> Self {
+        let script: Arc<dyn SourcePreprocessor> = Arc::new(ScriptPreprocessor);
+        Self::new()
+            .register("vue", Arc::clone(&script))
+            .register("svelte", script)
+    }
+}
+
+/// Register a pre-processor used when loading files with
+/// the given extension.
+pub fn register_preprocessor<S: Into<String>>(
+    extension: S,
+    preprocessor: Arc<dyn SourcePreprocessor>,
+) {
+    let mut registry = REGISTRY.write().unwrap();
+    registry
+        .preprocessors
+        .insert(extension.into(), preprocessor);
+}
+
+/// Get the registered pre-processor for a path.
+pub fn preprocessor(path: &Path) -> Option<Arc<dyn SourcePreprocessor>> {
+    REGISTRY.read().unwrap().get(path)
+}
+
+/// Extract the `<script>` blocks from a single-file component.
+///
+/// Content outside of the script blocks is replaced with line breaks
+/// so that line numbers for the script match the original file. When
+/// a script block declares `lang="ts"` the script is parsed as
+/// typescript.
+#[derive(Debug, Default)]
+pub struct ScriptPreprocessor;
+
+impl SourcePreprocessor for ScriptPreprocessor {
+    fn extract(&self, path: &Path, content: &str) -> Result<(String, Syntax)> {
+        let mut script = String::new();
+        let mut typescript = false;
+        let mut rest = content;
+        while let Some(start) = rest.find(SCRIPT_OPEN) {
+            let tag_end = match rest[start..].find('>') {
+                Some(end) => start + end,
+                None => {
+                    bail!("unterminated script element in {}", path.display())
+                }
+            };
+            let attributes = &rest[start + SCRIPT_OPEN.len()..tag_end];
+            typescript = typescript || is_typescript(attributes);
+
+            let body = &rest[tag_end + 1..];
+            let body_end = match body.find(SCRIPT_CLOSE) {
+                Some(end) => end,
+                None => {
+                    bail!("unterminated script element in {}", path.display())
+                }
+            };
+
+            line_breaks(&rest[..tag_end + 1], &mut script);
+            script.push_str(&body[..body_end]);
+            rest = &body[body_end + SCRIPT_CLOSE.len()..];
+        }
+
+        let syntax = if typescript {
+            Syntax::Typescript(TsConfig {
+                dynamic_import: true,
+                ..Default::default()
+            })
+        } else {
+            get_syntax(&FileName::Anon)
+        };
+        Ok((script, syntax))
+    }
+}
+
+/// Determine if the attributes for a script element
+/// declare a typescript block.
+fn is_typescript(attributes: &str) -> bool {
+    [
+        "lang=\"ts\"",
+        "lang='ts'",
+        "lang=\"typescript\"",
+        "lang='typescript'",
+    ]
+    .iter()
+    .any(|lang| attributes.contains(lang))
+}
+
+/// Push a line break for each line break in the content.
+fn line_breaks(content: &str, script: &mut String) {
+    for _ in content.matches('\n') {
+        script.push('\n');
+    }
+}
@@ -1,26 +1,143 @@
-//! Utility to print the module graph as a tree.
-use std::path::Path;
+//! Utility to print the module graph.
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use serde::Serialize;
 
-use crate::module::node::{parse_file, VisitedDependency, VisitedModule};
+use crate::{
+    module::{
+        base::root_relative,
+        node::{parse_file, ModuleNode, VisitedDependency, VisitedModule},
+        parser::detect_cycles,
+        resolver::ExportsResolver,
+    },
+    policy::analysis::{analyze_module_dependencies, flatten, join_words},
+};
 
-use swc_common::SourceMap;
-use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
+use swc_atoms::JsWord;
+use swc_common::{FileName, SourceMap};
+use swc_ecma_loader::resolve::Resolve;
 
 const TREE_BAR: &str = "│";
 const TREE_BRANCH: &str = "├──";
 const TREE_CORNER: &str = "└──";
 
+/// Output formats for the module graph.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PrintFormat {
+    /// Indented tree of module specifiers.
+    Tree,
+    /// Newline-delimited list of unique modules.
+    Flat,
+    /// JSON array of modules and their direct dependencies.
+    Json,
+}
+
+impl Default for PrintFormat {
+    fn default() -> Self {
+        PrintFormat::Tree
+    }
+}
+
+impl FromStr for PrintFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "tree" => Ok(PrintFormat::Tree),
+            "flat" => Ok(PrintFormat::Flat),
+            "json" => Ok(PrintFormat::Json),
+            _ => bail!("unknown print format {}", s),
+        }
+    }
+}
+
 /// Options to use when printing the module graph.
 #[derive(Debug, Default)]
 pub struct PrintOptions {
     /// Include file names.
     pub include_file: bool,
+    /// Output format.
+    pub format: PrintFormat,
+    /// Print the circular dependencies instead of the module graph.
+    pub cycles: bool,
+    /// Print file paths relative to this project root.
+    pub root: Option<PathBuf>,
+    /// Include the globals and builtins used by each module.
+    pub annotate: bool,
+}
+
+impl PrintOptions {
+    /// Path string for a file relative to the project root.
+    fn path<P: AsRef<Path>>(&self, file: P) -> String {
+        root_relative(file.as_ref(), self.root.as_deref())
+            .display()
+            .to_string()
+    }
+
+    /// Path string for a file name relative to the project root.
+    fn file_name(&self, file_name: &FileName) -> String {
+        match file_name {
+            FileName::Real(path) => self.path(path),
+            _ => path(file_name),
+        }
+    }
+}
+
+/// Module entry for the JSON output format.
+#[derive(Debug, Serialize)]
+struct ModuleEntry {
+    /// Resolved path for the module.
+    file: String,
+    /// Resolved paths for the direct dependencies of the module.
+    dependencies: Vec<String>,
+    /// Globals and builtins used by the module.
+    #[serde(flatten)]
+    annotations: Option<Annotations>,
+}
+
+/// Globals and builtins used by a module.
+#[derive(Debug, Serialize)]
+struct Annotations {
+    /// Global symbol paths.
+    globals: Vec<String>,
+    /// Builtin symbol paths.
+    builtin: Vec<String>,
+}
+
+impl Annotations {
+    /// Analyze a module for the globals and builtins.
+    fn new(node: &ModuleNode) -> Self {
+        let deps = node.dependencies.as_deref().unwrap_or_default();
+        let analysis = analyze_module_dependencies(
+            &node.module,
+            deps,
+            &Default::default(),
+        );
+        let join = |words: &Vec<JsWord>| join_words(words).as_ref().to_string();
+        Self {
+            globals: flatten(analysis.globals).iter().map(join).collect(),
+            builtin: flatten(analysis.builtin).iter().map(join).collect(),
+        }
+    }
+
+    /// Write the annotations as indented lists under a module.
+    fn write<W: Write>(&self, prefix: &str, out: &mut W) -> Result<()> {
+        for (label, names) in
+            [("globals", &self.globals), ("builtin", &self.builtin)]
+        {
+            if !names.is_empty() {
+                writeln!(out, "{}  {}: {}", prefix, label, names.join(", "))?;
+            }
+        }
+        Ok(())
+    }
 }
 
-/// Prints the module graph as a tree.
+/// Prints the module graph.
 pub struct Printer;
 
 impl Printer {
@@ -35,52 +152,270 @@ impl Printer {
         file: P,
         options: &PrintOptions,
     ) -> Result<()> {
-        let resolver: Box<dyn Resolve> =
-            Box::new(NodeModulesResolver::default());
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        self.write(file, options, &mut out)
+    }
+
+    /// Write module imports for an entry point to a destination.
+    pub fn write<P: AsRef<Path>, W: Write>(
+        &self,
+        file: P,
+        options: &PrintOptions,
+        out: &mut W,
+    ) -> Result<()> {
+        if options.cycles {
+            return self.write_cycles(file, options, out);
+        }
+
+        let resolver: Box<dyn Resolve> = Box::new(ExportsResolver::default());
         let source_map: Arc<SourceMap> = Arc::new(Default::default());
         let module =
             parse_file(file.as_ref(), &resolver, Arc::clone(&source_map))?;
-        let node = match &*module {
-            VisitedModule::Module(_, node) => Some(node),
-            VisitedModule::Json(_, node) => Some(node),
-            VisitedModule::Builtin(_) => None,
+        let (file_name, node) = match &*module {
+            VisitedModule::Module(file_name, node) => (file_name, Some(node)),
+            VisitedModule::Json(file_name, node) => (file_name, Some(node)),
+            VisitedModule::Builtin(file_name) => (file_name, None),
         };
-        println!("{}", file.as_ref().display());
 
+        match options.format {
+            PrintFormat::Tree => {
+                writeln!(out, "{}", options.path(&file))?;
+                if let Some(node) = node {
+                    if options.annotate {
+                        Annotations::new(node).write("", out)?;
+                    }
+                    self.write_tree(node, source_map, options, out)?;
+                }
+            }
+            PrintFormat::Flat => {
+                writeln!(out, "{}", options.path(&file))?;
+                if let Some(node) = node {
+                    if options.annotate {
+                        Annotations::new(node).write("", out)?;
+                    }
+                    self.write_flat(node, source_map, options, out)?;
+                }
+            }
+            PrintFormat::Json => {
+                // Entries are written as they are visited so that
+                // memory usage does not grow with the size of the graph
+                let mut entries = JsonArrayWriter::new(&mut *out);
+                if let Some(node) = node {
+                    let mut seen: HashSet<FileName> = HashSet::new();
+                    seen.insert(file_name.clone());
+                    entries
+                        .write(&entry(file_name, node, &resolver, options)?)?;
+                    node.visit(source_map, &mut |dep: VisitedDependency| {
+                        if let Some(node) = dep.node {
+                            if seen.insert(dep.file_name.clone()) {
+                                entries.write(&entry(
+                                    &dep.file_name,
+                                    node,
+                                    &resolver,
+                                    options,
+                                )?)?;
+                            }
+                        }
+                        Ok(())
+                    })?;
+                }
+                entries.finish()?;
+                writeln!(out)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_cycles<P: AsRef<Path>, W: Write>(
+        &self,
+        file: P,
+        options: &PrintOptions,
+        out: &mut W,
+    ) -> Result<()> {
+        let cycles = detect_cycles(file)?;
+        if let PrintFormat::Json = options.format {
+            let cycles: Vec<Vec<String>> = cycles
+                .iter()
+                .map(|cycle| cycle.iter().map(|p| options.path(p)).collect())
+                .collect();
+            serde_json::to_writer_pretty(&mut *out, &cycles)?;
+            writeln!(out)?;
+        } else {
+            for cycle in cycles {
+                let mut paths: Vec<String> =
+                    cycle.iter().map(|p| options.path(p)).collect();
+                // Close the loop back to the first module
+                paths.push(paths[0].clone());
+                writeln!(out, "{}", paths.join(" -> "))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_tree<W: Write>(
+        &self,
+        node: &ModuleNode,
+        source_map: Arc<SourceMap>,
+        options: &PrintOptions,
+        out: &mut W,
+    ) -> Result<()> {
         let mut visitor = |dep: VisitedDependency| {
             let mark = if dep.last { TREE_CORNER } else { TREE_BRANCH };
             for (j, iter_state) in dep.state.open.iter().enumerate() {
                 let end = j == (dep.state.open.len() - 1);
                 if !end {
                     if !iter_state.last {
-                        print!("{}   ", TREE_BAR);
+                        write!(out, "{}   ", TREE_BAR)?;
                     } else {
-                        print!("    ");
+                        write!(out, "    ")?;
                     }
                 } else {
-                    print!("{} ", mark);
+                    write!(out, "{} ", mark)?;
                 }
             }
 
-            print!("{}", &dep.spec);
+            write!(out, "{}", &dep.spec)?;
 
             if options.include_file {
-                print!(" {}", dep.file_name);
+                write!(out, " {}", options.file_name(&dep.file_name))?;
             }
 
             if let Some(cycle) = dep.cycles {
-                print!(" (∞ -> {})", cycle);
+                write!(out, " (∞ -> {})", cycle)?;
             }
 
-            print!("\n");
+            writeln!(out)?;
 
+            if let (true, Some(node)) = (options.annotate, dep.node) {
+                // Align with the tree bars for the dependencies
+                let prefix: String = dep
+                    .state
+                    .open
+                    .iter()
+                    .enumerate()
+                    .map(|(j, iter_state)| {
+                        let end = j == (dep.state.open.len() - 1);
+                        let last = if end { dep.last } else { iter_state.last };
+                        if last {
+                            String::from("    ")
+                        } else {
+                            format!("{}   ", TREE_BAR)
+                        }
+                    })
+                    .collect();
+                Annotations::new(node).write(&prefix, out)?;
+            }
+
+            Ok(())
+        };
+
+        node.visit(source_map, &mut visitor)
+    }
+
+    fn write_flat<W: Write>(
+        &self,
+        node: &ModuleNode,
+        source_map: Arc<SourceMap>,
+        options: &PrintOptions,
+        out: &mut W,
+    ) -> Result<()> {
+        let mut seen: HashSet<FileName> = HashSet::new();
+        let mut visitor = |dep: VisitedDependency| {
+            if seen.contains(&dep.file_name) {
+                return Ok(());
+            }
+            if options.include_file {
+                writeln!(
+                    out,
+                    "{} {}",
+                    &dep.spec,
+                    options.file_name(&dep.file_name)
+                )?;
+            } else {
+                writeln!(out, "{}", &dep.spec)?;
+            }
+            if let (true, Some(node)) = (options.annotate, dep.node) {
+                Annotations::new(node).write("", out)?;
+            }
+            seen.insert(dep.file_name);
             Ok(())
         };
 
-        if let Some(node) = node {
-            node.visit(source_map, &mut visitor)?;
+        node.visit(source_map, &mut visitor)
+    }
+}
+
+/// Write the elements of a JSON array one at a time.
+///
+/// The output is the same as pretty printing the entire array.
+struct JsonArrayWriter<'a, W: Write> {
+    out: &'a mut W,
+    len: usize,
+}
+
+impl<'a, W: Write> JsonArrayWriter<'a, W> {
+    fn new(out: &'a mut W) -> Self {
+        Self { out, len: 0 }
+    }
+
+    /// Write an element of the array.
+    fn write<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        let element = serde_json::to_string_pretty(value)?;
+        let separator = if self.len == 0 { "[" } else { "," };
+        writeln!(self.out, "{}", separator)?;
+        for (index, line) in element.lines().enumerate() {
+            if index > 0 {
+                writeln!(self.out)?;
+            }
+            write!(self.out, "  {}", line)?;
         }
+        self.len += 1;
+        Ok(())
+    }
 
+    /// Close the array.
+    fn finish(self) -> Result<()> {
+        if self.len == 0 {
+            write!(self.out, "[]")?;
+        } else {
+            write!(self.out, "\n]")?;
+        }
         Ok(())
     }
 }
+
+/// Create the JSON entry for a module.
+fn entry(
+    file_name: &FileName,
+    node: &ModuleNode,
+    resolver: &Box<dyn Resolve>,
+    options: &PrintOptions,
+) -> Result<ModuleEntry> {
+    let dependencies = node
+        .resolve_dependencies(resolver, file_name)?
+        .into_iter()
+        .map(|(_, file_name)| options.file_name(&file_name))
+        .collect();
+    let annotations = if options.annotate {
+        Some(Annotations::new(node))
+    } else {
+        None
+    };
+    Ok(ModuleEntry {
+        file: options.file_name(file_name),
+        dependencies,
+        annotations,
+    })
+}
+
+/// Get a path string for a file name without the decoration
+/// used by `Display` for custom file names.
+fn path(file_name: &FileName) -> String {
+    match file_name {
+        FileName::Real(path) => path.display().to_string(),
+        FileName::Custom(name) => name.clone(),
+        _ => file_name.to_string(),
+    }
+}
@@ -1,4 +1,5 @@
 //! Helpers to analyze modules.
+use swc_atoms::JsWord;
 use swc_common::Span;
 use swc_ecma_ast::*;
 use swc_ecma_visit::{Node, Visit, VisitAll};
@@ -121,6 +122,35 @@ impl Analyzer {
         }
         out
     }
+
+    /// Get the specifiers for named exports of local bindings,
+    /// eg: `export { foo, bar as baz };`.
+    pub fn named_export_specifiers(&self) -> Vec<&ExportNamedSpecifier> {
+        self.exports
+            .iter()
+            .filter_map(|rec| match rec {
+                ExportRecord::Named { specifiers } => Some(specifiers),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|spec| match spec {
+                ExportSpecifier::Named(spec) => Some(spec),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the identifier for a default export of a local binding,
+    /// eg: `export default foo;`.
+    pub fn default_export_ident(&self) -> Option<&Ident> {
+        self.exports.iter().find_map(|rec| match rec {
+            ExportRecord::DefaultExpr { expr } => match &**expr {
+                Expr::Ident(ident) => Some(ident),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
 }
 
 impl Visit for Analyzer {
@@ -229,10 +259,24 @@ impl Visit for Analyzer {
 /// to branch in many places to detect all the variants for where
 /// statements could appear so we detect the statements in a separate
 /// visitor pass.
+///
+/// An export is live when the binding is assigned to anywhere in the
+/// module, including inside functions, regardless of whether it was
+/// declared with `let` or `var`; a `let` binding that is never assigned
+/// to is a fixed export. Assignments include updates, compound and
+/// destructuring assignments and the heads of `for-in` and `for-of`
+/// loops. Bindings declared with `const` are always fixed exports as
+/// assigning to them throws a `TypeError`.
+///
+/// Names are matched without resolving scopes so an assignment to a
+/// local that shadows an export marks the export as live which is
+/// safe but less efficient.
 #[derive(Default, Debug)]
 pub struct LiveExportAnalysis {
     /// List of exported symbol names.
     pub exports: Vec<(String, Span)>,
+    /// Exported symbol names declared with `const`.
+    pub constants: IndexSet<String>,
     /// List of exported symbol names that are considered live exports.
     pub live: Vec<String>,
     /// List of export references that should be hoisted during transformation.
@@ -244,6 +288,7 @@ impl LiveExportAnalysis {
     pub fn new() -> Self {
         Self {
             exports: Default::default(),
+            constants: Default::default(),
             live: Default::default(),
             hoisted_refs: Default::default(),
         }
@@ -252,12 +297,57 @@ impl LiveExportAnalysis {
 
 impl LiveExportAnalysis {
     fn detect_match(&mut self, sym: &str) -> Option<&(String, Span)> {
+        if self.constants.contains(sym) {
+            return None;
+        }
         let matched = self.exports.iter().find(|(name, _)| sym == *name);
         if matched.is_some() {
             self.live.push(sym.to_string());
         }
         matched
     }
+
+    fn detect_pattern(&mut self, pat: &Pat) {
+        let mut names = Vec::new();
+        assigned_words(pat, &mut names);
+        for name in names {
+            self.detect_match(name.as_ref());
+        }
+    }
+}
+
+/// Collect the symbols assigned to by a pattern, eg: the pattern
+/// for `[a, { b: c }] = value` assigns to `a` and `c`.
+fn assigned_words<'a>(pat: &'a Pat, names: &mut Vec<&'a JsWord>) {
+    match pat {
+        Pat::Ident(binding) => names.push(&binding.id.sym),
+        Pat::Object(obj) => {
+            for prop in obj.props.iter() {
+                match prop {
+                    ObjectPatProp::Assign(entry) => names.push(&entry.key.sym),
+                    ObjectPatProp::KeyValue(entry) => {
+                        assigned_words(&*entry.value, names)
+                    }
+                    ObjectPatProp::Rest(entry) => {
+                        assigned_words(&*entry.arg, names)
+                    }
+                }
+            }
+        }
+        Pat::Array(arr) => {
+            for elem in arr.elems.iter().flatten() {
+                assigned_words(elem, names);
+            }
+        }
+        Pat::Rest(rest) => assigned_words(&*rest.arg, names),
+        Pat::Assign(assign) => assigned_words(&*assign.left, names),
+        Pat::Expr(expr) => {
+            if let Expr::Ident(ident) = &**expr {
+                names.push(&ident.sym);
+            }
+        }
+        _ => {}
+    }
 }
 
 impl VisitAll for LiveExportAnalysis {
@@ -293,6 +383,20 @@ impl VisitAll for LiveExportAnalysis {
         }
     }
 
+    // for (count in obj) {}
+    fn visit_for_in_stmt(&mut self, n: &ForInStmt, _: &dyn Node) {
+        if let VarDeclOrPat::Pat(pat) = &n.left {
+            self.detect_pattern(pat);
+        }
+    }
+
+    // for (count of list) {}
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt, _: &dyn Node) {
+        if let VarDeclOrPat::Pat(pat) = &n.left {
+            self.detect_pattern(pat);
+        }
+    }
+
     fn visit_expr(&mut self, n: &Expr, _: &dyn Node) {
         match n {
             // ++i, i++, --i, i--
@@ -302,14 +406,11 @@ impl VisitAll for LiveExportAnalysis {
                 }
                 _ => {}
             },
-            // count = 1
+            // count = 1, count += 1, [count] = [1]
             Expr::Assign(expr) => match &expr.left {
-                PatOrExpr::Pat(pat) => match &**pat {
-                    Pat::Ident(ident) => {
-                        self.detect_match(ident.id.sym.as_ref());
-                    }
-                    _ => {}
-                },
+                PatOrExpr::Pat(pat) => {
+                    self.detect_pattern(pat);
+                }
                 PatOrExpr::Expr(expr) => match &**expr {
                     Expr::Ident(ident) => {
                         self.detect_match(ident.sym.as_ref());
@@ -11,7 +11,7 @@ use serde::{Serialize, Serializer};
 pub type LiveExport<'a> = (&'a str, bool);
 
 /// Enumeration of the import types.
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq)]
 pub enum ImportKind {
     /// A named import specifier.
     Named,
@@ -27,9 +27,27 @@ pub struct ImportName<'a> {
     name: &'a str,
     alias: Option<&'a str>,
     kind: ImportKind,
+    /// Re-exported names are bound using the live exports
+    /// so they do not need a local variable.
+    reexport: bool,
 }
 
 impl<'a> ImportName<'a> {
+    /// Get the imported name.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Get the local alias for the import.
+    pub fn alias(&self) -> Option<&str> {
+        self.alias
+    }
+
+    /// Get the kind of import.
+    pub fn kind(&self) -> &ImportKind {
+        &self.kind
+    }
+
     /// Get the raw name for the import respecting the import kind
     /// to return `default` or `*` when necessary.
     pub fn raw_name(&self) -> &str {
@@ -77,6 +95,9 @@ pub struct StaticModuleRecord<'a> {
 }
 
 /// Owned static module record meta data.
+///
+/// The lists of star exports and fixed export local names are sorted
+/// so the meta data is the same for every run.
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct StaticModuleRecordMeta {
@@ -90,6 +111,79 @@ pub struct StaticModuleRecordMeta {
     pub fixed_export_map: IndexMap<String, Vec<String>>,
 }
 
+impl StaticModuleRecordMeta {
+    /// Get the names exported by declarations in this module.
+    ///
+    /// Names provided by `export * from` declarations are not included.
+    pub fn export_names(&self) -> IndexSet<&str> {
+        self.fixed_export_map
+            .keys()
+            .chain(self.live_export_map.keys())
+            .map(|k| &k[..])
+            .collect::<IndexSet<_>>()
+    }
+
+    /// Resolve the names exported by this module including the names
+    /// provided by the `export * from` declarations.
+    ///
+    /// The `export_alls` map should contain the meta data for each module
+    /// specifier in the list of `export_alls`; local exports take precedence
+    /// over names provided by a star export and names provided by more than
+    /// one star export are ambiguous so they are not exported.
+    ///
+    /// Only the direct exports of each star exported module are considered.
+    pub fn resolve_exports(
+        &self,
+        export_alls: &IndexMap<String, StaticModuleRecordMeta>,
+    ) -> ExportNames {
+        let mut result: ExportNames = Default::default();
+        let local = self.export_names();
+        for name in local.iter() {
+            result.names.insert(name.to_string(), None);
+        }
+
+        let mut candidates: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for spec in self.export_alls.iter() {
+            if let Some(meta) = export_alls.get(spec) {
+                for name in meta.export_names() {
+                    // Star exports never provide the default export
+                    if name == "default" || local.contains(name) {
+                        continue;
+                    }
+                    let list = candidates.entry(name).or_insert(Vec::new());
+                    list.push(&spec[..]);
+                }
+            }
+        }
+
+        for (name, specs) in candidates {
+            if specs.len() == 1 {
+                result
+                    .names
+                    .insert(name.to_string(), Some(specs[0].to_string()));
+            } else {
+                result.ambiguous.insert(
+                    name.to_string(),
+                    specs.into_iter().map(|s| s.to_string()).collect(),
+                );
+            }
+        }
+
+        result
+    }
+}
+
+/// Export names for a module after resolving star exports.
+#[derive(Debug, Default)]
+pub struct ExportNames {
+    /// Map of export names to the module specifier that provides the
+    /// export; local exports do not have a module specifier.
+    pub names: IndexMap<String, Option<String>>,
+    /// Names provided by multiple star exports mapped to the
+    /// conflicting module specifiers.
+    pub ambiguous: IndexMap<String, Vec<String>>,
+}
+
 /// Meta data and transformed program combined.
 #[derive(Serialize, Debug)]
 pub struct StaticModuleRecordProgram {
@@ -97,17 +191,16 @@ pub struct StaticModuleRecordProgram {
     pub meta: StaticModuleRecordMeta,
     /// The module transformed to a program functor.
     pub program: String,
+    /// Source map for the program functor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map: Option<String>,
 }
 
-impl Into<StaticModuleRecordMeta> for StaticModuleRecord<'_> {
-    fn into(self) -> StaticModuleRecordMeta {
+impl From<&StaticModuleRecord<'_>> for StaticModuleRecordMeta {
+    fn from(record: &StaticModuleRecord<'_>) -> Self {
         StaticModuleRecordMeta {
-            export_alls: self
-                .export_alls
-                .iter()
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>(),
-            imports: self
+            export_alls: sorted(&record.export_alls),
+            imports: record
                 .imports
                 .iter()
                 .map(|(k, v)| {
@@ -119,49 +212,62 @@ impl Into<StaticModuleRecordMeta> for StaticModuleRecord<'_> {
                     )
                 })
                 .collect::<IndexMap<_, _>>(),
-            live_export_map: self
+            live_export_map: record
                 .live_export_map
                 .iter()
                 .map(|(k, v)| (k.to_string(), (v.0.to_string(), v.1)))
                 .collect::<IndexMap<_, _>>(),
-            fixed_export_map: self
+            fixed_export_map: record
                 .fixed_export_map
                 .iter()
-                .map(|(k, v)| {
-                    (
-                        k.to_string(),
-                        v.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
-                    )
-                })
+                .map(|(k, v)| (k.to_string(), sorted(v)))
                 .collect::<IndexMap<_, _>>(),
         }
     }
 }
 
+impl Into<StaticModuleRecordMeta> for StaticModuleRecord<'_> {
+    fn into(self) -> StaticModuleRecordMeta {
+        (&self).into()
+    }
+}
+
+fn sorted(items: &[&str]) -> Vec<String> {
+    let mut items = items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    items.sort();
+    items
+}
+
 impl<'a> StaticModuleRecord<'a> {
     /// Get the list of import declarations.
     ///
     /// This is used by the transform to set up the locally
     /// scoped variable names.
-
-    /// FIXME: do not declare re-exports???
+    ///
+    /// Names are unique and in declaration order; re-exported names
+    /// are not declared as they are bound using the live exports
+    /// rather than a local variable.
     pub fn decls(&self) -> Vec<&str> {
         self.imports
             .iter()
             .map(|(_k, v)| v)
             .flatten()
-            .map(|i| {
-                if let Some(alias) = i.alias {
-                    // Special case when re-exporting as default
-                    //
-                    // export { meaning as default } from './meaning.js';
-                    if alias == "default" {
-                        return i.name;
-                    }
-                    return alias;
-                }
-                i.name
-            })
+            .filter(|i| !i.reexport)
+            .map(|i| i.alias.unwrap_or(i.name))
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the public export names for a local symbol in the fixed exports.
+    ///
+    /// A local symbol may be exported using multiple names, eg:
+    /// `export { internalName as publicName }`.
+    pub fn fixed_export_names(&self, local: &str) -> Vec<&str> {
+        self.fixed_export_map
+            .iter()
+            .filter(|(_k, v)| v.iter().any(|name| *name == local))
+            .map(|(k, _v)| *k)
             .collect::<Vec<_>>()
     }
 
@@ -188,4 +294,8 @@ pub mod parser;
 pub mod transform;
 
 pub use parser::Parser;
-pub use transform::{transform, TransformSource};
+pub use transform::{
+    module_meta, transform, transform_program, transform_program_with_options,
+    transform_program_with_source_map, transform_with_export_alls,
+    transform_with_source_map, OutputKind, TransformOptions, TransformSource,
+};
@@ -1,4 +1,6 @@
 //! Parse static module record meta data.
+use std::path::Path;
+
 use anyhow::Result;
 
 use swc_ecma_ast::*;
@@ -10,7 +12,10 @@ use super::analyzer::{
     Analyzer, ExportRecord, ImportRecord, LiveExportAnalysis, ReexportRecord,
 };
 
-use super::{ImportKind, ImportName, StaticModuleRecord};
+use super::{
+    module_meta, ImportKind, ImportName, StaticModuleRecord,
+    StaticModuleRecordMeta, TransformSource,
+};
 
 use crate::helpers::var_symbol_names;
 
@@ -31,6 +36,26 @@ impl Parser {
         }
     }
 
+    /// Lex and parse a file to the static module record meta data.
+    pub fn parse_file<P: AsRef<Path>>(
+        file: P,
+    ) -> Result<StaticModuleRecordMeta> {
+        module_meta(TransformSource::File(file.as_ref().to_path_buf()))
+    }
+
+    /// Lex and parse source code to the static module record meta data.
+    ///
+    /// The file name is used to determine the syntax for the source.
+    pub fn parse_str<C: Into<String>, N: Into<String>>(
+        content: C,
+        file_name: N,
+    ) -> Result<StaticModuleRecordMeta> {
+        module_meta(TransformSource::Str {
+            content: content.into(),
+            file_name: file_name.into(),
+        })
+    }
+
     /// Parse a module to a static module record.
     pub fn parse<'m>(
         &'m mut self,
@@ -48,7 +73,46 @@ impl Parser {
 
         module.visit_children_with(&mut self.analyzer);
 
-        self.live_exports.exports = self.analyzer.var_export_names();
+        let var_exports = self.analyzer.var_export_names();
+
+        // A default export of a local variable is live when the
+        // variable is assigned to, eg: `export default count; count++;`
+        let default_local = self
+            .analyzer
+            .default_export_ident()
+            .filter(|ident| declares_var(module, ident.sym.as_ref()))
+            .map(|ident| (ident.sym.as_ref().to_string(), ident.span));
+
+        // Likewise for a local variable exported by name, eg:
+        // `let count = 0; export { count }; count++;`
+        let named_locals = self
+            .analyzer
+            .named_export_specifiers()
+            .into_iter()
+            .filter(|spec| declares_var(module, spec.orig.sym.as_ref()))
+            .map(|spec| (spec.orig.sym.as_ref().to_string(), spec.orig.span))
+            .collect::<Vec<_>>();
+
+        self.live_exports.exports = var_exports.clone();
+        for local in default_local.iter().chain(named_locals.iter()) {
+            if !self
+                .live_exports
+                .exports
+                .iter()
+                .any(|(name, _)| name == &local.0)
+            {
+                self.live_exports.exports.push(local.clone());
+            }
+        }
+        self.live_exports.constants = self
+            .live_exports
+            .exports
+            .iter()
+            .filter(|(name, _)| {
+                var_decl_kind(module, name) == Some(VarDeclKind::Const)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
         module.visit_all_children_with(&mut self.live_exports);
 
         record.hoisted_funcs = self
@@ -72,16 +136,19 @@ impl Parser {
                         name: name.as_ref(),
                         alias: Some(&alias[..]),
                         kind: ImportKind::Named,
+                        reexport: false,
                     }),
                     ImportRecord::Default { name, .. } => Some(ImportName {
                         name: name.as_ref(),
                         alias: None,
                         kind: ImportKind::Default,
+                        reexport: false,
                     }),
                     ImportRecord::All { name } => Some(ImportName {
                         name: name.as_ref(),
                         alias: None,
                         kind: ImportKind::All,
+                        reexport: false,
                     }),
                 })
                 .filter(|s| s.is_some())
@@ -133,8 +200,30 @@ impl Parser {
         }
 
         for name in self.live_exports.live.iter() {
-            record.live_export_map.insert(name, (name, true));
-            record.fixed_export_map.remove(&name[..]);
+            if let Some((local, _)) = &default_local {
+                if local == name {
+                    record.live_export_map.insert("default", (name, true));
+                    record.fixed_export_map.remove("default");
+                }
+            }
+            if var_exports.iter().any(|(export, _)| export == name) {
+                record.live_export_map.insert(name, (name, true));
+                record.fixed_export_map.remove(&name[..]);
+            }
+        }
+
+        // Each specifier is classified separately so that only the
+        // names for locals that are assigned to are live exports
+        for spec in self.analyzer.named_export_specifiers() {
+            let local = spec.orig.sym.as_ref();
+            let is_live = named_locals.iter().any(|(name, _)| name == local)
+                && self.live_exports.live.iter().any(|name| name == local);
+            if is_live {
+                let key =
+                    spec.exported.as_ref().unwrap_or(&spec.orig).sym.as_ref();
+                record.live_export_map.insert(key, (local, true));
+                record.fixed_export_map.remove(key);
+            }
         }
 
         for symbol in self.analyzer.reexports.iter() {
@@ -145,27 +234,46 @@ impl Parser {
                 } => {
                     let words = specifiers
                         .iter()
-                        .filter(|s| {
-                            if let ExportSpecifier::Named(_) = s {
-                                true
-                            } else {
-                                false
+                        .filter_map(|s| match s {
+                            ExportSpecifier::Named(export) => {
+                                // export { default as Foo } from './foo.js';
+                                let kind =
+                                    if export.orig.sym.as_ref() == "default" {
+                                        ImportKind::Default
+                                    } else {
+                                        ImportKind::Named
+                                    };
+                                Some(ImportName {
+                                    name: export.orig.sym.as_ref(),
+                                    alias: export
+                                        .exported
+                                        .as_ref()
+                                        .map(|ident| ident.sym.as_ref()),
+                                    kind,
+                                    reexport: true,
+                                })
                             }
-                        })
-                        .map(|s| match s {
-                            ExportSpecifier::Named(export) => ImportName {
-                                name: export.orig.sym.as_ref(),
-                                alias: export
-                                    .exported
-                                    .as_ref()
-                                    .map(|ident| ident.sym.as_ref()),
-                                kind: ImportKind::Named,
-                            },
-                            _ => unreachable!(),
+                            // export * as ns from './foo.js';
+                            ExportSpecifier::Namespace(export) => {
+                                Some(ImportName {
+                                    name: export.name.sym.as_ref(),
+                                    alias: None,
+                                    kind: ImportKind::All,
+                                    // Needs a local for the namespace object
+                                    reexport: false,
+                                })
+                            }
+                            _ => None,
                         })
                         .collect::<Vec<_>>();
 
-                    record.imports.insert(&module_path[..], words);
+                    // Modules may be imported from and re-exported from
+                    // more than once so we must extend any existing entry
+                    let list = record
+                        .imports
+                        .entry(&module_path[..])
+                        .or_insert(Vec::new());
+                    list.extend(words);
 
                     for spec in specifiers {
                         match spec {
@@ -182,13 +290,21 @@ impl Parser {
                                     .live_export_map
                                     .insert(key, (val, false));
                             }
+                            // The namespace object is bound to a local
+                            // by the imports so it is a fixed export
+                            ExportSpecifier::Namespace(export) => {
+                                let name = export.name.sym.as_ref();
+                                record
+                                    .fixed_export_map
+                                    .insert(name, vec![name]);
+                            }
                             _ => {}
                         }
                     }
                 }
                 ReexportRecord::All { module_path } => {
                     let module_path = &module_path[..];
-                    record.imports.insert(module_path, Vec::new());
+                    record.imports.entry(module_path).or_insert(Vec::new());
                     record.export_alls.push(module_path);
                 }
             }
@@ -197,3 +313,27 @@ impl Parser {
         Ok(record)
     }
 }
+
+/// Determine if a top-level variable declaration in a module
+/// declares the given name.
+fn declares_var(module: &Module, name: &str) -> bool {
+    var_decl_kind(module, name).is_some()
+}
+
+/// Get the kind of the top-level variable declaration for a name.
+fn var_decl_kind(module: &Module, name: &str) -> Option<VarDeclKind> {
+    let declares = |var: &VarDecl| {
+        var.decls.iter().any(|decl| match &decl.name {
+            Pat::Ident(binding) => binding.id.sym.as_ref() == name,
+            _ => false,
+        })
+    };
+    module.body.iter().find_map(|item| match item {
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(var)))
+        | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            decl: Decl::Var(var),
+            ..
+        })) if declares(var) => Some(var.kind.clone()),
+        _ => None,
+    })
+}
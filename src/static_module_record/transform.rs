@@ -1,13 +1,12 @@
 //! Transform a module to a static module record program.
+use std::fmt;
+use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 
-use swc::{
-    config::{JscTarget, SourceMapsConfig},
-    TransformOutput,
-};
+use swc::{config::SourceMapsConfig, TransformOutput};
 use swc_common::{
     errors::{emitter::ColorConfig, Handler},
     FileName, SourceMap, DUMMY_SP,
@@ -15,20 +14,24 @@ use swc_common::{
 
 use swc_atoms::JsWord;
 use swc_ecma_ast::*;
-use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
 use swc_ecma_visit::{Node, Visit, VisitWith};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 
 use super::{
-    ImportName, Parser as StaticModuleRecordParser, StaticModuleRecord,
-    StaticModuleRecordMeta,
+    ExportNames, ImportKind, ImportName, Parser as StaticModuleRecordParser,
+    StaticModuleRecord, StaticModuleRecordMeta, StaticModuleRecordProgram,
 };
 
-use crate::{helpers::var_symbol_names, swc_utils};
+use crate::{
+    helpers::{var_symbol_names, EXPORTS, MODULE, REQUIRE},
+    swc_utils,
+};
 
-const HIDDEN_PREFIX: &str = "$h\u{200d}_";
-const HIDDEN_CONST_VAR_PREFIX: &str = "$c\u{200d}_";
+/// Default prefix for hidden variables in the generated functor.
+pub const HIDDEN_PREFIX: &str = "$h\u{200d}_";
+/// Default prefix for hidden constant variables in the generated functor.
+pub const HIDDEN_CONST_VAR_PREFIX: &str = "$c\u{200d}_";
 const IMPORTS: &str = "imports";
 const LIVE_VAR: &str = "liveVar";
 const ONCE_VAR: &str = "onceVar";
@@ -40,17 +43,101 @@ const OBJECT: &str = "Object";
 const DEFINE_PROPERTY: &str = "defineProperty";
 const NAME: &str = "name";
 const VALUE: &str = "value";
+const ES_MODULE: &str = "__esModule";
 
-fn prefix_hidden(word: &str) -> JsWord {
-    format!("{}{}", HIDDEN_PREFIX, word).into()
+/// Prefixes for the hidden variables declared by the generated functor.
+#[derive(Debug, Clone)]
+pub struct HiddenPrefix {
+    hidden: String,
+    constant: String,
 }
 
-fn prefix_const(word: &str) -> JsWord {
-    format!("{}{}", HIDDEN_CONST_VAR_PREFIX, word).into()
+impl Default for HiddenPrefix {
+    fn default() -> Self {
+        Self {
+            hidden: HIDDEN_PREFIX.to_string(),
+            constant: HIDDEN_CONST_VAR_PREFIX.to_string(),
+        }
+    }
+}
+
+impl HiddenPrefix {
+    /// Create prefixes for hidden variables and hidden constant variables.
+    ///
+    /// Each prefix must be a legal identifier and the prefixes must not
+    /// overlap otherwise generated names could collide.
+    pub fn new(hidden: &str, constant: &str) -> Result<Self> {
+        for prefix in [hidden, constant].iter() {
+            if !is_identifier(prefix) {
+                bail!("hidden prefix {:?} is not a valid identifier", prefix);
+            }
+        }
+        if hidden.starts_with(constant) || constant.starts_with(hidden) {
+            bail!(
+                "hidden prefixes {:?} and {:?} must not overlap",
+                hidden,
+                constant
+            );
+        }
+        Ok(Self {
+            hidden: hidden.to_string(),
+            constant: constant.to_string(),
+        })
+    }
+
+    /// Extend the prefixes until no identifier in the module
+    /// starts with either prefix.
+    pub fn avoid(mut self, module: &Module) -> Self {
+        let mut collector = IdentCollector {
+            names: Default::default(),
+        };
+        module.visit_children_with(&mut collector);
+        while collector.names.iter().any(|name| {
+            name.starts_with(&self.hidden) || name.starts_with(&self.constant)
+        }) {
+            self.hidden.push('_');
+            self.constant.push('_');
+        }
+        self
+    }
+
+    fn hidden(&self, word: &str) -> JsWord {
+        format!("{}{}", self.hidden, word).into()
+    }
+
+    fn constant(&self, word: &str) -> JsWord {
+        format!("{}{}", self.constant, word).into()
+    }
+}
+
+/// Determine if a string is a legal identifier.
+fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(c) if c == '$' || c == '_' || c.is_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| {
+        c == '$'
+            || c == '_'
+            || c == '\u{200c}'
+            || c == '\u{200d}'
+            || c.is_alphanumeric()
+    })
+}
+
+/// Collect all the identifier names in a module.
+struct IdentCollector {
+    names: IndexSet<JsWord>,
+}
+
+impl Visit for IdentCollector {
+    fn visit_ident(&mut self, n: &Ident, _: &dyn Node) {
+        self.names.insert(n.sym.clone());
+    }
 }
 
 /// Sources that may be transformed
-#[derive(Debug)]
 pub enum TransformSource {
     /// Load a file from disc for the transformation.
     File(PathBuf),
@@ -61,6 +148,50 @@ pub enum TransformSource {
         /// The file name for the module.
         file_name: String,
     },
+    /// Load from a reader.
+    ///
+    /// The source is read into a single buffer that is handed to
+    /// the source map without copying.
+    Reader {
+        /// Reader for the module source.
+        reader: Box<dyn Read>,
+        /// The file name for the module.
+        file_name: String,
+    },
+}
+
+impl TransformSource {
+    /// Read the source for a reader into a string.
+    ///
+    /// For other variants `None` is returned.
+    pub fn read(&mut self) -> Result<Option<String>> {
+        if let TransformSource::Reader { reader, .. } = self {
+            let mut content = String::new();
+            reader.read_to_string(&mut content)?;
+            Ok(Some(content))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl fmt::Debug for TransformSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransformSource::File(path) => {
+                f.debug_tuple("File").field(path).finish()
+            }
+            TransformSource::Str { content, file_name } => f
+                .debug_struct("Str")
+                .field("content", content)
+                .field("file_name", file_name)
+                .finish(),
+            TransformSource::Reader { file_name, .. } => f
+                .debug_struct("Reader")
+                .field("file_name", file_name)
+                .finish(),
+        }
+    }
 }
 
 impl From<PathBuf> for TransformSource {
@@ -75,6 +206,44 @@ impl From<&str> for TransformSource {
     }
 }
 
+/// Options for transforming to a static module record program.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TransformOptions {
+    /// Include a source map for the program.
+    pub source_map: bool,
+    /// Print the program using minified formatting.
+    pub minify: bool,
+    /// Shape of the generated program.
+    pub output_kind: OutputKind,
+    /// Sort the imports by module specifier.
+    pub sort_imports: bool,
+}
+
+/// Shape of the program created by the generator.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OutputKind {
+    /// Functor that accepts `{ imports, liveVar, onceVar }` for
+    /// static module record runtimes.
+    StaticModuleRecord,
+    /// Function that accepts `require`, `module` and `exports` for
+    /// CommonJS runtimes.
+    ///
+    /// The functor is invoked with implementations of `imports`,
+    /// `liveVar` and `onceVar` that call `require` and assign to
+    /// `exports`; live exports are defined as getters so later
+    /// assignments are visible to importers.
+    ///
+    /// Modules that use top-level await are not supported as the
+    /// exports must be assigned before the function returns.
+    CommonJs,
+}
+
+impl Default for OutputKind {
+    fn default() -> Self {
+        OutputKind::StaticModuleRecord
+    }
+}
+
 /// Result of parsing a source module.
 pub struct ParseOutput<'a> {
     /// The source map.
@@ -93,24 +262,140 @@ pub fn transform(
     source: TransformSource,
     source_map: Arc<SourceMap>,
 ) -> Result<(StaticModuleRecordMeta, TransformOutput)> {
-    let (meta, program) = transform_program(source, Arc::clone(&source_map))?;
+    transform_print(source, source_map, Default::default())
+}
+
+/// Transform source to codegen output and generate a source map.
+pub fn transform_with_source_map(
+    source: TransformSource,
+    source_map: Arc<SourceMap>,
+) -> Result<(StaticModuleRecordMeta, TransformOutput)> {
+    let options = TransformOptions {
+        source_map: true,
+        ..Default::default()
+    };
+    transform_print(source, source_map, options)
+}
+
+/// Transform source to codegen output resolving the names provided
+/// by the `export * from` declarations.
+///
+/// The map contains the meta data for star exported modules keyed by
+/// module specifier; the star exports for these modules are emitted as
+/// named re-exports so local exports take precedence. Names provided
+/// by more than one star export are not exported and are returned in
+/// the ambiguous export names.
+pub fn transform_with_export_alls(
+    source: TransformSource,
+    source_map: Arc<SourceMap>,
+    export_alls: &IndexMap<String, StaticModuleRecordMeta>,
+) -> Result<(StaticModuleRecordMeta, TransformOutput, ExportNames)> {
+    let module = parse_source(source, Arc::clone(&source_map))?;
+    let (meta, program, names) = generate_script_with_exports(
+        &module,
+        &Default::default(),
+        export_alls,
+    )?;
+    let result = print_script(&program, source_map, &Default::default())?;
+    Ok((meta, result, names))
+}
+
+fn transform_print(
+    source: TransformSource,
+    source_map: Arc<SourceMap>,
+    options: TransformOptions,
+) -> Result<(StaticModuleRecordMeta, TransformOutput)> {
+    let module = parse_source(source, Arc::clone(&source_map))?;
+    let (meta, program) = generate_script(&module, &options)?;
+    let result = print_script(&program, source_map, &options)?;
+    Ok((meta, result))
+}
 
-    let result = swc_utils::print(
-        &program,
+/// Print a generated program using the transform options.
+fn print_script(
+    program: &Program,
+    source_map: Arc<SourceMap>,
+    options: &TransformOptions,
+) -> Result<TransformOutput> {
+    swc_utils::print_minify(
+        program,
         source_map,
         None,
         None,
-        SourceMapsConfig::Bool(false),
-    )?;
+        SourceMapsConfig::Bool(options.source_map),
+        options.minify,
+    )
+}
 
-    Ok((meta, result))
+/// Transform source to a static module record program.
+///
+/// The trailing semi-colon is removed from the generated functor
+/// program as it breaks static module record interoperability.
+pub fn transform_program(
+    source: TransformSource,
+) -> Result<StaticModuleRecordProgram> {
+    transform_program_with_options(source, Default::default())
+}
+
+/// Transform source to a static module record program
+/// that includes a source map.
+pub fn transform_program_with_source_map(
+    source: TransformSource,
+) -> Result<StaticModuleRecordProgram> {
+    let options = TransformOptions {
+        source_map: true,
+        ..Default::default()
+    };
+    transform_program_with_options(source, options)
+}
+
+/// Transform source to a static module record program using
+/// the given options.
+///
+/// The trailing semi-colon is also removed from minified output.
+pub fn transform_program_with_options(
+    source: TransformSource,
+    options: TransformOptions,
+) -> Result<StaticModuleRecordProgram> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (meta, result) = transform_print(source, source_map, options)?;
+    Ok(StaticModuleRecordProgram {
+        meta,
+        program: trim_code(result.code),
+        map: result.map,
+    })
+}
+
+/// Remove a trailing semi-colon from the generated functor program.
+fn trim_code(code: String) -> String {
+    let out = code.trim_end();
+    out.trim_end_matches(";").to_string()
 }
 
 /// Transform source to a script program.
-pub fn transform_program(
+pub fn transform_script(
     source: TransformSource,
     source_map: Arc<SourceMap>,
 ) -> Result<(StaticModuleRecordMeta, Program)> {
+    let module = parse_source(source, source_map)?;
+    Ok(transform_module_script(&module)?)
+}
+
+/// Compute the static module record meta data for source without
+/// generating the transformed program.
+pub fn module_meta(source: TransformSource) -> Result<StaticModuleRecordMeta> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let module = parse_source(source, source_map)?;
+    let mut parser = StaticModuleRecordParser::new();
+    let meta = parser.parse(&module)?;
+    Ok(meta.into())
+}
+
+/// Parse source to a module.
+fn parse_source(
+    mut source: TransformSource,
+    source_map: Arc<SourceMap>,
+) -> Result<Module> {
     let handler = Handler::with_tty_emitter(
         ColorConfig::Auto,
         true,
@@ -118,46 +403,132 @@ pub fn transform_program(
         Some(source_map.clone()),
     );
 
-    let fm = match source {
-        TransformSource::File(path) => source_map.load_file(&path)?,
-        TransformSource::Str { content, file_name } => source_map
-            .new_source_file(
+    let content = source.read()?;
+    let (fm, syntax) = match source {
+        TransformSource::File(path) => {
+            swc_utils::load_source_file(&source_map, &path)?
+        }
+        TransformSource::Str { content, file_name } => {
+            let fm = source_map.new_source_file(
                 FileName::Custom(file_name.into()),
                 content.into(),
-            ),
+            );
+            let syntax = swc_utils::get_syntax(&fm.name);
+            (fm, syntax)
+        }
+        TransformSource::Reader { file_name, .. } => {
+            let fm = source_map
+                .new_source_file(FileName::Custom(file_name), content.unwrap());
+            let syntax = swc_utils::get_syntax(&fm.name);
+            (fm, syntax)
+        }
     };
 
-    let lexer = Lexer::new(
-        Syntax::Es(Default::default()),
-        JscTarget::Es2020,
-        StringInput::from(&*fm),
-        None,
-    );
-
-    let mut parser = Parser::new_from(lexer);
+    let mut parser = swc_utils::get_parser_with_syntax(&*fm, syntax);
     for e in parser.take_errors() {
         e.into_diagnostic(&handler).emit();
     }
 
-    let module = parser
-        .parse_module()
-        .map_err(|e| e.into_diagnostic(&handler).emit())
-        .expect("failed to parse module");
+    let module = parser.parse_module().map_err(|e| {
+        let error = swc_utils::ParseError::new(&e, &source_map);
+        e.into_diagnostic(&handler).emit();
+        error
+    })?;
 
-    Ok(transform_module_script(&module)?)
+    Ok(module)
 }
 
 /// Transform a module to a script program.
 pub fn transform_module_script(
     module: &Module,
 ) -> Result<(StaticModuleRecordMeta, Program)> {
+    transform_module_script_as(module, Default::default())
+}
+
+/// Transform a module to a script program with the given output shape.
+pub fn transform_module_script_as(
+    module: &Module,
+    output_kind: OutputKind,
+) -> Result<(StaticModuleRecordMeta, Program)> {
+    let options = TransformOptions {
+        output_kind,
+        ..Default::default()
+    };
+    generate_script(module, &options)
+}
+
+/// Generate a script program using the generator options.
+fn generate_script(
+    module: &Module,
+    options: &TransformOptions,
+) -> Result<(StaticModuleRecordMeta, Program)> {
+    let (meta, program, _) =
+        generate_script_with_exports(module, options, &Default::default())?;
+    Ok((meta, program))
+}
+
+/// Generate a script program resolving the names provided by the
+/// star exports using the meta data for the star exported modules.
+fn generate_script_with_exports(
+    module: &Module,
+    options: &TransformOptions,
+    export_alls: &IndexMap<String, StaticModuleRecordMeta>,
+) -> Result<(StaticModuleRecordMeta, Program, ExportNames)> {
+    // Declared first as the record borrows the resolved names
+    let names: ExportNames;
     let mut parser = StaticModuleRecordParser::new();
-    let meta = parser.parse(module)?;
-    let generator = Generator::new(&meta);
+    let mut meta = parser.parse(module)?;
+
+    names = if export_alls.is_empty() {
+        Default::default()
+    } else {
+        StaticModuleRecordMeta::from(&meta).resolve_exports(export_alls)
+    };
+
+    for (name, specs) in names.ambiguous.iter() {
+        log::warn!(
+            "export {} is ambiguous, it is provided by {}",
+            name,
+            specs.join(", ")
+        );
+    }
+
+    resolve_star_exports(&mut meta, &names, export_alls);
+    let generator = Generator::new(&meta)
+        .output_kind(options.output_kind)
+        .sort_imports(options.sort_imports);
     let script = generator
         .script()
         .context("failed to generate transformed script")?;
-    Ok((meta.into(), Program::Script(script)))
+    Ok((meta.into(), Program::Script(script), names))
+}
+
+/// Replace the star exports that have meta data with named re-exports
+/// of the names resolved for each star exported module.
+///
+/// Names exported locally or by more than one star export are not
+/// resolved to a module so they are not re-exported.
+fn resolve_star_exports<'a>(
+    record: &mut StaticModuleRecord<'a>,
+    names: &'a ExportNames,
+    export_alls: &IndexMap<String, StaticModuleRecordMeta>,
+) {
+    for (name, spec) in names.names.iter() {
+        if let Some(spec) = spec {
+            record.imports.entry(&spec[..]).or_insert(Vec::new()).push(
+                ImportName {
+                    name: &name[..],
+                    alias: None,
+                    kind: ImportKind::Named,
+                    reexport: true,
+                },
+            );
+            record.live_export_map.insert(&name[..], (&name[..], false));
+        }
+    }
+    record
+        .export_alls
+        .retain(|spec| !export_alls.contains_key(*spec));
 }
 
 /// Transform a module to a function.
@@ -175,7 +546,88 @@ pub fn transform_module_function(
 
 struct Visitor<'a> {
     meta: &'a StaticModuleRecord<'a>,
+    prefix: &'a HiddenPrefix,
     body: &'a mut Vec<Stmt>,
+    /// Fixed export names that have already been emitted.
+    once_exports: IndexSet<String>,
+}
+
+impl Visitor<'_> {
+    /// Declare the local variables for live exports of local bindings
+    /// as live bindings, eg: `let count = 0; export default count; count++;`
+    /// or `let count = 0; export { count }; count++;`
+    ///
+    /// Returns `false` when the declaration does not declare a live local.
+    fn live_local_decl(&mut self, var: &VarDecl) -> bool {
+        let meta = self.meta;
+        let live_local = |decl: &VarDeclarator| match &decl.name {
+            Pat::Ident(binding) => {
+                let name = binding.id.sym.as_ref();
+                meta.live_export_map
+                    .values()
+                    .find(|(local, live)| *live && *local == name)
+                    .map(|(local, _)| *local)
+            }
+            _ => None,
+        };
+        if !var.decls.iter().any(|decl| live_local(decl).is_some()) {
+            return false;
+        }
+
+        let mut decls = Vec::new();
+        for decl in var.decls.iter() {
+            let local = match live_local(decl) {
+                Some(local) => local,
+                None => {
+                    decls.push(decl.clone());
+                    continue;
+                }
+            };
+
+            if !decls.is_empty() {
+                self.body.push(Stmt::Decl(Decl::Var(VarDecl {
+                    span: DUMMY_SP,
+                    kind: var.kind.clone(),
+                    declare: false,
+                    decls: std::mem::take(&mut decls),
+                })));
+            }
+
+            let prop_name = self.prefix.constant(local);
+            self.body.push(Stmt::Decl(Decl::Var(VarDecl {
+                span: DUMMY_SP,
+                kind: VarDeclKind::Let,
+                declare: false,
+                decls: vec![VarDeclarator {
+                    span: DUMMY_SP,
+                    name: Pat::Ident(BindingIdent {
+                        id: Ident {
+                            span: DUMMY_SP,
+                            sym: prop_name.clone(),
+                            optional: false,
+                        },
+                        type_ann: None,
+                    }),
+                    init: decl.init.clone(),
+                    definite: false,
+                }],
+            })));
+            let prop_target = self.prefix.hidden(LIVE);
+            self.body
+                .push(call_stmt(prop_target, local, Some(prop_name)));
+        }
+
+        if !decls.is_empty() {
+            self.body.push(Stmt::Decl(Decl::Var(VarDecl {
+                span: DUMMY_SP,
+                kind: var.kind.clone(),
+                declare: false,
+                decls,
+            })));
+        }
+
+        true
+    }
 }
 
 fn call_stmt(
@@ -348,8 +800,25 @@ impl<'a> Visit for Visitor<'a> {
         match n {
             ModuleItem::ModuleDecl(decl) => match decl {
                 ModuleDecl::ExportNamed(export) => {
+                    // Namespace re-export, eg: `export * as ns from './foo.js'`
+                    if export.src.is_some() {
+                        for spec in export.specifiers.iter() {
+                            if let ExportSpecifier::Namespace(spec) = spec {
+                                let name = spec.name.sym.as_ref();
+                                if !self.once_exports.insert(name.to_string()) {
+                                    continue;
+                                }
+                                let prop_target = self.prefix.hidden(ONCE);
+                                let call = call_stmt(
+                                    prop_target,
+                                    name,
+                                    Some(name.into()),
+                                );
+                                self.body.push(call);
+                            }
+                        }
                     // Not a re-export
-                    if export.src.is_none() {
+                    } else {
                         for spec in export.specifiers.iter() {
                             if let ExportSpecifier::Named(spec) = spec {
                                 let export_name = spec
@@ -359,7 +828,18 @@ impl<'a> Visit for Visitor<'a> {
                                     .sym
                                     .as_ref();
                                 let local_name = spec.orig.sym.as_ref();
-                                let prop_target = prefix_hidden(ONCE);
+                                // Live exports are declared with the local
+                                if self
+                                    .meta
+                                    .live_export_map
+                                    .contains_key(export_name)
+                                    || !self
+                                        .once_exports
+                                        .insert(export_name.to_string())
+                                {
+                                    continue;
+                                }
+                                let prop_target = self.prefix.hidden(ONCE);
                                 let call = call_stmt(
                                     prop_target,
                                     export_name,
@@ -371,8 +851,8 @@ impl<'a> Visit for Visitor<'a> {
                     }
                 }
                 ModuleDecl::ExportDefaultDecl(export) => {
-                    let prop_target = prefix_hidden(ONCE);
-                    let prop_arg = prefix_const(DEFAULT);
+                    let prop_target = self.prefix.hidden(ONCE);
+                    let prop_arg = self.prefix.constant(DEFAULT);
                     let value_expr = match &export.decl {
                         DefaultDecl::Class(class_expr) => {
                             Box::new(Expr::Class(class_expr.clone()))
@@ -390,9 +870,12 @@ impl<'a> Visit for Visitor<'a> {
                 ModuleDecl::ExportDefaultExpr(export) => {
                     // const { default: $c_default } = { default: 42 };
                     // $h_once.default($c_default);
+                    //
+                    // A live default export is bound when the
+                    // local variable is declared
                     if self.meta.fixed_export_map.contains_key(DEFAULT) {
-                        let prop_target = prefix_hidden(ONCE);
-                        let prop_arg = prefix_const(DEFAULT);
+                        let prop_target = self.prefix.hidden(ONCE);
+                        let prop_arg = self.prefix.constant(DEFAULT);
                         let value_expr = export.expr.clone();
                         let (default_stmt, call) =
                             default_stmt(prop_target, prop_arg, value_expr);
@@ -406,7 +889,13 @@ impl<'a> Visit for Visitor<'a> {
                         for (decl, names) in symbols {
                             let mut decl_emitted = false;
                             for name in names {
-                                if self.meta.fixed_export_map.contains_key(name)
+                                let export_names =
+                                    self.meta.fixed_export_names(name);
+                                if !export_names.is_empty()
+                                    && !self
+                                        .meta
+                                        .live_export_map
+                                        .contains_key(name)
                                 {
                                     if !decl_emitted {
                                         self.body.push(Stmt::Decl(Decl::Var(
@@ -420,21 +909,31 @@ impl<'a> Visit for Visitor<'a> {
                                         decl_emitted = true;
                                     }
 
-                                    let prop_target = prefix_hidden(ONCE);
-                                    // TODO: handle alias in fixed exports!
-                                    let call = call_stmt(
-                                        prop_target,
-                                        name,
-                                        Some(name.into()),
-                                    );
-                                    self.body.push(call);
+                                    // Use the public export name as the
+                                    // property and pass the local symbol
+                                    for export_name in export_names {
+                                        if !self
+                                            .once_exports
+                                            .insert(export_name.to_string())
+                                        {
+                                            continue;
+                                        }
+                                        let prop_target =
+                                            self.prefix.hidden(ONCE);
+                                        let call = call_stmt(
+                                            prop_target,
+                                            export_name,
+                                            Some(name.into()),
+                                        );
+                                        self.body.push(call);
+                                    }
                                 } else if self
                                     .meta
                                     .live_export_map
                                     .contains_key(name)
                                 {
-                                    let prop_name = prefix_const(name);
-                                    let prop_target = prefix_hidden(LIVE);
+                                    let prop_name = self.prefix.constant(name);
+                                    let prop_target = self.prefix.hidden(LIVE);
                                     if !decl_emitted {
                                         self.body.push(Stmt::Decl(Decl::Var(
                                             VarDecl {
@@ -501,7 +1000,8 @@ impl<'a> Visit for Visitor<'a> {
                     }
                     Decl::Fn(func) => {
                         // Rename the function so it matches the hoisted function statements
-                        let target = prefix_const(func.ident.sym.as_ref());
+                        let target =
+                            self.prefix.constant(func.ident.sym.as_ref());
                         let mut ident = func.ident.clone();
                         ident.sym = JsWord::from(target.clone());
 
@@ -519,7 +1019,7 @@ impl<'a> Visit for Visitor<'a> {
 
                         // Set up the live export
                         let name = class.ident.sym.as_ref();
-                        let prop_target = prefix_hidden(LIVE);
+                        let prop_target = self.prefix.hidden(LIVE);
                         let call = call_stmt(
                             prop_target,
                             name,
@@ -531,7 +1031,14 @@ impl<'a> Visit for Visitor<'a> {
                 },
                 _ => {}
             },
-            ModuleItem::Stmt(stmt) => self.visit_stmt(stmt, node),
+            ModuleItem::Stmt(stmt) => {
+                if let Stmt::Decl(Decl::Var(var)) = stmt {
+                    if self.live_local_decl(var) {
+                        return;
+                    }
+                }
+                self.visit_stmt(stmt, node)
+            }
         }
     }
 
@@ -543,20 +1050,66 @@ impl<'a> Visit for Visitor<'a> {
 /// Generate a static module record functor program.
 pub struct Generator<'a> {
     meta: &'a StaticModuleRecord<'a>,
+    prefix: HiddenPrefix,
+    output_kind: OutputKind,
+    sort_imports: bool,
 }
 
 impl<'a> Generator<'a> {
     /// Create a new generator.
     pub fn new(meta: &'a StaticModuleRecord<'a>) -> Self {
-        Generator { meta }
+        Generator::with_prefix(meta, Default::default())
+    }
+
+    /// Create a new generator using the given hidden variable prefixes.
+    ///
+    /// The prefixes are extended when necessary so that they do not
+    /// collide with identifiers in the module.
+    pub fn with_prefix(
+        meta: &'a StaticModuleRecord<'a>,
+        prefix: HiddenPrefix,
+    ) -> Self {
+        let prefix = prefix.avoid(meta.module);
+        Generator {
+            meta,
+            prefix,
+            output_kind: Default::default(),
+            sort_imports: false,
+        }
+    }
+
+    /// Set the shape of the generated program.
+    pub fn output_kind(mut self, output_kind: OutputKind) -> Self {
+        self.output_kind = output_kind;
+        self
+    }
+
+    /// Sort the imports by module specifier.
+    ///
+    /// By default imports are emitted in source order to match
+    /// endo; sorting gives stable output when diffing programs.
+    pub fn sort_imports(mut self, sort_imports: bool) -> Self {
+        self.sort_imports = sort_imports;
+        self
     }
 
     /// Create the program as a function.
     pub fn function(&self) -> Result<Function> {
+        let is_async = has_top_level_await(self.meta.module);
+        let (params, body) = match self.output_kind {
+            OutputKind::StaticModuleRecord => (self.params(), self.body()),
+            OutputKind::CommonJs => {
+                if is_async {
+                    bail!(
+                        "top-level await is not supported for CommonJS output"
+                    );
+                }
+                (self.commonjs_params(), self.commonjs_body())
+            }
+        };
         Ok(Function {
             span: DUMMY_SP,
-            params: self
-                .params()
+            params: params
                 .into_iter()
                 .map(|pat| Param {
                     span: DUMMY_SP,
@@ -564,10 +1117,10 @@ impl<'a> Generator<'a> {
                     pat,
                 })
                 .collect(),
-            body: Some(self.body()),
+            body: Some(body),
             decorators: vec![],
             is_generator: false,
-            is_async: false,
+            is_async,
             type_params: None,
             return_type: None,
         })
@@ -578,22 +1131,22 @@ impl<'a> Generator<'a> {
         let mut script = Script {
             span: DUMMY_SP,
             body: Vec::with_capacity(1),
-            shebang: None,
+            shebang: self.meta.module.shebang.clone(),
+        };
+
+        let expr = match self.output_kind {
+            OutputKind::StaticModuleRecord => self.functor(self.body()),
+            OutputKind::CommonJs => Expr::Fn(FnExpr {
+                ident: None,
+                function: self.function()?,
+            }),
         };
 
         let stmt = Stmt::Expr(ExprStmt {
             span: DUMMY_SP,
             expr: Box::new(Expr::Paren(ParenExpr {
                 span: DUMMY_SP,
-                expr: Box::new(Expr::Arrow(ArrowExpr {
-                    span: DUMMY_SP,
-                    params: self.params(),
-                    body: BlockStmtOrExpr::BlockStmt(self.body()),
-                    is_async: false,
-                    is_generator: false,
-                    type_params: None,
-                    return_type: None,
-                })),
+                expr: Box::new(expr),
             })),
         });
 
@@ -602,6 +1155,296 @@ impl<'a> Generator<'a> {
         Ok(script)
     }
 
+    /// The functor arrow function for a body block.
+    ///
+    /// The functor is async when the module uses top-level await.
+    fn functor(&self, body: BlockStmt) -> Expr {
+        Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            params: self.params(),
+            body: BlockStmtOrExpr::BlockStmt(body),
+            is_async: has_top_level_await(self.meta.module),
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        })
+    }
+
+    /// The CommonJS function parameters.
+    fn commonjs_params(&self) -> Vec<Pat> {
+        [REQUIRE, MODULE, EXPORTS]
+            .iter()
+            .map(|name| {
+                Pat::Ident(BindingIdent {
+                    id: Ident {
+                        span: DUMMY_SP,
+                        sym: (*name).into(),
+                        optional: false,
+                    },
+                    type_ann: None,
+                })
+            })
+            .collect()
+    }
+
+    /// The CommonJS function body block.
+    ///
+    /// Declares the live export bindings and the functor arguments
+    /// then invokes the functor.
+    fn commonjs_body(&self) -> BlockStmt {
+        let body = self.body();
+        let live = hidden_props(&body, self.prefix.hidden(LIVE));
+        let once = hidden_props(&body, self.prefix.hidden(ONCE));
+        let env = self.prefix.hidden("env");
+
+        let mut stmts = self.commonjs_env(env.clone(), &live, &once);
+        stmts.push(expr_stmt(call(
+            paren(Box::new(self.functor(body))),
+            vec![ident_expr(env)],
+        )));
+
+        BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        }
+    }
+
+    /// Statements that declare the functor arguments backed by
+    /// `require` and `exports`.
+    ///
+    /// Names passed to `liveVar` are declared as local bindings so that
+    /// references in the functor resolve to them; re-exported names
+    /// are only set on the exports.
+    fn commonjs_env(
+        &self,
+        env: JsWord,
+        live: &IndexSet<String>,
+        once: &IndexSet<String>,
+    ) -> Vec<Stmt> {
+        let arg = self.prefix.hidden("a");
+        let mut bindings = Vec::new();
+        let mut live_vars = Vec::new();
+        for name in live.iter() {
+            let entries: Vec<(&str, bool)> = self
+                .meta
+                .live_export_map
+                .iter()
+                .filter(|(_, (local, _))| local == name)
+                .map(|(key, (_, is_local))| (*key, *is_local))
+                .collect();
+            let reexport =
+                !entries.is_empty() && entries.iter().all(|(_, l)| !l);
+            let keys: Vec<&str> = if entries.is_empty() {
+                vec![&name[..]]
+            } else {
+                entries.iter().map(|(key, _)| *key).collect()
+            };
+
+            let mut setter = Vec::new();
+            let value: JsWord = if reexport {
+                arg.clone()
+            } else {
+                bindings.push(name.clone());
+                setter.push(expr_stmt(assign(
+                    PatOrExpr::Pat(Box::new(binding(&name[..]))),
+                    ident_expr(arg.clone()),
+                )));
+                name[..].into()
+            };
+            for key in keys {
+                setter.push(expr_stmt(call(
+                    member(ident_expr(OBJECT), DEFINE_PROPERTY),
+                    vec![
+                        ident_expr(EXPORTS),
+                        str_lit(key),
+                        object(vec![
+                            key_value("enumerable", bool_lit(true)),
+                            key_value("configurable", bool_lit(true)),
+                            key_value(
+                                "get",
+                                arrow_expr(vec![], ident_expr(value.clone())),
+                            ),
+                        ]),
+                    ],
+                )));
+            }
+            live_vars.push(str_key_value(
+                name,
+                arrow(vec![binding(arg.clone())], setter),
+            ));
+        }
+
+        let once_vars = once
+            .iter()
+            .map(|name| {
+                str_key_value(
+                    name,
+                    arrow(
+                        vec![binding(arg.clone())],
+                        vec![expr_stmt(assign(
+                            PatOrExpr::Expr(computed(
+                                ident_expr(EXPORTS),
+                                str_lit(name),
+                            )),
+                            ident_expr(arg.clone()),
+                        ))],
+                    ),
+                )
+            })
+            .collect();
+
+        let mut stmts = Vec::new();
+        if !bindings.is_empty() {
+            stmts.push(Stmt::Decl(Decl::Var(VarDecl {
+                span: DUMMY_SP,
+                kind: VarDeclKind::Let,
+                declare: false,
+                decls: bindings
+                    .iter()
+                    .map(|name| VarDeclarator {
+                        span: DUMMY_SP,
+                        definite: false,
+                        init: None,
+                        name: binding(&name[..]),
+                    })
+                    .collect(),
+            })));
+        }
+        stmts.push(expr_stmt(call(
+            member(ident_expr(OBJECT), DEFINE_PROPERTY),
+            vec![
+                ident_expr(EXPORTS),
+                str_lit(ES_MODULE),
+                object(vec![key_value(VALUE, bool_lit(true))]),
+            ],
+        )));
+        stmts.push(const_decl(
+            binding(env),
+            object(vec![
+                key_value(IMPORTS, self.commonjs_imports()),
+                key_value(LIVE_VAR, object(live_vars)),
+                key_value(ONCE_VAR, object(once_vars)),
+            ]),
+        ));
+        stmts
+    }
+
+    /// The CommonJS `imports` function.
+    ///
+    /// Requires each module specifier and passes the imported values
+    /// to the setters; names provided by star exports are defined on
+    /// the exports unless they are already exported.
+    fn commonjs_imports(&self) -> Box<Expr> {
+        // const ns = require(specifier);
+        let require_ns = || {
+            const_decl(
+                binding("ns"),
+                call(ident_expr(REQUIRE), vec![ident_expr("specifier")]),
+            )
+        };
+
+        // name === "*" || (name === "default" && !(ns && ns.__esModule))
+        let is_namespace = bin(
+            BinaryOp::LogicalOr,
+            bin(BinaryOp::EqEqEq, ident_expr(NAME), str_lit("*")),
+            paren(bin(
+                BinaryOp::LogicalAnd,
+                bin(BinaryOp::EqEqEq, ident_expr(NAME), str_lit(DEFAULT)),
+                not(paren(bin(
+                    BinaryOp::LogicalAnd,
+                    ident_expr("ns"),
+                    member(ident_expr("ns"), ES_MODULE),
+                ))),
+            )),
+        );
+        let value = Box::new(Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: is_namespace,
+            cons: ident_expr("ns"),
+            alt: computed(ident_expr("ns"), ident_expr(NAME)),
+        }));
+        let names = for_of(
+            array_pat(&[NAME, "setters"]),
+            ident_expr("names"),
+            vec![
+                const_decl(binding(VALUE), value),
+                for_of(
+                    binding("setter"),
+                    ident_expr("setters"),
+                    vec![expr_stmt(call(
+                        ident_expr("setter"),
+                        vec![ident_expr(VALUE)],
+                    ))],
+                ),
+            ],
+        );
+        let imports = for_of(
+            array_pat(&["specifier", "names"]),
+            ident_expr(IMPORTS),
+            vec![require_ns(), names],
+        );
+
+        // name !== "default"
+        //   && !Object.prototype.hasOwnProperty.call(exports, name)
+        let is_exported = call(
+            member(
+                member(
+                    member(ident_expr(OBJECT), "prototype"),
+                    "hasOwnProperty",
+                ),
+                "call",
+            ),
+            vec![ident_expr(EXPORTS), ident_expr(NAME)],
+        );
+        let define = expr_stmt(call(
+            member(ident_expr(OBJECT), DEFINE_PROPERTY),
+            vec![
+                ident_expr(EXPORTS),
+                ident_expr(NAME),
+                object(vec![
+                    key_value("enumerable", bool_lit(true)),
+                    key_value(
+                        "get",
+                        arrow_expr(
+                            vec![],
+                            computed(ident_expr("ns"), ident_expr(NAME)),
+                        ),
+                    ),
+                ]),
+            ],
+        ));
+        let star = Stmt::If(IfStmt {
+            span: DUMMY_SP,
+            test: bin(
+                BinaryOp::LogicalAnd,
+                bin(BinaryOp::NotEqEq, ident_expr(NAME), str_lit(DEFAULT)),
+                not(is_exported),
+            ),
+            cons: Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![define],
+            })),
+            alt: None,
+        });
+        let all = for_of(
+            binding("specifier"),
+            ident_expr("all"),
+            vec![
+                require_ns(),
+                for_of(
+                    binding(NAME),
+                    call(
+                        member(ident_expr(OBJECT), "keys"),
+                        vec![ident_expr("ns")],
+                    ),
+                    vec![star],
+                ),
+            ],
+        );
+
+        arrow(vec![binding(IMPORTS), binding("all")], vec![imports, all])
+    }
+
     /// Build up the functor function parameters.
     fn params(&self) -> Vec<Pat> {
         let mut props = IndexMap::new();
@@ -622,7 +1465,7 @@ impl<'a> Generator<'a> {
                         value: Box::new(Pat::Ident(BindingIdent {
                             id: Ident {
                                 span: DUMMY_SP,
-                                sym: prefix_hidden(target),
+                                sym: self.prefix.hidden(target),
                                 optional: false,
                             },
                             type_ann: None,
@@ -680,7 +1523,9 @@ impl<'a> Generator<'a> {
 
         let mut visitor = Visitor {
             meta: self.meta,
+            prefix: &self.prefix,
             body: &mut block.stmts,
+            once_exports: Default::default(),
         };
         self.meta.module.visit_children_with(&mut visitor);
 
@@ -689,14 +1534,14 @@ impl<'a> Generator<'a> {
 
     fn hoist_exported_funcs(&self, stmts: &mut Vec<Stmt>) {
         for name in self.meta.hoisted_funcs.iter() {
-            let target = prefix_const(name);
+            let target = self.prefix.constant(name);
 
             // Use original `name` property for the function
             let define = define_property(target.as_ref(), NAME, name);
             stmts.push(define);
 
             // Set up the live export
-            let prop_target = prefix_hidden(LIVE);
+            let prop_target = self.prefix.hidden(LIVE);
             let call = call_stmt(prop_target, name, Some(target));
             stmts.push(call);
         }
@@ -705,7 +1550,7 @@ impl<'a> Generator<'a> {
     fn hoist_exported_refs(&self, stmts: &mut Vec<Stmt>) {
         for name in self.meta.hoisted_refs.iter() {
             // Set up the live export
-            let prop_target = prefix_hidden(LIVE);
+            let prop_target = self.prefix.hidden(LIVE);
             let call = call_stmt(prop_target, name, None);
             stmts.push(call);
         }
@@ -718,7 +1563,7 @@ impl<'a> Generator<'a> {
                 span: DUMMY_SP,
                 callee: ExprOrSuper::Expr(Box::new(Expr::Ident(Ident {
                     span: DUMMY_SP,
-                    sym: prefix_hidden(IMPORTS),
+                    sym: self.prefix.hidden(IMPORTS),
                     optional: false,
                 }))),
                 args: vec![self.imports_arg_map(), self.imports_arg_all()],
@@ -755,7 +1600,12 @@ impl<'a> Generator<'a> {
                 elems: {
                     let mut out = Vec::with_capacity(self.meta.imports.len());
                     let computed_aliases = self.meta.aliases();
-                    for (key, props) in self.meta.imports.iter() {
+                    let mut imports: Vec<_> =
+                        self.meta.imports.iter().collect();
+                    if self.sort_imports {
+                        imports.sort_by(|(a, _), (b, _)| a.cmp(b));
+                    }
+                    for (key, props) in imports {
                         let key: &str = &key[..];
                         let aliases = computed_aliases.get(key).unwrap();
                         let groups = self.group_duplicates(props, aliases);
@@ -867,28 +1717,13 @@ impl<'a> Generator<'a> {
                                                             for (prop, alias) in list {
                                                                 let name = prop.name;
 
-                                                                let live = self
-                                                                    .meta
-                                                                    .live_export_map
-                                                                    .contains_key(key)
-                                                                    || {
-                                                                        // NOTE: This is a bit of a hack :(
-                                                                        // NOTE: becuase imports doesn't contain the local name
-                                                                        // NOTE: so `export {gray as grey} from './gray.js'` only
-                                                                        // NOTE: gives us `grey` and `grey` right now.
-                                                                        let live_alias = self
-                                                                            .meta
-                                                                            .live_export_map
-                                                                            .iter()
-                                                                            .find(|(_k, v)| {
-                                                                                if v.0 == key {
-                                                                                    return true;
-                                                                                }
-                                                                                false
-                                                                            });
-
-                                                                        live_alias.is_some()
-                                                                    };
+                                                                // Re-exported names are always bound
+                                                                // using the live exports
+                                                                let live = prop.reexport
+                                                                    || self
+                                                                        .meta
+                                                                        .live_export_map
+                                                                        .contains_key(key);
 
                                                                 items.push(Some(
                                                                     self.imports_prop_func(name, alias, live)
@@ -971,7 +1806,7 @@ impl<'a> Generator<'a> {
                     span: DUMMY_SP,
                     obj: ExprOrSuper::Expr(Box::new(Expr::Ident(Ident {
                         span: DUMMY_SP,
-                        sym: prefix_hidden(LIVE),
+                        sym: self.prefix.hidden(LIVE),
                         optional: false,
                     }))),
                     prop: Box::new(Expr::Lit(Lit::Str(Str {
@@ -986,7 +1821,7 @@ impl<'a> Generator<'a> {
                 })),
             }
         } else {
-            let arg = prefix_hidden("a");
+            let arg = self.prefix.hidden("a");
             ExprOrSpread {
                 spread: None,
                 expr: Box::new(Expr::Arrow(ArrowExpr {
@@ -1062,3 +1897,282 @@ impl<'a> Generator<'a> {
         }
     }
 }
+
+/// Determine if a module uses top-level await, eg: `await load();`
+/// or `for await (const chunk of stream) {}`.
+fn has_top_level_await(module: &Module) -> bool {
+    let mut visitor = TopLevelAwait { found: false };
+    module.visit_children_with(&mut visitor);
+    visitor.found
+}
+
+/// Find await expressions outside of functions.
+struct TopLevelAwait {
+    found: bool,
+}
+
+impl Visit for TopLevelAwait {
+    // Await in a function body belongs to the function
+    fn visit_function(&mut self, _: &Function, _: &dyn Node) {}
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr, _: &dyn Node) {}
+
+    fn visit_await_expr(&mut self, _: &AwaitExpr, _: &dyn Node) {
+        self.found = true;
+    }
+
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt, _: &dyn Node) {
+        if n.await_token.is_some() {
+            self.found = true;
+        }
+        n.visit_children_with(self);
+    }
+}
+
+/// Collect the property names accessed on a hidden variable,
+/// eg: `$h‍_live.foo` or `$h‍_live["foo"]`.
+fn hidden_props(body: &BlockStmt, target: JsWord) -> IndexSet<String> {
+    let mut collector = HiddenProps {
+        target,
+        names: Default::default(),
+    };
+    body.visit_children_with(&mut collector);
+    collector.names
+}
+
+struct HiddenProps {
+    target: JsWord,
+    names: IndexSet<String>,
+}
+
+impl Visit for HiddenProps {
+    fn visit_member_expr(&mut self, n: &MemberExpr, _: &dyn Node) {
+        if let ExprOrSuper::Expr(obj) = &n.obj {
+            if let Expr::Ident(ident) = &**obj {
+                if ident.sym == self.target {
+                    match (&*n.prop, n.computed) {
+                        (Expr::Ident(prop), false) => {
+                            self.names.insert(prop.sym.to_string());
+                        }
+                        (Expr::Lit(Lit::Str(prop)), true) => {
+                            self.names.insert(prop.value.to_string());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+}
+
+fn ident_expr<S: Into<JsWord>>(sym: S) -> Box<Expr> {
+    Box::new(Expr::Ident(Ident {
+        span: DUMMY_SP,
+        sym: sym.into(),
+        optional: false,
+    }))
+}
+
+fn binding<S: Into<JsWord>>(sym: S) -> Pat {
+    Pat::Ident(BindingIdent {
+        id: Ident {
+            span: DUMMY_SP,
+            sym: sym.into(),
+            optional: false,
+        },
+        type_ann: None,
+    })
+}
+
+fn array_pat(names: &[&str]) -> Pat {
+    Pat::Array(ArrayPat {
+        span: DUMMY_SP,
+        elems: names.iter().map(|name| Some(binding(*name))).collect(),
+        optional: false,
+        type_ann: None,
+    })
+}
+
+fn str_lit(value: &str) -> Box<Expr> {
+    Box::new(Expr::Lit(Lit::Str(Str {
+        span: DUMMY_SP,
+        kind: StrKind::Normal {
+            contains_quote: true,
+        },
+        value: value.into(),
+        has_escape: false,
+    })))
+}
+
+fn bool_lit(value: bool) -> Box<Expr> {
+    Box::new(Expr::Lit(Lit::Bool(Bool {
+        span: DUMMY_SP,
+        value,
+    })))
+}
+
+fn paren(expr: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr,
+    }))
+}
+
+fn not(arg: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Unary(UnaryExpr {
+        span: DUMMY_SP,
+        op: UnaryOp::Bang,
+        arg,
+    }))
+}
+
+fn bin(op: BinaryOp, left: Box<Expr>, right: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op,
+        left,
+        right,
+    }))
+}
+
+fn member(obj: Box<Expr>, prop: &str) -> Box<Expr> {
+    Box::new(Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: ExprOrSuper::Expr(obj),
+        prop: ident_expr(prop),
+        computed: false,
+    }))
+}
+
+fn computed(obj: Box<Expr>, prop: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: ExprOrSuper::Expr(obj),
+        prop,
+        computed: true,
+    }))
+}
+
+fn call(callee: Box<Expr>, args: Vec<Box<Expr>>) -> Box<Expr> {
+    Box::new(Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: ExprOrSuper::Expr(callee),
+        args: args
+            .into_iter()
+            .map(|expr| ExprOrSpread { spread: None, expr })
+            .collect(),
+        type_args: None,
+    }))
+}
+
+fn assign(left: PatOrExpr, right: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Assign(AssignExpr {
+        span: DUMMY_SP,
+        op: AssignOp::Assign,
+        left,
+        right,
+    }))
+}
+
+fn arrow(params: Vec<Pat>, stmts: Vec<Stmt>) -> Box<Expr> {
+    Box::new(Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params,
+        body: BlockStmtOrExpr::BlockStmt(BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        }),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    }))
+}
+
+fn arrow_expr(params: Vec<Pat>, expr: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params,
+        body: BlockStmtOrExpr::Expr(expr),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    }))
+}
+
+fn object(props: Vec<PropOrSpread>) -> Box<Expr> {
+    Box::new(Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props,
+    }))
+}
+
+fn key_value(key: &str, value: Box<Expr>) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: key.into(),
+            optional: false,
+        }),
+        value,
+    })))
+}
+
+fn str_key_value(key: &str, value: Box<Expr>) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Str(Str {
+            span: DUMMY_SP,
+            kind: StrKind::Normal {
+                contains_quote: true,
+            },
+            value: key.into(),
+            has_escape: false,
+        }),
+        value,
+    })))
+}
+
+fn expr_stmt(expr: Box<Expr>) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr,
+    })
+}
+
+fn const_decl(name: Pat, init: Box<Expr>) -> Stmt {
+    Stmt::Decl(Decl::Var(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            definite: false,
+            name,
+            init: Some(init),
+        }],
+    }))
+}
+
+fn for_of(left: Pat, right: Box<Expr>, stmts: Vec<Stmt>) -> Stmt {
+    Stmt::ForOf(ForOfStmt {
+        span: DUMMY_SP,
+        await_token: None,
+        left: VarDeclOrPat::VarDecl(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                definite: false,
+                name: left,
+                init: None,
+            }],
+        }),
+        right,
+        body: Box::new(Stmt::Block(BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        })),
+    })
+}
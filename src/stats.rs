@@ -0,0 +1,137 @@
+//! Collect timings for the phases of a command.
+//!
+//! Durations are accumulated using atomics so a collector can be
+//! shared between the threads that parse modules; when modules are
+//! processed in parallel the duration for a phase is the total for
+//! all threads and may exceed the elapsed time.
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering::SeqCst};
+use std::time::{Duration, Instant};
+
+/// Phase of a command.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Phase {
+    /// Resolve module specifiers to file paths.
+    Resolve,
+    /// Read and parse module sources.
+    Parse,
+    /// Analyze the dependencies and capabilities of modules.
+    Analyze,
+    /// Serialize the output.
+    Serialize,
+}
+
+impl Phase {
+    const ALL: [Phase; 4] = [
+        Phase::Resolve,
+        Phase::Parse,
+        Phase::Analyze,
+        Phase::Serialize,
+    ];
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Resolve => write!(f, "resolve"),
+            Phase::Parse => write!(f, "parse"),
+            Phase::Analyze => write!(f, "analyze"),
+            Phase::Serialize => write!(f, "serialize"),
+        }
+    }
+}
+
+/// Collects the time spent in each phase of a command.
+#[derive(Debug)]
+pub struct Timings {
+    start: Instant,
+    nanos: [AtomicU64; 4],
+    counts: [AtomicU64; 4],
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            nanos: Default::default(),
+            counts: Default::default(),
+        }
+    }
+}
+
+impl Timings {
+    /// Create a collector, the elapsed time is measured from
+    /// when the collector is created.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a duration to a phase.
+    pub fn add(&self, phase: Phase, duration: Duration) {
+        self.nanos[phase.index()].fetch_add(duration.as_nanos() as u64, SeqCst);
+        self.counts[phase.index()].fetch_add(1, SeqCst);
+    }
+
+    /// Call a function and add the time it takes to a phase.
+    pub fn time<T, F: FnOnce() -> T>(&self, phase: Phase, func: F) -> T {
+        let now = Instant::now();
+        let result = func();
+        self.add(phase, now.elapsed());
+        result
+    }
+
+    /// Get the total duration for a phase.
+    pub fn get(&self, phase: Phase) -> Duration {
+        Duration::from_nanos(self.nanos[phase.index()].load(SeqCst))
+    }
+
+    /// Get the number of times a phase was timed.
+    pub fn count(&self, phase: Phase) -> u64 {
+        self.counts[phase.index()].load(SeqCst)
+    }
+
+    /// Get the time elapsed since the collector was created.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Summary table for the phases.
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<10} {:>8} {:>14}", "phase", "count", "time")?;
+        for phase in Phase::ALL.iter() {
+            writeln!(
+                f,
+                "{:<10} {:>8} {:>14}",
+                phase.to_string(),
+                self.count(*phase),
+                format!("{:.3?}", self.get(*phase))
+            )?;
+        }
+        write!(
+            f,
+            "{:<10} {:>8} {:>14}",
+            "elapsed",
+            "",
+            format!("{:.3?}", self.elapsed())
+        )
+    }
+}
+
+/// Call a function and add the time it takes to a phase
+/// when a collector is given.
+pub fn time<T, F: FnOnce() -> T>(
+    timings: Option<&Timings>,
+    phase: Phase,
+    func: F,
+) -> T {
+    match timings {
+        Some(timings) => timings.time(phase, func),
+        None => func(),
+    }
+}
@@ -1,4 +1,5 @@
 //! Helpers to get a handler, parser, compiler or bundler.
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -10,15 +11,68 @@ use swc::{
 };
 use swc_common::{
     errors::{emitter::ColorConfig, Handler},
-    FileName, SourceFile, SourceMap,
+    FileName, Loc, SourceFile, SourceMap, Span,
 };
 use swc_ecma_ast::Module;
 use swc_ecma_codegen::Node;
-use swc_ecma_parser::{lexer::Lexer, EsConfig, Parser, StringInput, Syntax};
+use swc_ecma_parser::{
+    lexer::Lexer, EsConfig, Parser, StringInput, Syntax, TsConfig,
+};
 
 use swc::IdentCollector;
 use swc_ecma_visit::VisitWith;
 
+use crate::preprocess::preprocessor;
+
+/// Error returned when a module fails to parse.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// Name of the file that failed to parse.
+    pub file_name: String,
+    /// Line number (1-indexed).
+    pub line: usize,
+    /// Column number (0-indexed).
+    pub column: usize,
+    /// Message for the syntax error.
+    pub message: String,
+}
+
+impl ParseError {
+    /// Create a parse error from a parser error.
+    pub(crate) fn new(
+        error: &swc_ecma_parser::error::Error,
+        source_map: &SourceMap,
+    ) -> Self {
+        let loc = span_to_location(source_map, error.span());
+        Self {
+            file_name: loc.file.name.to_string(),
+            line: loc.line,
+            column: loc.col_display,
+            message: error.kind().msg().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to parse module {}:{}:{} {}",
+            self.file_name, self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Get the source location for the start of a span.
+///
+/// The line is 1-indexed and `col_display` is the 0-indexed
+/// column to use when reporting diagnostics.
+pub fn span_to_location(source_map: &SourceMap, span: Span) -> Loc {
+    source_map.lookup_char_pos(span.lo)
+}
+
 pub(crate) fn get_handler(
     source_map: Option<Arc<SourceMap>>,
 ) -> (Arc<SourceMap>, Handler) {
@@ -33,46 +87,95 @@ pub(crate) fn get_handler(
     (sm, handler)
 }
 
+/// Get the parser syntax for a file name.
+///
+/// Files with a `.tsx` extension are parsed as typescript with JSX,
+/// all other files are parsed as ECMAScript with JSX enabled.
+pub fn get_syntax(file_name: &FileName) -> Syntax {
+    let extension = match file_name {
+        FileName::Real(path) => path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned()),
+        FileName::Custom(name) => Path::new(name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned()),
+        _ => None,
+    };
+
+    match extension.as_deref() {
+        Some("tsx") => Syntax::Typescript(TsConfig {
+            tsx: true,
+            dynamic_import: true,
+            ..Default::default()
+        }),
+        _ => Syntax::Es(EsConfig {
+            jsx: true,
+            dynamic_import: true,
+            import_meta: true,
+            top_level_await: true,
+            ..Default::default()
+        }),
+    }
+}
+
 pub(crate) fn get_parser<'a>(
     fm: &'a SourceFile,
 ) -> Parser<Lexer<'a, StringInput<'a>>> {
-    let es_config = EsConfig {
-        jsx: true,
-        dynamic_import: true,
-        ..Default::default()
-    };
+    get_parser_with_syntax(fm, get_syntax(&fm.name))
+}
 
-    let lexer = Lexer::new(
-        Syntax::Es(es_config),
-        JscTarget::Es2020,
-        StringInput::from(fm),
-        None,
-    );
+pub(crate) fn get_parser_with_syntax<'a>(
+    fm: &'a SourceFile,
+    syntax: Syntax,
+) -> Parser<Lexer<'a, StringInput<'a>>> {
+    let lexer =
+        Lexer::new(syntax, JscTarget::Es2020, StringInput::from(fm), None);
     Parser::new_from(lexer)
 }
 
+/// Load a source file into the source map.
+///
+/// When a pre-processor is registered for the file extension the
+/// extracted script is loaded and the syntax for the script is
+/// returned.
+pub(crate) fn load_source_file(
+    source_map: &SourceMap,
+    file: &Path,
+) -> Result<(Arc<SourceFile>, Syntax)> {
+    if let Some(preprocessor) = preprocessor(file) {
+        let content = std::fs::read_to_string(file)?;
+        let (script, syntax) = preprocessor.extract(file, &content)?;
+        let fm = source_map
+            .new_source_file(FileName::Real(file.to_path_buf()), script);
+        Ok((fm, syntax))
+    } else {
+        let fm = source_map.load_file(file)?;
+        let syntax = get_syntax(&fm.name);
+        Ok((fm, syntax))
+    }
+}
+
 /// Parse a module from a file.
 pub fn load_file<P: AsRef<Path>>(
     file: P,
     source_map: Option<Arc<SourceMap>>,
 ) -> Result<(FileName, Arc<SourceMap>, Module)> {
     let (sm, handler) = get_handler(source_map);
-    let fm = sm.load_file(file.as_ref())?;
+    let (fm, syntax) = load_source_file(&sm, file.as_ref())?;
     let file_name = fm.name.clone();
 
-    let mut parser = get_parser(&*fm);
+    let mut parser = get_parser_with_syntax(&*fm, syntax);
     for e in parser.take_errors() {
         e.into_diagnostic(&handler).emit();
     }
 
-    Ok((
-        file_name,
-        sm,
-        parser
-            .parse_module()
-            .map_err(|e| e.into_diagnostic(&handler).emit())
-            .expect("Failed to parse module"),
-    ))
+    let module = parser.parse_module().map_err(|e| {
+        let error = ParseError::new(&e, &sm);
+        e.into_diagnostic(&handler).emit();
+        error
+    })?;
+
+    Ok((file_name, sm, module))
 }
 
 /// Parse a module from a string.
@@ -94,14 +197,13 @@ pub fn load_code<S: AsRef<str>>(
         e.into_diagnostic(&handler).emit();
     }
 
-    Ok((
-        file_name,
-        sm,
-        parser
-            .parse_module()
-            .map_err(|e| e.into_diagnostic(&handler).emit())
-            .expect("Failed to parse module"),
-    ))
+    let module = parser.parse_module().map_err(|e| {
+        let error = ParseError::new(&e, &sm);
+        e.into_diagnostic(&handler).emit();
+        error
+    })?;
+
+    Ok((file_name, sm, module))
 }
 
 // NOTE: The signature for Compiler.print() changes a lot
@@ -115,6 +217,28 @@ pub fn print<T>(
     output_path: Option<PathBuf>,
     source_maps_config: SourceMapsConfig,
 ) -> Result<TransformOutput>
+where
+    T: Node + VisitWith<IdentCollector>,
+{
+    print_minify(
+        node,
+        source_map,
+        source_file_name,
+        output_path,
+        source_maps_config,
+        false,
+    )
+}
+
+/// Print a node optionally using minified formatting.
+pub fn print_minify<T>(
+    node: &T,
+    source_map: Arc<SourceMap>,
+    source_file_name: Option<&str>,
+    output_path: Option<PathBuf>,
+    source_maps_config: SourceMapsConfig,
+    minify: bool,
+) -> Result<TransformOutput>
 where
     T: Node + VisitWith<IdentCollector>,
 {
@@ -140,7 +264,7 @@ where
         source_maps_config,
         &[],
         None,
-        false,
+        minify,
         None,
     )
 }
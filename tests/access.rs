@@ -0,0 +1,38 @@
+use basalt::access::{Access, AccessKind};
+
+#[test]
+fn access_none_all() {
+    assert!(!Access::none().any());
+    assert_eq!("---", Access::none().to_rwx_string());
+    assert_eq!("rwx", Access::all().to_rwx_string());
+}
+
+#[test]
+fn access_with_contains() {
+    let access = Access::with(AccessKind::Write);
+    assert!(access.contains(AccessKind::Write));
+    assert!(!access.contains(AccessKind::Read));
+    assert!(!access.contains(AccessKind::Execute));
+}
+
+#[test]
+fn access_merge_idempotent() {
+    let mut access = Access::with(AccessKind::Read);
+    let other = Access::with(AccessKind::Execute);
+    access.merge(&other);
+    let merged = access;
+    access.merge(&other);
+    assert_eq!(merged, access);
+    let copy = access;
+    access.merge(&copy);
+    assert_eq!(merged, access);
+}
+
+#[test]
+fn access_rwx_display() {
+    let mut access = Access::with(AccessKind::Read);
+    access.merge(&Access::with(AccessKind::Execute));
+    assert_eq!("r-x", access.to_rwx_string());
+    assert_eq!("r-x", access.to_string());
+    assert_eq!("-w-", Access::with(AccessKind::Write).to_string());
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use basalt::{
+    policy::analysis::{globals_scope::GlobalAnalysis, join_words},
+    swc_utils::load_file,
+};
+
+use swc_ecma_visit::VisitWith;
+
+use testing::read_to_string;
+
+fn analyze(dir: &str) -> Result<(String, String)> {
+    analyze_with_extra_builtins(dir, &[])
+}
+
+fn analyze_with_extra_builtins(
+    dir: &str,
+    extra_builtins: &[&str],
+) -> Result<(String, String)> {
+    let base = PathBuf::from(dir);
+    let input = base.join("input.js");
+    let expected = read_to_string(&base.join("output.json"))?;
+    let extra_builtins: HashSet<String> =
+        extra_builtins.iter().map(|s| s.to_string()).collect();
+    let mut analyzer =
+        GlobalAnalysis::with_extra_builtins(Default::default(), extra_builtins);
+    let (_, _, module) = load_file(&input, None)?;
+    module.visit_children_with(&mut analyzer);
+    let builtins = analyzer.compute_builtins();
+    let builtins: Vec<String> = builtins
+        .iter()
+        .map(|words| join_words(words).as_ref().to_string())
+        .collect();
+    let result = serde_json::to_string_pretty(&builtins)?;
+    Ok((expected.trim_end().to_owned(), result))
+}
+
+const DYNAMIC_IMPORTS: &[&str] = &[
+    "tests/builtins/dynamic-import/import",
+    "tests/builtins/dynamic-import/await",
+    "tests/builtins/dynamic-import/then",
+    "tests/builtins/dynamic-import/package",
+];
+
+#[test]
+fn builtins_dynamic_imports() -> Result<()> {
+    for dir in DYNAMIC_IMPORTS {
+        println!("Run dynamic import spec {:#?}", dir);
+        let (expected, result) = analyze(dir)?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+    Ok(())
+}
+
+const MEMBER_ASSIGNS: &[&str] = &[
+    "tests/builtins/member-assign/prototype",
+    "tests/builtins/member-assign/property",
+    "tests/builtins/member-assign/unused",
+];
+
+#[test]
+fn builtins_member_assigns() -> Result<()> {
+    for dir in MEMBER_ASSIGNS {
+        println!("Run member assign spec {:#?}", dir);
+        let (expected, result) = analyze(dir)?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+    Ok(())
+}
+
+const EXTRA_BUILTINS: &[&str] = &[
+    "tests/builtins/extra/import",
+    "tests/builtins/extra/require",
+];
+
+#[test]
+fn builtins_extra() -> Result<()> {
+    for dir in EXTRA_BUILTINS {
+        println!("Run extra builtins spec {:#?}", dir);
+        let (expected, result) =
+            analyze_with_extra_builtins(dir, &["electron", "host-api"])?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+
+    // Without the extra builtins the package import is ignored
+    let (_, result) = analyze("tests/builtins/extra/import")?;
+    assert_eq!("[]", result);
+    Ok(())
+}
+
+const FUNCTION_METHODS: &[&str] = &[
+    "tests/builtins/function-methods/call",
+    "tests/builtins/function-methods/reference",
+];
+
+#[test]
+fn builtins_function_methods() -> Result<()> {
+    for dir in FUNCTION_METHODS {
+        println!("Run function method spec {:#?}", dir);
+        let (expected, result) = analyze(dir)?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+    Ok(())
+}
+
+const SCOPES: &[&str] = &[
+    "tests/builtins/scope/param-shadow",
+    "tests/builtins/scope/local-shadow",
+    "tests/builtins/scope/sibling",
+    "tests/builtins/scope/inner",
+    "tests/builtins/scope/assign",
+];
+
+#[test]
+fn builtins_scopes() -> Result<()> {
+    for dir in SCOPES {
+        println!("Run scope spec {:#?}", dir);
+        let (expected, result) = analyze(dir)?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+    Ok(())
+}
+
+const CJS_REEXPORTS: &[&str] = &[
+    "tests/builtins/cjs-reexport/exports",
+    "tests/builtins/cjs-reexport/module-exports",
+    "tests/builtins/cjs-reexport/module-exports-member",
+];
+
+#[test]
+fn builtins_cjs_reexports() -> Result<()> {
+    for dir in CJS_REEXPORTS {
+        println!("Run cjs re-export spec {:#?}", dir);
+        let (expected, result) = analyze(dir)?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+    Ok(())
+}
@@ -0,0 +1 @@
+Object.assign(exports, require('util'));
@@ -0,0 +1 @@
+Object.assign(module.exports, require('fs').promises);
@@ -0,0 +1,3 @@
+async function read() {
+    const fs = await import('fs');
+}
@@ -0,0 +1 @@
+import('path').then((path) => {});
@@ -0,0 +1,4 @@
+const fs = require('fs');
+const host = require('host-api');
+fs.readSync('foo.txt');
+host.open();
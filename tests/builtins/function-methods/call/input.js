@@ -0,0 +1,2 @@
+import fs from 'fs';
+const readFile = fs.readFile.bind(null);
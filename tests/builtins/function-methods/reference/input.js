@@ -0,0 +1,2 @@
+import fs from 'fs';
+const call = fs.readFile.call;
@@ -0,0 +1,3 @@
+const Foo = {};
+Foo.join = require('path').join;
+Foo.join('a', 'b');
@@ -0,0 +1,3 @@
+function Foo() {}
+Foo.prototype.util = require('util');
+Foo.prototype.util.inspect({});
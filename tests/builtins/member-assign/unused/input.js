@@ -0,0 +1,2 @@
+function Foo() {}
+Foo.prototype.util = require('util');
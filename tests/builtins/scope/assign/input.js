@@ -0,0 +1,5 @@
+var fs;
+try {
+    fs = require('fs');
+} catch (e) {}
+fs.readFileSync('foo.txt');
@@ -0,0 +1,4 @@
+const fs = require('fs');
+function read() {
+    return () => fs.readFileSync('foo.txt');
+}
@@ -0,0 +1,6 @@
+const fs = require('fs');
+function write() {
+    const fs = {};
+    fs.writeFileSync('foo.txt', '');
+}
+fs.readFileSync('foo.txt');
@@ -0,0 +1,5 @@
+const fs = require('fs');
+function write(fs) {
+    fs.writeFileSync('foo.txt', '');
+}
+fs.readFileSync('foo.txt');
@@ -0,0 +1,8 @@
+function read() {
+    const fs = require('fs');
+    return fs.readFileSync('foo.txt');
+}
+function write() {
+    const fs = {};
+    fs.writeFileSync('foo.txt', '');
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use basalt::{bundle, bundler::module_id, BundleArgs};
+
+#[test]
+fn bundle_dependency_order() -> Result<()> {
+    let output = std::env::temp_dir().join("basalt-bundle-chain.js");
+    bundle(BundleArgs {
+        modules: vec![PathBuf::from("tests/fixtures/bundle-chain/main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output.clone()),
+        ..Default::default()
+    })?;
+    let code = std::fs::read_to_string(&output)?;
+    let b = code.find("chain-b").unwrap();
+    let a = code.find("chain-a").unwrap();
+    let main = code.find("chain-main").unwrap();
+    assert!(b < a);
+    assert!(a < main);
+    Ok(())
+}
+
+#[test]
+fn bundle_cycle_error() -> Result<()> {
+    let output = std::env::temp_dir().join("basalt-bundle-cycle.js");
+    let result = bundle(BundleArgs {
+        modules: vec![PathBuf::from("tests/fixtures/bundle-cycle-esm/main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output),
+        ..Default::default()
+    });
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("circular dependency"));
+    assert!(err.contains("a.js"));
+    assert!(err.contains("b.js"));
+    Ok(())
+}
+
+#[test]
+fn bundle_commonjs_cycle() -> Result<()> {
+    // The cycle between foo.js and bar.js includes a CommonJS module
+    let output = std::env::temp_dir().join("basalt-bundle-cjs-cycle.js");
+    bundle(BundleArgs {
+        modules: vec![PathBuf::from("tests/fixtures/basic-tree/main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output.clone()),
+        ..Default::default()
+    })?;
+    let code = std::fs::read_to_string(&output)?;
+    assert!(code.contains("qux.js"));
+    Ok(())
+}
+
+#[test]
+fn bundle_module_id_relative_to_root() -> Result<()> {
+    assert_eq!(
+        module_id("/home/user/app/src/main.js", "/home/user/app", None),
+        module_id("/srv/build/app/src/main.js", "/srv/build/app", None),
+    );
+    assert_ne!(
+        module_id("/app/main.js", "/app", None),
+        module_id("/app/main.js", "/app", Some(b"module.exports = 1;")),
+    );
+
+    let root = PathBuf::from("tests/fixtures/bundle-chain");
+    let output = std::env::temp_dir().join("basalt-bundle-ids.js");
+    bundle(BundleArgs {
+        modules: vec![root.join("main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output.clone()),
+        root: Some(root.clone()),
+        ..Default::default()
+    })?;
+    let code = std::fs::read_to_string(&output)?;
+    assert!(code.contains(&module_id("main.js", "", None)));
+    assert!(code.contains(&module_id("a.js", "", None)));
+    assert!(code.contains(&module_id("b.js", "", None)));
+    Ok(())
+}
+
+#[test]
+fn bundle_module_graph() -> Result<()> {
+    let root = PathBuf::from("tests/fixtures/bundle-chain");
+    let output = std::env::temp_dir().join("basalt-bundle-graph.js");
+    let graph = std::env::temp_dir().join("basalt-bundle-graph.json");
+    bundle(BundleArgs {
+        modules: vec![root.join("main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output),
+        root: Some(root.clone()),
+        graph: Some(graph.clone()),
+        ..Default::default()
+    })?;
+    let value: Value = serde_json::from_str(&std::fs::read_to_string(graph)?)?;
+
+    let modules = value["modules"].as_array().unwrap();
+    assert_eq!(3, modules.len());
+    assert!(modules[0]["path"].as_str().unwrap().ends_with("b.js"));
+    assert!(modules[2]["path"].as_str().unwrap().ends_with("main.js"));
+
+    let main_id = modules[2]["id"].as_str().unwrap();
+    assert_eq!(main_id, value["entry"][0].as_str().unwrap());
+
+    let edges = value["edges"].as_array().unwrap();
+    assert_eq!(2, edges.len());
+    assert!(edges
+        .iter()
+        .any(|e| e["from"] == main_id && e["specifier"] == "./a.js"));
+    Ok(())
+}
+
+#[test]
+fn bundle_multiple_entry_points() -> Result<()> {
+    let root = PathBuf::from("tests/fixtures/bundle-multi");
+    let output = std::env::temp_dir().join("basalt-bundle-multi.js");
+    let graph = std::env::temp_dir().join("basalt-bundle-multi.json");
+    bundle(BundleArgs {
+        modules: vec![root.join("main.js"), root.join("worker.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output.clone()),
+        root: Some(root.clone()),
+        graph: Some(graph.clone()),
+        ..Default::default()
+    })?;
+
+    let code = std::fs::read_to_string(&output)?;
+    assert_eq!(1, code.matches("multi-shared").count());
+    assert!(code.contains("multi-main"));
+    assert!(code.contains("multi-worker"));
+
+    let value: Value = serde_json::from_str(&std::fs::read_to_string(graph)?)?;
+    assert_eq!(3, value["modules"].as_array().unwrap().len());
+    let entry = value["entry"].as_array().unwrap();
+    assert_eq!(2, entry.len());
+    assert_eq!(module_id("main.js", "", None), entry[0]);
+    assert_eq!(module_id("worker.js", "", None), entry[1]);
+    Ok(())
+}
+
+#[test]
+fn bundle_json_module() -> Result<()> {
+    let root = PathBuf::from("tests/fixtures/json-import");
+    let output = std::env::temp_dir().join("basalt-bundle-json.js");
+    let graph = std::env::temp_dir().join("basalt-bundle-json.json");
+    bundle(BundleArgs {
+        modules: vec![root.join("main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output.clone()),
+        root: Some(root.clone()),
+        graph: Some(graph.clone()),
+        ..Default::default()
+    })?;
+
+    let code = std::fs::read_to_string(&output)?;
+    assert!(code.contains("json-import-config"));
+
+    let value: Value = serde_json::from_str(&std::fs::read_to_string(graph)?)?;
+    let modules = value["modules"].as_array().unwrap();
+    assert_eq!(2, modules.len());
+    assert!(modules[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("config.json"));
+    Ok(())
+}
+
+#[test]
+fn bundle_side_effect_import() -> Result<()> {
+    let root = PathBuf::from("tests/fixtures/side-effect-import");
+    let output = std::env::temp_dir().join("basalt-bundle-side-effect.js");
+    let graph = std::env::temp_dir().join("basalt-bundle-side-effect.json");
+    bundle(BundleArgs {
+        modules: vec![root.join("main.js")],
+        policy: vec![PathBuf::from(
+            "tests/fixtures/policy/lavamoat-policy.json",
+        )],
+        output: Some(output),
+        root: Some(root.clone()),
+        graph: Some(graph.clone()),
+        ..Default::default()
+    })?;
+
+    let value: Value = serde_json::from_str(&std::fs::read_to_string(graph)?)?;
+    let main_id = module_id("main.js", "", None);
+    let polyfill_id = module_id("polyfill.js", "", None);
+    assert_eq!(Value::from(vec![main_id.clone()]), value["entry"]);
+
+    // The runtime requires the dependencies of the entry point in
+    // import order so the side-effect module executes first
+    let specifiers: Vec<&str> = value["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["from"] == main_id)
+        .map(|e| e["specifier"].as_str().unwrap())
+        .collect();
+    assert_eq!(vec!["./polyfill.js", "./value.js"], specifiers);
+
+    // Dependencies are emitted before the importer
+    let ids: Vec<&str> = value["modules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(3, ids.len());
+    let position = |id: &str| ids.iter().position(|i| *i == id).unwrap();
+    assert!(position(&polyfill_id) < position(&main_id));
+    Ok(())
+}
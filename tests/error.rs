@@ -0,0 +1,63 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use basalt::{module::dependencies::resolve, BasaltError};
+
+#[test]
+fn error_io_not_a_file() {
+    let err = basalt::meta(
+        PathBuf::from("tests/fixtures/missing.js"),
+        Default::default(),
+    )
+    .unwrap_err();
+    match err {
+        BasaltError::Io(err) => assert_eq!(ErrorKind::NotFound, err.kind()),
+        _ => panic!("expected io error, got {:?}", err),
+    }
+}
+
+#[test]
+fn error_parse() {
+    let err = basalt::meta(
+        PathBuf::from("tests/fixtures/parse-error/main.js"),
+        Default::default(),
+    )
+    .unwrap_err();
+    match err {
+        BasaltError::Parse(err) => {
+            assert!(err.file_name.contains("main.js"));
+            assert_eq!(1, err.line);
+        }
+        _ => panic!("expected parse error, got {:?}", err),
+    }
+}
+
+#[test]
+fn error_policy() {
+    let err = basalt::diff(
+        PathBuf::from("tests/policy/merge/policy1.json"),
+        PathBuf::from("tests/policy/merge/policy2.json"),
+    )
+    .unwrap_err();
+    assert!(matches!(err, BasaltError::Policy(_)));
+}
+
+#[test]
+fn error_resolve() {
+    let err = resolve("./missing.js", "tests/fixtures/basic-tree/main.js")
+        .unwrap_err();
+    assert!(matches!(BasaltError::from(err), BasaltError::Resolve(_)));
+}
+
+#[test]
+fn error_usage() {
+    let err = basalt::inspect(
+        Some(String::from("const a = 1;")),
+        Some(PathBuf::from("tests/fixtures/basic-tree/main.js")),
+        false,
+        Box::new(std::io::empty()),
+        &mut Vec::new(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, BasaltError::Usage(_)));
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use swc_common::SourceMap;
+
+use basalt::static_module_record::{
+    self, transform_with_export_alls, StaticModuleRecordMeta,
+};
+
+fn meta(file: PathBuf) -> Result<StaticModuleRecordMeta> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (meta, _) = static_module_record::transform(file.into(), source_map)?;
+    Ok(meta)
+}
+
+fn load(
+    dir: &str,
+) -> Result<(
+    StaticModuleRecordMeta,
+    IndexMap<String, StaticModuleRecordMeta>,
+)> {
+    let base = PathBuf::from(dir);
+    let entry = meta(base.join("input.js"))?;
+    let mut export_alls = IndexMap::new();
+    for spec in entry.export_alls.iter() {
+        export_alls.insert(spec.to_string(), meta(base.join(spec))?);
+    }
+    Ok((entry, export_alls))
+}
+
+#[test]
+fn export_all_local_shadow() -> Result<()> {
+    let (entry, export_alls) = load("tests/transform/export-all-local-shadow")?;
+    let exports = entry.resolve_exports(&export_alls);
+    assert!(exports.ambiguous.is_empty());
+    assert_eq!(2, exports.names.len());
+    assert_eq!(Some(&None), exports.names.get("x"));
+    assert_eq!(
+        Some(&Some(String::from("./star.js"))),
+        exports.names.get("y")
+    );
+    assert!(exports.names.get("default").is_none());
+    Ok(())
+}
+
+#[test]
+fn export_all_conflict() -> Result<()> {
+    let (entry, export_alls) = load("tests/transform/export-all-conflict")?;
+    let exports = entry.resolve_exports(&export_alls);
+    assert_eq!(2, exports.names.len());
+    assert_eq!(Some(&Some(String::from("./a.js"))), exports.names.get("y"));
+    assert_eq!(Some(&Some(String::from("./b.js"))), exports.names.get("z"));
+    assert!(exports.names.get("x").is_none());
+    assert_eq!(
+        Some(&vec![String::from("./a.js"), String::from("./b.js")]),
+        exports.ambiguous.get("x")
+    );
+    Ok(())
+}
+
+#[test]
+fn export_all_local_shadow_transform() -> Result<()> {
+    let dir = "tests/transform/export-all-local-shadow";
+    let (_, export_alls) = load(dir)?;
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (meta, output, exports) = transform_with_export_alls(
+        PathBuf::from(dir).join("input.js").into(),
+        source_map,
+        &export_alls,
+    )?;
+    assert!(exports.ambiguous.is_empty());
+    assert!(meta.export_alls.is_empty());
+    assert_eq!(
+        Some(&vec![String::from("y")]),
+        meta.imports.get("./star.js")
+    );
+    assert_eq!(
+        Some(&(String::from("y"), false)),
+        meta.live_export_map.get("y")
+    );
+    assert!(meta.live_export_map.get("x").is_none());
+    assert!(meta.fixed_export_map.get("x").is_some());
+    assert!(output.code.contains("$h\u{200d}_live[\"y\"]"));
+    assert!(!output.code.contains("$h\u{200d}_live[\"x\"]"));
+    Ok(())
+}
+
+#[test]
+fn export_all_conflict_transform() -> Result<()> {
+    let dir = "tests/transform/export-all-conflict";
+    let (_, export_alls) = load(dir)?;
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (meta, output, exports) = transform_with_export_alls(
+        PathBuf::from(dir).join("input.js").into(),
+        source_map,
+        &export_alls,
+    )?;
+    assert_eq!(
+        Some(&vec![String::from("./a.js"), String::from("./b.js")]),
+        exports.ambiguous.get("x")
+    );
+    assert!(meta.export_alls.is_empty());
+    assert_eq!(Some(&vec![String::from("y")]), meta.imports.get("./a.js"));
+    assert_eq!(Some(&vec![String::from("z")]), meta.imports.get("./b.js"));
+    assert!(meta.live_export_map.get("x").is_none());
+    assert!(!output.code.contains("$h\u{200d}_live[\"x\"]"));
+    Ok(())
+}
+
+#[test]
+fn export_all_sorted() -> Result<()> {
+    let entry =
+        meta(PathBuf::from("tests/transform/export-all-order/input.js"))?;
+    assert_eq!(
+        vec![String::from("./a.js"), String::from("./b.js")],
+        entry.export_alls
+    );
+    Ok(())
+}
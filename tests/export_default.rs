@@ -88,3 +88,19 @@ fn export_default_this() -> Result<()> {
     assert_eq!(expected, result.code);
     Ok(())
 }
+
+#[test]
+fn export_default_live() -> Result<()> {
+    let expected =
+        read_to_string("tests/transform/export-default-live/output.js")?;
+    let (meta, result) =
+        transform("tests/transform/export-default-live/input.js")?;
+    //print!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert_eq!(
+        Some(&(String::from("count"), true)),
+        meta.live_export_map.get("default")
+    );
+    assert!(meta.fixed_export_map.get("default").is_none());
+    Ok(())
+}
@@ -4,7 +4,9 @@ use std::sync::Arc;
 use swc::TransformOutput;
 use swc_common::SourceMap;
 
-use basalt::static_module_record::{self, StaticModuleRecordMeta};
+use basalt::static_module_record::{
+    self, StaticModuleRecordMeta, TransformSource,
+};
 
 use testing::read_to_string;
 
@@ -124,3 +126,85 @@ fn export_name_13() -> Result<()> {
     assert_eq!(expected, result.code);
     Ok(())
 }
+
+#[test]
+fn export_name_alias() -> Result<()> {
+    let expected =
+        read_to_string("tests/transform/export-name-alias/output.js")?;
+    let (meta, result) =
+        transform("tests/transform/export-name-alias/input.js")?;
+    //print!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert_eq!(
+        Some(&vec![String::from("internalName")]),
+        meta.fixed_export_map.get("publicName")
+    );
+    Ok(())
+}
+
+#[test]
+fn export_name_14() -> Result<()> {
+    let expected = read_to_string("tests/transform/export-name-14/output.js")?;
+    let (meta, result) = transform("tests/transform/export-name-14/input.js")?;
+    //print!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert_eq!(
+        vec!["a", "bee", "c"],
+        meta.fixed_export_map.keys().collect::<Vec<_>>()
+    );
+    assert!(meta.live_export_map.is_empty());
+    Ok(())
+}
+
+#[test]
+fn export_name_specifier_live() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let source = TransformSource::Str {
+        content: String::from(
+            "let a = 1, b = 2;\nexport { a, b as bee };\nb = 3;",
+        ),
+        file_name: String::from("export-name-specifier-live.js"),
+    };
+    let (meta, result) = static_module_record::transform(source, source_map)?;
+    assert_eq!(
+        Some(&vec![String::from("a")]),
+        meta.fixed_export_map.get("a")
+    );
+    assert!(meta.fixed_export_map.get("bee").is_none());
+    assert_eq!(
+        Some(&(String::from("b"), true)),
+        meta.live_export_map.get("bee")
+    );
+    assert!(result.code.contains("let a = 1;"));
+    assert!(result.code.contains("let $c\u{200d}_b = 2;"));
+    assert!(result.code.contains("$h\u{200d}_live.b($c\u{200d}_b);"));
+    assert!(result.code.contains("$h\u{200d}_once.a(a);"));
+    assert!(!result.code.contains("_once.bee"));
+    Ok(())
+}
+
+#[test]
+fn export_name_destructuring() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let source = TransformSource::Str {
+        content: String::from(
+            "export const { a, b: renamed, c = 3, d: { e }, ...f } = obj;
+export let [x, , y = 2, [z], ...rest] = list;",
+        ),
+        file_name: String::from("export-name-destructuring.js"),
+    };
+    let (meta, result) = static_module_record::transform(source, source_map)?;
+    let names = ["a", "renamed", "c", "e", "f", "x", "y", "z", "rest"];
+    assert_eq!(
+        names.to_vec(),
+        meta.fixed_export_map.keys().collect::<Vec<_>>()
+    );
+    for name in names.iter() {
+        let call = format!("$h\u{200d}_once.{}({});", name, name);
+        assert_eq!(1, result.code.matches(&call).count());
+    }
+    // Property names in a pattern are not declared
+    assert!(!result.code.contains("_once.b("));
+    assert!(!result.code.contains("_once.d("));
+    Ok(())
+}
@@ -0,0 +1,3 @@
+import { read } from './util.js';
+
+document.title = read(location.href);
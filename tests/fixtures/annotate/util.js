@@ -0,0 +1,5 @@
+import { readFileSync } from 'fs';
+
+export function read(file) {
+  return readFileSync(file);
+}
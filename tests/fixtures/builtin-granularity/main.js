@@ -0,0 +1 @@
+import 'granularity-dep';
@@ -0,0 +1,2 @@
+const b = require('./b.js');
+module.exports = [b, 'chain-a'];
@@ -0,0 +1,3 @@
+import { b } from './b.js';
+export const a = 'cycle-a';
+export const fromB = () => b;
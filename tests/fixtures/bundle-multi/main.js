@@ -0,0 +1,2 @@
+const shared = require('./shared.js');
+console.log(shared, 'multi-main');
@@ -0,0 +1,4 @@
+import esm from './esm';
+import lib from './lib';
+const common = require('./common');
+const util = require('./util.cjs');
@@ -0,0 +1,2 @@
+import config from './config.json';
+console.log(config.name);
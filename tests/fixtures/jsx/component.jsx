@@ -0,0 +1,15 @@
+import React from 'react';
+
+export function Button({ label }) {
+  return (
+    <button className="primary" onClick={() => console.log(label)}>
+      {label}
+    </button>
+  );
+}
+
+export const List = ({ items }) => (
+  <>
+    {items.map((item) => <li key={item}>{item}</li>)}
+  </>
+);
@@ -0,0 +1,9 @@
+import React from 'react';
+
+interface Props {
+  label: string;
+}
+
+export function Button({ label }: Props) {
+  return <button className="primary">{label}</button>;
+}
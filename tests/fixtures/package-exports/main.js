@@ -0,0 +1,4 @@
+import pkg from 'exports-pkg';
+import feature from 'exports-pkg/features/a';
+import sugar from 'sugar-pkg';
+import main from 'main-pkg';
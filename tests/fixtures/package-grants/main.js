@@ -0,0 +1 @@
+import 'alias-dependent';
@@ -0,0 +1,2 @@
+import 'alias-name';
+import 'alias-name/lib/deep.js';
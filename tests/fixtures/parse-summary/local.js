@@ -0,0 +1,2 @@
+import { basename } from 'path';
+export default basename;
@@ -0,0 +1,5 @@
+import fs from 'fs';
+import { join } from 'path';
+import util from 'summary-dep/lib/util.js';
+import './local.js';
+const os = require('os');
@@ -0,0 +1,3 @@
+import dep from 'cache-dep';
+
+dep(document.title);
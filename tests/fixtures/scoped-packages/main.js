@@ -0,0 +1,3 @@
+import '@scope/name';
+import '@scope/name/lib/deep.js';
+import 'unscoped/lib/deep.js';
@@ -0,0 +1,3 @@
+import './polyfill.js';
+import { value } from './value.js';
+console.log(value, 'side-effect-main');
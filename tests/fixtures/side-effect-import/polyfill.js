@@ -0,0 +1 @@
+globalThis.polyfilled = 'side-effect-polyfill';
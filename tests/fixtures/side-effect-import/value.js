@@ -0,0 +1 @@
+export const value = 'side-effect-value';
@@ -0,0 +1,4 @@
+import { join } from 'path';
+import { value } from './lib.js';
+
+export default join(value, 'main.js');
@@ -0,0 +1,3 @@
+import './missing.js';
+import { value } from './ok.js';
+export default value;
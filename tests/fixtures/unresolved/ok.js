@@ -0,0 +1,2 @@
+import pkg from 'missing-package';
+export const value = pkg;
@@ -1,9 +1,17 @@
 use anyhow::Result;
 use std::path::PathBuf;
 
-use basalt::policy::analysis::globals_scope::GlobalAnalysis;
-use basalt::swc_utils::load_file;
+use basalt::policy::analysis::{
+    flatten,
+    globals_scope::{
+        analyze_globals, analyze_globals_with_locations, GlobalAnalysis,
+        GlobalLocation, GlobalOptions,
+    },
+};
+use basalt::swc_utils::{load_code, load_file};
 
+use indexmap::IndexSet;
+use swc_atoms::JsWord;
 use swc_ecma_visit::VisitWith;
 
 use testing::read_to_string;
@@ -77,6 +85,8 @@ const EXPRESSIONS: &[&str] = &[
     "tests/globals/expr/unary",
     "tests/globals/expr/class",
     "tests/globals/expr/class-parameters",
+    "tests/globals/expr/class-static",
+    "tests/globals/expr/class-accessor",
     "tests/globals/expr/array-lit",
     "tests/globals/expr/object-lit",
     "tests/globals/expr/function-default-arguments",
@@ -89,8 +99,12 @@ const EXPRESSIONS: &[&str] = &[
     "tests/globals/expr/sequence",
     "tests/globals/expr/binary",
     "tests/globals/expr/optional-chain",
+    "tests/globals/expr/optional-chain-member",
     "tests/globals/expr/member",
+    "tests/globals/expr/member-computed",
     "tests/globals/expr/member-paren-function",
+    "tests/globals/expr/jsx-attribute",
+    "tests/globals/expr/jsx-children",
 ];
 
 #[test]
@@ -133,6 +147,23 @@ fn globals_normalize_global_this() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn globals_normalize_global_this_member() -> Result<()> {
+    let (expected, result) =
+        analyze("tests/globals/normalize/global-this-member")?;
+    //println!("{}", result);
+    assert_eq!(expected.trim_end(), result);
+    Ok(())
+}
+
+#[test]
+fn globals_normalize_global_member() -> Result<()> {
+    let (expected, result) = analyze("tests/globals/normalize/global-member")?;
+    //println!("{}", result);
+    assert_eq!(expected.trim_end(), result);
+    Ok(())
+}
+
 #[test]
 fn globals_hoisted_vars() -> Result<()> {
     let (expected, result) = analyze("tests/globals/hoisted-vars")?;
@@ -140,3 +171,111 @@ fn globals_hoisted_vars() -> Result<()> {
     assert_eq!(expected.trim_end(), result);
     Ok(())
 }
+
+#[test]
+fn globals_analyze_api() -> Result<()> {
+    let (_, _, module) = load_file("tests/globals/expr/member/input.js", None)?;
+    let globals = analyze_globals(&module, Default::default());
+    let expected = read_to_string(&PathBuf::from(
+        "tests/globals/expr/member/output.json",
+    ))?;
+    let expected: IndexSet<JsWord> = serde_json::from_str(&expected)?;
+    assert_eq!(expected, globals);
+    Ok(())
+}
+
+#[test]
+fn globals_keep_member_paths() -> Result<()> {
+    let (_, _, module) = load_file("tests/globals/deep/input.js", None)?;
+    let options = GlobalOptions::default().keep_member_paths(true);
+    let globals = analyze_globals(&module, options);
+    let expected =
+        read_to_string(&PathBuf::from("tests/globals/deep/output.json"))?;
+    let result = serde_json::to_string_pretty(&globals)?;
+    assert_eq!(expected.trim_end(), result);
+
+    let globals = analyze_globals(&module, Default::default());
+    let result: Vec<&str> = globals.iter().map(|w| w.as_ref()).collect();
+    assert_eq!(vec!["Buffer", "process.env"], result);
+    Ok(())
+}
+
+#[test]
+fn globals_ignore_node_global() -> Result<()> {
+    let (_, _, module) =
+        load_file("tests/globals/normalize/global-member/input.js", None)?;
+    let options = GlobalOptions::default().ignore_node_global(false);
+    let globals = analyze_globals(&module, options);
+    let result: Vec<&str> = globals.iter().map(|w| w.as_ref()).collect();
+    assert_eq!(vec!["global.a.b.c", "global.process.env"], result);
+    Ok(())
+}
+
+#[test]
+fn globals_destructuring_property_name() -> Result<()> {
+    let code = "const { location: loc, document: { title } } = window;
+location.reload(loc, title);";
+    let (_, _, module) = load_code(code, None, None)?;
+    let globals = analyze_globals(&module, Default::default());
+    let result: Vec<&str> = globals.iter().map(|w| w.as_ref()).collect();
+    assert_eq!(vec!["window", "location"], result);
+    Ok(())
+}
+
+#[test]
+fn globals_with_locations() -> Result<()> {
+    let code = r#"const a = 1;
+function foo() {
+  return process.env;
+}
+process;
+  alert(a);
+"#;
+    let (_, source_map, module) = load_code(code, None, None)?;
+    let globals = analyze_globals_with_locations(
+        &module,
+        Default::default(),
+        &source_map,
+    );
+    let result: Vec<(&str, GlobalLocation)> =
+        globals.iter().map(|(k, v)| (k.as_ref(), *v)).collect();
+    assert_eq!(
+        vec![
+            ("alert", GlobalLocation { line: 6, column: 2 }),
+            // Collapsed into the parent so uses the first reference
+            ("process", GlobalLocation { line: 3, column: 9 }),
+        ],
+        result
+    );
+    Ok(())
+}
+
+#[test]
+fn globals_flatten_siblings() -> Result<()> {
+    let path = |words: &[&str]| -> Vec<JsWord> {
+        words.iter().map(|w| JsWord::from(*w)).collect()
+    };
+
+    let mut set = IndexSet::new();
+    set.insert(path(&["a", "c"]));
+    set.insert(path(&["a", "b"]));
+    let first = flatten(set);
+
+    let mut set = IndexSet::new();
+    set.insert(path(&["a", "b"]));
+    set.insert(path(&["a", "c"]));
+    let second = flatten(set);
+
+    let expected = vec![path(&["a", "b"]), path(&["a", "c"])];
+    assert_eq!(expected, first.into_iter().collect::<Vec<_>>());
+    assert_eq!(expected, second.into_iter().collect::<Vec<_>>());
+
+    // Parent prefix still collapses deep paths
+    let mut set = IndexSet::new();
+    set.insert(path(&["a", "b"]));
+    set.insert(path(&["a"]));
+    set.insert(path(&["a", "c"]));
+    let result = flatten(set);
+    assert_eq!(vec![path(&["a"])], result.into_iter().collect::<Vec<_>>());
+    Ok(())
+}
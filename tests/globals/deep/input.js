@@ -0,0 +1,4 @@
+Buffer.alloc(16);
+const buf = Buffer;
+process.env.NODE_ENV;
+process.env;
@@ -0,0 +1,13 @@
+class Storage {
+  get value() {
+    return localStorage.getItem('value');
+  }
+
+  set value(value) {
+    sessionStorage.setItem('value', value);
+  }
+
+  static get location() {
+    return location;
+  }
+}
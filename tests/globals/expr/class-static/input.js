@@ -0,0 +1,12 @@
+class Config {
+  static env = process.env;
+  static #storage = localStorage;
+
+  static create() {
+    return fetch;
+  }
+
+  static #load() {
+    const doc = document;
+  }
+}
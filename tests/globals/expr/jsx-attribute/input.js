@@ -0,0 +1,6 @@
+import Button from './button.js';
+function handler() {}
+const props = {};
+const a = <div onClick={handler} title={document.title} {...props} />;
+const b = <Button style={{color: theme.primary}} icon={<Icon />} />;
+const c = <Layout.Header data-url={location.href} />;
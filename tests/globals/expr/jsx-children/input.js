@@ -0,0 +1,12 @@
+const items = [];
+const list = (
+  <ul>
+    {items.map((item) => <li key={item}>{item}</li>)}
+    {process.env.NODE_ENV}
+    <>
+      <span>{window.foo}</span>
+      {/* comment */}
+    </>
+    text
+  </ul>
+);
@@ -0,0 +1,6 @@
+console['log']('foo');
+process["env"].NODE_ENV;
+
+// Dynamic computed access stops at the computed part
+const key = 'platform';
+os[key];
@@ -2,8 +2,9 @@ const doc = this.document;
 console.log('foo');
 fetch().then();
 
-// Computed member property will only evaluate until the computed part
-// and the globalThis should be stripped so this evaluates to `window`
+// Computed string literal member property is treated as dot access
+// and the globalThis should be stripped so this evaluates to
+// `window.addEventListener`
 const addEventListener = globalThis.window['addEventListener'];
 
 // TODO: member expression in computed evaluation!
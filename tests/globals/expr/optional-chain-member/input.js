@@ -0,0 +1,2 @@
+fetch?.call(window);
+const nested = a?.b?.c;
@@ -0,0 +1,2 @@
+global.a.b.c;
+const env = global.process.env;
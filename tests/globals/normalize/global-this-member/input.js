@@ -0,0 +1,3 @@
+globalThis.a.b.c;
+const env = globalThis.process.env;
+globalThis['Buffer'].from('');
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use swc_common::SourceMap;
+
+use basalt::static_module_record::{
+    self,
+    transform::{HiddenPrefix, HIDDEN_PREFIX},
+    TransformSource,
+};
+
+#[test]
+fn hidden_prefix_validate() -> Result<()> {
+    assert!(HiddenPrefix::new("$x_", "$y_").is_ok());
+    assert!(HiddenPrefix::new("1x_", "$y_").is_err());
+    assert!(HiddenPrefix::new("$x-", "$y_").is_err());
+    assert!(HiddenPrefix::new("$x_", "$x_c_").is_err());
+    Ok(())
+}
+
+#[test]
+fn hidden_prefix_avoid_collision() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let content = format!(
+        "const {}live = 1;\nexport let count = {}live;\n",
+        HIDDEN_PREFIX, HIDDEN_PREFIX
+    );
+    let source = TransformSource::Str {
+        content,
+        file_name: String::from("collide.js"),
+    };
+    let (_, result) = static_module_record::transform(source, source_map)?;
+    let extended = format!("{}_live", HIDDEN_PREFIX);
+    assert!(result.code.contains(&extended));
+    Ok(())
+}
@@ -4,7 +4,10 @@ use std::sync::Arc;
 use swc::TransformOutput;
 use swc_common::SourceMap;
 
-use basalt::static_module_record::{self, StaticModuleRecordMeta};
+use basalt::{
+    static_module_record::{self, ImportKind, Parser, StaticModuleRecordMeta},
+    swc_utils::{load_code, ParseError},
+};
 
 use testing::read_to_string;
 
@@ -74,3 +77,175 @@ fn import_duplicate() -> Result<()> {
     assert_eq!(expected, result.code);
     Ok(())
 }
+
+#[test]
+fn import_module_meta() -> Result<()> {
+    let file = "tests/transform/import-multiple-names/input.js";
+    let (expected, _) = transform(file)?;
+    let meta = static_module_record::module_meta(file.into())?;
+    assert_eq!(
+        serde_json::to_string(&expected)?,
+        serde_json::to_string(&meta)?
+    );
+    assert!(!meta.imports.is_empty());
+    Ok(())
+}
+
+#[test]
+fn import_parse_file() -> Result<()> {
+    let file = "tests/transform/import-multiple-names/input.js";
+    let (expected, _) = transform(file)?;
+    let meta = Parser::parse_file(file)?;
+    assert_eq!(
+        serde_json::to_string(&expected)?,
+        serde_json::to_string(&meta)?
+    );
+
+    let content = read_to_string(file)?;
+    let meta = Parser::parse_str(content, file)?;
+    assert_eq!(
+        serde_json::to_string(&expected)?,
+        serde_json::to_string(&meta)?
+    );
+
+    let meta = Parser::parse_str("export const foo = <div />;", "foo.jsx")?;
+    assert_eq!(
+        vec!["foo"],
+        meta.export_names().into_iter().collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn import_source_map() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (_, result) = static_module_record::transform_with_source_map(
+        "tests/transform/import-default/input.js".into(),
+        source_map,
+    )?;
+    let map = result.map.expect("transform to generate a source map");
+    let map: serde_json::Value = serde_json::from_str(&map)?;
+    assert_eq!(Some(3), map.get("version").and_then(|v| v.as_u64()));
+
+    let (_, result) = transform("tests/transform/import-default/input.js")?;
+    assert!(result.map.is_none());
+    Ok(())
+}
+
+#[test]
+fn import_parse_error() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let source = static_module_record::TransformSource::Str {
+        content: String::from("import {foo from 'bar';"),
+        file_name: String::from("bad.js"),
+    };
+    let err = static_module_record::transform(source, source_map).unwrap_err();
+    let err = err.downcast::<ParseError>()?;
+    assert!(err.file_name.contains("bad.js"));
+    assert_eq!(1, err.line);
+    Ok(())
+}
+
+#[test]
+fn import_reader_source() -> Result<()> {
+    let expected =
+        read_to_string("tests/transform/import-wildcard-name/output.js")?;
+    let file =
+        std::fs::File::open("tests/transform/import-wildcard-name/input.js")?;
+    let source = static_module_record::TransformSource::Reader {
+        reader: Box::new(file),
+        file_name: String::from("input.js"),
+    };
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (_, result) = static_module_record::transform(source, source_map)?;
+    assert_eq!(expected, result.code);
+    Ok(())
+}
+
+#[test]
+fn import_transform_program() -> Result<()> {
+    let output = static_module_record::transform_program(
+        "tests/transform/import-wildcard-name/input.js".into(),
+    )?;
+    assert!(!output.program.ends_with(";"));
+    assert!(output.map.is_none());
+    assert_eq!(1, output.meta.imports.len());
+    Ok(())
+}
+
+#[test]
+fn import_transform_program_minify() -> Result<()> {
+    let options = static_module_record::TransformOptions {
+        minify: true,
+        ..Default::default()
+    };
+    let output = static_module_record::transform_program_with_options(
+        "tests/transform/import-wildcard-name/input.js".into(),
+        options,
+    )?;
+    assert!(!output.program.ends_with(";"));
+    assert!(!output.program.contains("\n"));
+    assert_eq!(1, output.meta.imports.len());
+    Ok(())
+}
+
+#[test]
+fn import_transform_sort_imports() -> Result<()> {
+    const SOURCE: &str = concat!(
+        "import b from './b.js';\n",
+        "import a from './a.js';\n",
+        "export default [a, b];",
+    );
+    let source = || static_module_record::TransformSource::Str {
+        content: String::from(SOURCE),
+        file_name: String::from("sort.js"),
+    };
+    let position = |program: &str, spec: &str| program.find(spec).unwrap();
+
+    // Source order by default
+    let output = static_module_record::transform_program(source())?;
+    let program = &output.program;
+    assert!(position(program, "./b.js") < position(program, "./a.js"));
+
+    let options = static_module_record::TransformOptions {
+        sort_imports: true,
+        ..Default::default()
+    };
+    let output = static_module_record::transform_program_with_options(
+        source(),
+        options,
+    )?;
+    let program = &output.program;
+    assert!(position(program, "./a.js") < position(program, "./b.js"));
+
+    // Meta data keeps the source order
+    assert_eq!(
+        vec!["./b.js", "./a.js"],
+        output.meta.imports.keys().collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn import_mixed_default_named() -> Result<()> {
+    let (_, _, module) =
+        load_code("import foo, { bar as baz } from './m.js';", None, None)?;
+    let mut parser = Parser::new();
+    let record = parser.parse(&module)?;
+
+    assert_eq!(1, record.imports.len());
+    let names = record.imports.get("./m.js").unwrap();
+    assert_eq!(2, names.len());
+
+    assert_eq!(&ImportKind::Default, names[0].kind());
+    assert_eq!("foo", names[0].name());
+    assert_eq!("default", names[0].raw_name());
+
+    assert_eq!(&ImportKind::Named, names[1].kind());
+    assert_eq!("bar", names[1].name());
+    assert_eq!(Some("baz"), names[1].alias());
+
+    assert_eq!(vec!["foo", "baz"], record.decls());
+    assert_eq!(&vec!["foo", "baz"], record.aliases().get("./m.js").unwrap());
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::inspect;
+
+#[test]
+fn inspect_json() -> Result<()> {
+    let mut output = Vec::new();
+    inspect(
+        Some(String::from("export const foo = 42;")),
+        None,
+        true,
+        Box::new(std::io::empty()),
+        &mut output,
+    )?;
+    let value: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(Some("Module"), value["type"].as_str());
+    assert_eq!(Some("ExportDeclaration"), value["body"][0]["type"].as_str());
+    Ok(())
+}
+
+#[test]
+fn inspect_stdin() -> Result<()> {
+    let mut output = Vec::new();
+    inspect(
+        None,
+        Some(PathBuf::from("-")),
+        true,
+        Box::new("export const foo = 42;".as_bytes()),
+        &mut output,
+    )?;
+    let value: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(Some("ExportDeclaration"), value["body"][0]["type"].as_str());
+    Ok(())
+}
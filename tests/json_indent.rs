@@ -0,0 +1,30 @@
+use anyhow::Result;
+use serde_json::json;
+
+use basalt::json::{to_string_pretty, Indent};
+
+#[test]
+fn json_indent_parse() -> Result<()> {
+    assert_eq!(Indent::Spaces(4), "4".parse()?);
+    assert_eq!(Indent::Tab, "tab".parse()?);
+    assert_eq!(Indent::Spaces(2), Default::default());
+    assert!("two".parse::<Indent>().is_err());
+    Ok(())
+}
+
+#[test]
+fn json_indent_output() -> Result<()> {
+    let value = json!({"resources": {"foo": {"packages": {"bar": true}}}});
+
+    let expected = serde_json::to_string_pretty(&value)?;
+    assert_eq!(expected, to_string_pretty(&value, Default::default())?);
+
+    let output = to_string_pretty(&value, Indent::Spaces(4))?;
+    assert!(output.contains("\n    \"resources\""));
+    assert!(output.contains("\n            \"packages\""));
+
+    let output = to_string_pretty(&value, Indent::Tab)?;
+    assert!(output.contains("\n\t\"resources\""));
+    assert!(output.contains("\n\t\t\t\"packages\""));
+    Ok(())
+}
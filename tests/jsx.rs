@@ -0,0 +1,42 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use swc_common::SourceMap;
+use swc_ecma_ast::{Decl, ModuleDecl, ModuleItem, Stmt};
+
+use basalt::static_module_record::{self, TransformSource};
+use basalt::swc_utils::load_file;
+
+#[test]
+fn jsx_load_file() -> Result<()> {
+    let (_, _, module) = load_file("tests/fixtures/jsx/component.jsx", None)?;
+    assert_eq!(3, module.body.len());
+    Ok(())
+}
+
+#[test]
+fn jsx_load_file_tsx() -> Result<()> {
+    let (_, _, module) = load_file("tests/fixtures/jsx/component.tsx", None)?;
+    assert_eq!(3, module.body.len());
+    assert!(matches!(
+        &module.body[1],
+        ModuleItem::Stmt(Stmt::Decl(Decl::TsInterface(_)))
+    ));
+    assert!(matches!(
+        &module.body[2],
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn jsx_transform() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (meta, result) = static_module_record::transform(
+        TransformSource::File("tests/fixtures/jsx/component.jsx".into()),
+        source_map,
+    )?;
+    assert!(meta.imports.contains_key("react"));
+    assert!(result.code.contains("<button"));
+    Ok(())
+}
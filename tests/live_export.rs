@@ -4,7 +4,9 @@ use std::sync::Arc;
 
 use swc_common::SourceMap;
 
-use basalt::static_module_record::{transform, TransformSource};
+use basalt::static_module_record::{
+    transform, Parser, StaticModuleRecordMeta, TransformSource,
+};
 
 use testing::read_to_string;
 
@@ -23,3 +25,60 @@ fn live_export_assignment() -> Result<()> {
     assert_eq!(expected, result.code);
     Ok(())
 }
+
+fn meta(code: &str) -> Result<StaticModuleRecordMeta> {
+    Parser::parse_str(code, "live-export.js")
+}
+
+fn is_live(meta: &StaticModuleRecordMeta, name: &str) -> bool {
+    let live = meta.live_export_map.contains_key(name);
+    assert_ne!(live, meta.fixed_export_map.contains_key(name));
+    live
+}
+
+#[test]
+fn live_export_let_without_assignment() -> Result<()> {
+    let meta = meta("export let count = 0;\nexport var total = 0;")?;
+    assert!(!is_live(&meta, "count"));
+    assert!(!is_live(&meta, "total"));
+    Ok(())
+}
+
+#[test]
+fn live_export_assignment_in_function() -> Result<()> {
+    let meta = meta("export let count = 0;\nfunction inc() { count += 1; }")?;
+    assert!(is_live(&meta, "count"));
+    Ok(())
+}
+
+#[test]
+fn live_export_destructuring_assignment() -> Result<()> {
+    let meta = meta(
+        "export let a, b, c, d;
+[a] = [1];
+({ b, nested: { value: c } } = {});
+for (d of [1]) {}",
+    )?;
+    for name in ["a", "b", "c", "d"].iter() {
+        assert!(is_live(&meta, name));
+    }
+    Ok(())
+}
+
+#[test]
+fn live_export_destructuring_key() -> Result<()> {
+    let meta = meta("export let key = 0;\nlet other;\n({ key: other } = {});")?;
+    assert!(!is_live(&meta, "key"));
+    Ok(())
+}
+
+#[test]
+fn live_export_const_assignment() -> Result<()> {
+    // Assigning to a constant throws so the export is fixed
+    let meta = meta("export const count = 0;\ncount = 1;")?;
+    assert!(!is_live(&meta, "count"));
+
+    let meta = meta("const count = 0;\nexport { count };\ncount++;")?;
+    assert!(!is_live(&meta, "count"));
+    Ok(())
+}
@@ -0,0 +1 @@
+module.exports = require('unscoped/lib/deep.js');
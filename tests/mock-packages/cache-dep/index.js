@@ -0,0 +1,6 @@
+import { readFileSync } from 'fs';
+import { home } from './lib/env.js';
+
+export default function load(name) {
+  return readFileSync(home() + name);
+}
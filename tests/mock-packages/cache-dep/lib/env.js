@@ -0,0 +1,7 @@
+export function home() {
+  with (process.env) {
+    return HOME;
+  }
+}
+
+export const timer = setTimeout;
@@ -0,0 +1,8 @@
+import { readFile, writeFile } from 'fs';
+import path from 'path';
+
+export function copy(from, to) {
+  readFile(from, (err, data) => {
+    writeFile(path.join(to, 'copy'), data, () => {});
+  });
+}
@@ -0,0 +1,8 @@
+const fs = require('fs');
+
+function load(name) {
+  return require('./lib/' + name);
+}
+
+const moduleName = process.env.MODULE;
+module.exports = { fs, load, plugin: require(moduleName) };
@@ -0,0 +1,7 @@
+const value = eval('1 + 1');
+const add = new Function('a', 'b', 'return a + b');
+
+setTimeout('console.log(value)', 10);
+setTimeout(() => console.log(add(1, 2)), 10);
+
+module.exports = { value, add };
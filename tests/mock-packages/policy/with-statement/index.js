@@ -0,0 +1,5 @@
+import process from 'process';
+
+with (process) {
+  const foo = env.FOO;
+}
@@ -3,7 +3,11 @@ use anyhow::Result;
 use swc_common::FileName;
 use swc_ecma_loader::{resolve::Resolve, resolvers::node::NodeModulesResolver};
 
-use basalt::module::base::module_base_directory;
+use std::path::PathBuf;
+
+use basalt::module::base::{
+    module_base_directory, module_package_name, module_package_version,
+};
 
 #[test]
 fn module_base_package() -> Result<()> {
@@ -68,3 +72,28 @@ fn module_base_package_nested_file() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn module_base_package_name() -> Result<()> {
+    let base = PathBuf::from("tests/mock-packages/alias-name");
+    assert_eq!(
+        Some(String::from("canonical-name")),
+        module_package_name(&base.join("index.js"))
+    );
+    // Skips the nested `package.json` that does not declare a name
+    assert_eq!(
+        Some(String::from("canonical-name")),
+        module_package_name(&base.join("lib").join("deep.js"))
+    );
+    Ok(())
+}
+
+#[test]
+fn module_base_package_version() -> Result<()> {
+    let base = PathBuf::from("tests/mock-packages/alias-name");
+    assert_eq!(
+        Some(String::from("1.0.0")),
+        module_package_version(&base.join("lib").join("deep.js"))
+    );
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use swc_common::SourceMap;
+use swc_ecma_loader::resolve::Resolve;
+
+use basalt::module::{node::parse_file, resolver::ExportsResolver};
+
+#[test]
+fn module_cache_canonical_path() -> Result<()> {
+    let resolver: Box<dyn Resolve> = Box::new(ExportsResolver::default());
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let first = parse_file(
+        "tests/fixtures/basic-tree/main.js",
+        &resolver,
+        Arc::clone(&source_map),
+    )?;
+    let second = parse_file(
+        "tests/fixtures/basic-tree/../basic-tree/main.js",
+        &resolver,
+        Arc::clone(&source_map),
+    )?;
+    assert!(Arc::ptr_eq(&first, &second));
+    Ok(())
+}
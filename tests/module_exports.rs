@@ -0,0 +1,89 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use swc_common::FileName;
+use swc_ecma_loader::resolve::Resolve;
+
+use basalt::module::resolver::ExportsResolver;
+
+const MAIN: &str = "tests/fixtures/package-exports/main.js";
+
+fn package(path: &str) -> Result<FileName> {
+    Ok(FileName::Real(
+        PathBuf::from("node_modules").join(path).canonicalize()?,
+    ))
+}
+
+fn entry() -> FileName {
+    FileName::Real(PathBuf::from(MAIN))
+}
+
+#[test]
+fn module_exports_conditions() -> Result<()> {
+    let resolver = ExportsResolver::default();
+    assert_eq!(
+        package("exports-pkg/esm/index.mjs")?,
+        resolver.resolve(&entry(), "exports-pkg")?
+    );
+
+    let resolver = ExportsResolver::default()
+        .conditions(vec!["require".into(), "default".into()]);
+    assert_eq!(
+        package("exports-pkg/cjs/index.cjs")?,
+        resolver.resolve(&entry(), "exports-pkg")?
+    );
+    Ok(())
+}
+
+#[test]
+fn module_exports_patterns() -> Result<()> {
+    let resolver = ExportsResolver::default();
+    assert_eq!(
+        package("exports-pkg/esm/features/a.mjs")?,
+        resolver.resolve(&entry(), "exports-pkg/features/a")?
+    );
+    assert_eq!(
+        package("exports-pkg/package.json")?,
+        resolver.resolve(&entry(), "exports-pkg/package.json")?
+    );
+
+    let resolver = ExportsResolver::default().conditions(vec![]);
+    assert!(resolver
+        .resolve(&entry(), "exports-pkg/features/a")
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn module_exports_not_exported() -> Result<()> {
+    let resolver = ExportsResolver::default();
+    let err = resolver
+        .resolve(&entry(), "exports-pkg/internal/index.js")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("./internal/index.js is not exported"));
+    // Longer patterns take precedence, null excludes the subpath
+    assert!(resolver
+        .resolve(&entry(), "exports-pkg/features/private/x")
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn module_exports_fallback() -> Result<()> {
+    let resolver = ExportsResolver::default();
+    assert_eq!(
+        package("sugar-pkg/index.mjs")?,
+        resolver.resolve(&entry(), "sugar-pkg")?
+    );
+    // Packages without exports use the main field
+    if let FileName::Real(path) = resolver.resolve(&entry(), "main-pkg")? {
+        assert_eq!(
+            package("main-pkg/lib.js")?,
+            FileName::Real(path.canonicalize()?)
+        );
+    } else {
+        panic!("expected a file for main-pkg");
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::module::parser::{detect_cycles, parse};
+
+#[test]
+fn module_parser_parallel() -> Result<()> {
+    let summary =
+        parse("tests/fixtures/basic-tree/main.js", Some(2), false, None)?;
+    assert_eq!(4, summary.modules);
+    assert_eq!(5, summary.visited);
+    Ok(())
+}
+
+#[test]
+fn module_parser_detect_cycles() -> Result<()> {
+    let base = PathBuf::from("tests/fixtures/basic-tree");
+    let cycles = detect_cycles(base.join("main.js"))?;
+    assert_eq!(vec![vec![base.join("foo.js"), base.join("bar.js")]], cycles);
+    Ok(())
+}
+
+#[test]
+fn module_parser_detect_self_cycle() -> Result<()> {
+    let file = PathBuf::from("tests/fixtures/self-loop/main.js");
+    let cycles = detect_cycles(&file)?;
+    assert_eq!(vec![vec![file]], cycles);
+    Ok(())
+}
+
+#[test]
+fn module_parser_self_import() -> Result<()> {
+    let summary =
+        parse("tests/fixtures/self-loop/./main.js", None, false, None)?;
+    assert_eq!(1, summary.modules);
+    assert_eq!(1, summary.visited);
+    Ok(())
+}
+
+#[test]
+fn module_parser_json_import() -> Result<()> {
+    let summary =
+        parse("tests/fixtures/json-import/main.js", None, false, None)?;
+    assert_eq!(2, summary.modules);
+    Ok(())
+}
+
+#[test]
+fn module_parser_summary() -> Result<()> {
+    let summary =
+        parse("tests/fixtures/parse-summary/main.js", None, false, None)?;
+    assert_eq!(3, summary.modules);
+    assert_eq!(6, summary.imports);
+    assert_eq!(
+        vec!["fs", "os", "path"],
+        summary.builtins.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["summary-dep"],
+        summary.packages.iter().collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn module_parser_continue_on_error() -> Result<()> {
+    let base = PathBuf::from("tests/fixtures/unresolved").canonicalize()?;
+    assert!(parse(base.join("main.js"), None, false, None).is_err());
+
+    let summary = parse(base.join("main.js"), None, true, None)?;
+    assert_eq!(2, summary.modules);
+    let failures: Vec<_> = summary
+        .failures
+        .iter()
+        .map(|f| (f.file.clone(), f.specifier.clone()))
+        .collect();
+    assert_eq!(
+        vec![
+            (base.join("main.js"), Some(String::from("./missing.js"))),
+            (base.join("ok.js"), Some(String::from("missing-package"))),
+        ],
+        failures
+    );
+    Ok(())
+}
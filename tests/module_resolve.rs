@@ -0,0 +1,66 @@
+use anyhow::Result;
+use std::path::{Component, PathBuf};
+
+use swc_common::FileName;
+use swc_ecma_ast::TargetEnv;
+use swc_ecma_loader::resolve::Resolve;
+
+use basalt::module::{
+    dependencies::resolve, node::resolve_dependency, parser::parse,
+    resolver::ExportsResolver,
+};
+
+const MAIN: &str = "tests/fixtures/extension-resolution/main.js";
+
+#[test]
+fn module_resolve_extensions() -> Result<()> {
+    let base = PathBuf::from("tests/fixtures/extension-resolution");
+    assert_eq!(base.join("./esm.mjs"), resolve("./esm", MAIN)?);
+    assert_eq!(base.join("./common.cjs"), resolve("./common", MAIN)?);
+    assert_eq!(base.join("./util.cjs"), resolve("./util.cjs", MAIN)?);
+    assert_eq!(
+        base.join("./lib").join("index.mjs"),
+        resolve("./lib", MAIN)?
+    );
+    Ok(())
+}
+
+#[test]
+fn module_resolve_normalize() -> Result<()> {
+    let base = PathBuf::from("tests/fixtures/extension-resolution");
+    let resolved = resolve("../extension-resolution/./lib", MAIN)?;
+    assert_eq!(base.join("lib").join("index.mjs"), resolved);
+    assert!(resolved
+        .components()
+        .all(|c| c != Component::ParentDir && c != Component::CurDir));
+    Ok(())
+}
+
+#[test]
+fn module_resolve_package_directory() -> Result<()> {
+    let resolver: Box<dyn Resolve> =
+        Box::new(ExportsResolver::new(TargetEnv::Node));
+    let base = FileName::Real(PathBuf::from(MAIN).canonicalize()?);
+    let resolved = resolve_dependency(&resolver, &base, "./pkg")?;
+    let expected = PathBuf::from("tests/fixtures/extension-resolution/pkg")
+        .canonicalize()?
+        .join("entry.js");
+    assert_eq!(FileName::Real(expected), resolved);
+    Ok(())
+}
+
+#[test]
+fn module_resolve_candidates_error() -> Result<()> {
+    let err = resolve("./missing", MAIN).unwrap_err().to_string();
+    assert!(err.contains("missing.mjs"));
+    assert!(err.contains("missing.cjs"));
+    assert!(err.contains("index.js"));
+    Ok(())
+}
+
+#[test]
+fn module_resolve_parse_graph() -> Result<()> {
+    let summary = parse(MAIN, None, false, None)?;
+    assert_eq!(5, summary.modules);
+    Ok(())
+}
@@ -0,0 +1 @@
+require('builtin-cjs-reexport');
@@ -0,0 +1 @@
+import 'dynamic-require';
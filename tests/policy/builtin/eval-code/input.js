@@ -0,0 +1 @@
+import 'eval-code';
@@ -0,0 +1 @@
+import 'with-statement';
@@ -0,0 +1 @@
+import 'named-deep';
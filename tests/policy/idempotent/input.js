@@ -0,0 +1,3 @@
+import 'named-import';
+import 'named-deep';
+import 'builtin-esm';
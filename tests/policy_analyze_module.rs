@@ -0,0 +1,75 @@
+use anyhow::Result;
+use swc_atoms::JsWord;
+
+use basalt::{
+    policy::analysis::{analyze_module, AnalysisOptions},
+    swc_utils::load_code,
+};
+
+fn words(path: &[&str]) -> Vec<JsWord> {
+    path.iter().map(|w| JsWord::from(*w)).collect()
+}
+
+#[test]
+fn policy_analyze_module() -> Result<()> {
+    let (_, _, module) = load_code(
+        r#"
+import fs from 'fs';
+import pick from 'lodash/pick';
+import local from './local.js';
+fs.readFileSync('foo.txt');
+window.alert(pick(local));
+"#,
+        None,
+        None,
+    )?;
+    let analysis = analyze_module(&module, &Default::default());
+
+    assert!(analysis.globals.contains(&words(&["window", "alert"])));
+    assert!(analysis.builtin.contains(&words(&["fs", "readFileSync"])));
+    assert_eq!(1, analysis.packages.len());
+    assert!(analysis.packages.contains("lodash"));
+    assert!(analysis.globals_access.is_empty());
+    Ok(())
+}
+
+#[test]
+fn policy_analyze_module_access_detail() -> Result<()> {
+    let (_, _, module) =
+        load_code("import fs from 'fs'; fs.readFileSync();", None, None)?;
+    let options = AnalysisOptions {
+        access_detail: true,
+        ..Default::default()
+    };
+    let analysis = analyze_module(&module, &options);
+    assert!(analysis
+        .builtin_access
+        .contains_key(&words(&["fs", "readFileSync"])));
+    Ok(())
+}
+
+#[test]
+fn policy_analyze_module_require_resolve() -> Result<()> {
+    let (_, _, module) = load_code(
+        r#"
+const file = require.resolve('fs');
+const other = require['resolve']('lodash');
+const os = require('os');
+module.exports = [file, other, os.platform()];
+"#,
+        None,
+        None,
+    )?;
+    let analysis = analyze_module(&module, &Default::default());
+
+    // Resolving a module does not access the module value
+    assert!(!analysis.builtin.iter().any(|path| path[0] == "fs"));
+    assert!(analysis.builtin.contains(&words(&["os", "platform"])));
+    assert!(analysis.globals.is_empty());
+    assert!(!analysis.packages.contains("lodash"));
+
+    assert_eq!(2, analysis.resolved.len());
+    assert!(analysis.resolved.contains(&JsWord::from("fs")));
+    assert!(analysis.resolved.contains(&JsWord::from("lodash")));
+    Ok(())
+}
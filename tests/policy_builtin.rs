@@ -22,6 +22,7 @@ const MODULES: &[&str] = &[
     "tests/policy/builtin/named-require",
     "tests/policy/builtin/named-deep",
     "tests/policy/builtin/binary-expression",
+    "tests/policy/builtin/cjs-reexport",
 ];
 
 #[test]
@@ -34,3 +35,89 @@ fn policy_builtin_resources() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn policy_builtin_from_source() -> Result<()> {
+    let dir = "tests/policy/builtin/esm";
+    let expected = read_to_string(PathBuf::from(dir).join("output.json"))?;
+    let file_name = PathBuf::from(dir).join("input.js");
+    let builder = PolicyBuilder::from_source(
+        String::from("import 'builtin-esm';"),
+        file_name.to_string_lossy().into_owned(),
+    );
+    let policy = builder.load()?.analyze()?.finalize();
+    let result = serde_json::to_string_pretty(&policy)?;
+    assert_eq!(expected.trim_end(), result);
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_access_detail() -> Result<()> {
+    let file = PathBuf::from("tests/policy/builtin/esm/input.js");
+    let builder = PolicyBuilder::new(file).access_detail(true);
+    let policy = builder.load()?.analyze()?.finalize();
+    let result = serde_json::to_value(&policy)?;
+    let builtin = &result["resources"]["builtin-esm"]["builtin"];
+    assert_eq!(
+        serde_json::json!({"read": false, "write": false, "execute": true}),
+        builtin["fs.readSync"]
+    );
+    assert_eq!(
+        serde_json::json!({"read": false, "write": false, "execute": true}),
+        builtin["path.join"]
+    );
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_access_detail_write() -> Result<()> {
+    let file = PathBuf::from("tests/policy/builtin/named-deep/input.js");
+    let builder = PolicyBuilder::new(file).access_detail(true);
+    let policy = builder.load()?.analyze()?.finalize();
+    let builtin = &policy.resources.get("named-deep").unwrap().builtin;
+    let result = serde_json::to_value(builtin)?;
+    assert_eq!(true, result["process.env.DEEP1"]["write"]);
+    assert_eq!(true, result["process.env.DEEP2"]["write"]);
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_with_warning() -> Result<()> {
+    let file = PathBuf::from("tests/policy/builtin/with-statement/input.js");
+    let builder = PolicyBuilder::new(file).load()?.analyze()?;
+    let warnings = builder.warnings();
+    assert_eq!(1, warnings.len());
+    assert_eq!("with-statement", warnings[0].package);
+    assert!(warnings[0].file.ends_with("with-statement/index.js"));
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_dynamic_require_warning() -> Result<()> {
+    let file = PathBuf::from("tests/policy/builtin/dynamic-require/input.js");
+    let builder = PolicyBuilder::new(file).load()?.analyze()?;
+    let warnings = builder.warnings();
+    assert_eq!(2, warnings.len());
+    assert!(warnings.iter().all(|w| w.package == "dynamic-require"
+        && w.file.ends_with("dynamic-require/index.js")
+        && w.message.starts_with("require()")));
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_eval_warning() -> Result<()> {
+    let file = PathBuf::from("tests/policy/builtin/eval-code/input.js");
+    let builder = PolicyBuilder::new(file).load()?.analyze()?;
+    let warnings = builder.warnings();
+    assert_eq!(3, warnings.len());
+    assert!(warnings
+        .iter()
+        .all(|w| w.package == "eval-code"
+            && w.file.ends_with("eval-code/index.js")));
+    let lines: Vec<Option<usize>> = warnings.iter().map(|w| w.line).collect();
+    assert_eq!(vec![Some(1), Some(2), Some(4)], lines);
+    assert!(warnings[0].message.starts_with("eval()"));
+    assert!(warnings[1].message.starts_with("Function"));
+    assert!(warnings[2].message.starts_with("timer"));
+    Ok(())
+}
@@ -6,7 +6,8 @@ use swc_ecma_visit::VisitWith;
 use basalt::{
     access::Access,
     policy::analysis::{
-        builtin::BuiltinAnalysis, globals_scope::GlobalAnalysis,
+        builtin::{builtin_access, flatten_access},
+        globals_scope::GlobalAnalysis,
     },
     swc_utils::load_code,
 };
@@ -19,8 +20,7 @@ fn analyze(code: &str) -> Result<IndexMap<JsWord, Access>> {
     let builtin_candidates =
         std::mem::take(&mut globals_scope.builder.candidates);
 
-    let analyzer: BuiltinAnalysis = Default::default();
-    Ok(analyzer.analyze(&module, builtin_candidates))
+    Ok(flatten_access(builtin_access(&module, builtin_candidates)))
 }
 
 // WRITE
@@ -144,8 +144,6 @@ fn policy_builtin_access_execute_require_dot_member_expr() -> Result<()> {
         "#;
     let result = analyze(code)?;
 
-    println!("Result {:#?}", result);
-
     assert_eq!(2, result.len());
     let access = result.get(&JsWord::from("util.inherits")).unwrap();
     assert_eq!(true, access.execute);
@@ -154,6 +152,20 @@ fn policy_builtin_access_execute_require_dot_member_expr() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn policy_builtin_access_execute_tagged_tpl() -> Result<()> {
+    let code = r#"
+        import fs, {readFileSync} from 'fs';
+        fs.readFileSync`foo.txt`;
+        readFileSync.call`bar.txt`;"#;
+    let result = analyze(code)?;
+    assert_eq!(1, result.len());
+    let access = result.get(&JsWord::from("fs.readFileSync")).unwrap();
+    assert_eq!(true, access.execute);
+    assert_eq!(false, access.read);
+    Ok(())
+}
+
 // READ
 
 #[test]
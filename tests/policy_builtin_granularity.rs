@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::builder::{BuiltinGranularity, PolicyBuilder};
+
+const MAIN: &str = "tests/fixtures/builtin-granularity/main.js";
+
+fn builtin(builder: PolicyBuilder) -> Result<serde_json::Value> {
+    let policy = builder.load()?.analyze()?.finalize();
+    let pkg = policy.resources.get("granularity-dep").unwrap();
+    Ok(serde_json::to_value(&pkg.builtin)?)
+}
+
+#[test]
+fn policy_builtin_granularity_parse() -> Result<()> {
+    assert_eq!(BuiltinGranularity::Module, "module".parse()?);
+    assert_eq!(BuiltinGranularity::Member, "member".parse()?);
+    assert!("package".parse::<BuiltinGranularity>().is_err());
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_granularity_member() -> Result<()> {
+    let result = builtin(PolicyBuilder::new(PathBuf::from(MAIN)))?;
+    assert_eq!(
+        serde_json::json!({
+            "fs.readFile": true,
+            "fs.writeFile": true,
+            "path.join": true,
+        }),
+        result
+    );
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_granularity_module() -> Result<()> {
+    let result = builtin(
+        PolicyBuilder::new(PathBuf::from(MAIN))
+            .builtin_granularity(BuiltinGranularity::Module),
+    )?;
+    assert_eq!(serde_json::json!({"fs": true, "path": true}), result);
+    Ok(())
+}
+
+#[test]
+fn policy_builtin_granularity_module_access() -> Result<()> {
+    let result = builtin(
+        PolicyBuilder::new(PathBuf::from(MAIN))
+            .builtin_granularity(BuiltinGranularity::Module)
+            .access_detail(true),
+    )?;
+    assert_eq!(
+        serde_json::json!({"read": false, "write": false, "execute": true}),
+        result["fs"]
+    );
+    assert_eq!(
+        serde_json::json!({"read": false, "write": false, "execute": true}),
+        result["path"]
+    );
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use basalt::policy::builder::{PolicyBuilder, PolicyWarning};
+
+const MAIN: &str = "tests/fixtures/policy-cache/main.js";
+
+fn generate(
+    cache_dir: Option<PathBuf>,
+) -> Result<(String, Vec<PolicyWarning>)> {
+    let mut builder =
+        PolicyBuilder::new(PathBuf::from(MAIN)).access_detail(true);
+    if let Some(cache_dir) = cache_dir {
+        builder = builder.cache_dir(cache_dir);
+    }
+    let builder = builder.load()?.analyze()?;
+    let warnings = builder.warnings().to_vec();
+    let policy = serde_json::to_string_pretty(&builder.finalize())?;
+    Ok((policy, warnings))
+}
+
+#[test]
+fn policy_cache_matches_uncached() -> Result<()> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let cache_dir = std::env::temp_dir().join(format!(
+        "basalt-policy-cache-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+
+    let (expected, expected_warnings) = generate(None)?;
+    assert!(expected.contains("\"fs.readFileSync\""));
+    assert_eq!(1, expected_warnings.len());
+
+    // First run populates the cache
+    let (cold, cold_warnings) = generate(Some(cache_dir.clone()))?;
+    assert_eq!(expected, cold);
+    assert_eq!(expected_warnings, cold_warnings);
+    let entries = std::fs::read_dir(&cache_dir)?.count();
+    assert_eq!(2, entries);
+
+    // Second run reuses the cached analysis
+    let (warm, warm_warnings) = generate(Some(cache_dir.clone()))?;
+    assert_eq!(expected, warm);
+    assert_eq!(expected_warnings, warm_warnings);
+    assert_eq!(entries, std::fs::read_dir(&cache_dir)?.count());
+
+    std::fs::remove_dir_all(&cache_dir)?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use basalt::{
+    audit, check,
+    policy::{Violation, ViolationKind},
+    BasaltError,
+};
+
+const DIR: &str = "tests/policy/check";
+
+fn run(policy: &[&str]) -> basalt::error::Result<Vec<Violation>> {
+    let dir = PathBuf::from(DIR);
+    check(
+        dir.join("input.js"),
+        policy.iter().map(|file| dir.join(file)).collect(),
+    )
+}
+
+#[test]
+fn policy_check_granted_parent() -> Result<()> {
+    assert!(run(&["granted.json"])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn policy_check_violations() -> Result<()> {
+    let violations = run(&["partial.json"])?;
+    assert_eq!(
+        vec![Violation {
+            package: String::from("named-deep"),
+            kind: ViolationKind::Builtin,
+            name: String::from("process.env.DEEP2"),
+        }],
+        violations
+    );
+    assert_eq!(
+        "builtin process.env.DEEP2 is not granted to named-deep",
+        violations[0].to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn policy_check_merged() -> Result<()> {
+    assert!(run(&["partial.json", "override.json"])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn policy_check_requires_policy() -> Result<()> {
+    assert!(matches!(run(&[]), Err(BasaltError::Usage(_))));
+    Ok(())
+}
+
+#[test]
+fn policy_check_unused() -> Result<()> {
+    let dir = PathBuf::from(DIR);
+    let unused = audit(dir.join("input.js"), vec![dir.join("broad.json")])?;
+
+    let pkg = unused.get("named-deep").unwrap();
+    assert_eq!(vec!["fs"], pkg.builtin);
+    assert_eq!(vec!["console"], pkg.globals);
+    assert_eq!(vec!["other"], pkg.packages);
+
+    let pkg = unused.get("unused-pkg").unwrap();
+    assert!(pkg.builtin.is_empty());
+    assert_eq!(vec!["window"], pkg.globals);
+
+    let unused = audit(dir.join("input.js"), vec![dir.join("partial.json")])?;
+    assert!(unused.is_empty());
+    Ok(())
+}
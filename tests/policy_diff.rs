@@ -0,0 +1,70 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::{EnvPolicy, Policy};
+
+use testing::read_to_string;
+
+fn load(file: &str) -> Result<Policy> {
+    Ok(serde_json::from_str(&read_to_string(PathBuf::from(file))?)?)
+}
+
+#[test]
+fn policy_diff() -> Result<()> {
+    let policy1 = load("tests/policy/merge/policy1.json")?;
+    let policy2 = load("tests/policy/merge/policy2.json")?;
+
+    let diff = policy1.diff(&policy2);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+
+    let pkg = diff.changed.get("@lavamoat/mock").unwrap();
+    assert_eq!(true, pkg.native.as_ref().unwrap().to);
+    assert_eq!(EnvPolicy::Unfrozen, pkg.env.as_ref().unwrap().to);
+    assert_eq!(vec!["path.basename"], pkg.builtin.added);
+    assert_eq!(vec!["path.join"], pkg.builtin.removed);
+    assert!(pkg.globals.added.is_empty());
+    assert_eq!(vec!["process.env"], pkg.globals.removed);
+    assert_eq!(vec!["@lavamoat/alt-mock-dep"], pkg.packages.added);
+    assert_eq!(vec!["@lavamoat/mock-dep"], pkg.packages.removed);
+    assert!(diff.has_added_capabilities());
+
+    Ok(())
+}
+
+#[test]
+fn policy_diff_packages() -> Result<()> {
+    let policy1 = load("tests/policy/merge/policy1.json")?;
+    let empty: Policy = Default::default();
+
+    let diff = policy1.diff(&empty);
+    assert_eq!(vec!["@lavamoat/mock"], diff.removed);
+    assert!(!diff.has_added_capabilities());
+
+    let diff = empty.diff(&policy1);
+    assert_eq!(vec!["@lavamoat/mock"], diff.added);
+    assert!(diff.has_added_capabilities());
+
+    assert!(policy1.diff(&policy1).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn policy_diff_access() -> Result<()> {
+    let policy1 = load("tests/policy/diff/policy1.json")?;
+    let policy2 = load("tests/policy/diff/policy2.json")?;
+
+    let diff = policy1.diff(&policy2);
+    let pkg = diff.changed.get("@lavamoat/mock").unwrap();
+    assert_eq!(vec!["console"], pkg.globals.added);
+    assert_eq!(vec!["process.env"], pkg.globals.removed);
+    assert!(diff.has_added_capabilities());
+
+    let diff = policy2.diff(&policy1);
+    let pkg = diff.changed.get("@lavamoat/mock").unwrap();
+    assert_eq!(vec!["process.env"], pkg.globals.added);
+    assert_eq!(vec!["console"], pkg.globals.removed);
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use basalt::{
+    json::Indent,
+    policy::{Policy, PolicyFormat},
+};
+
+const CORPUS: &str = "tests/policy/round-trip";
+
+fn corpus() -> Result<Vec<Policy>> {
+    let mut files = std::fs::read_dir(CORPUS)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>>>()?;
+    files.sort();
+    files.iter().map(|file| Policy::load_file(file)).collect()
+}
+
+fn round_trip(format: PolicyFormat) -> Result<()> {
+    for policy in corpus()? {
+        let contents = format.serialize(&policy, Default::default())?;
+        assert_eq!(policy, format.parse(&contents)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn policy_format_from_path() -> Result<()> {
+    let formats = [
+        ("policy.json", PolicyFormat::Json),
+        ("policy.yaml", PolicyFormat::Yaml),
+        ("policy.yml", PolicyFormat::Yaml),
+        ("policy.TOML", PolicyFormat::Toml),
+        ("policy", PolicyFormat::Json),
+    ];
+    for (file, format) in formats.iter() {
+        assert_eq!(*format, PolicyFormat::from_path(Path::new(file)));
+    }
+    Ok(())
+}
+
+#[test]
+fn policy_format_json_round_trip() -> Result<()> {
+    round_trip(PolicyFormat::Json)?;
+    let policy =
+        Policy::load_file(Path::new("tests/policy/merge/output.json"))?;
+    let contents = PolicyFormat::Json.serialize(&policy, Indent::Tab)?;
+    assert!(contents.contains("\n\t\"resources\""));
+    Ok(())
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn policy_format_yaml_round_trip() -> Result<()> {
+    round_trip(PolicyFormat::Yaml)
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn policy_format_toml_round_trip() -> Result<()> {
+    round_trip(PolicyFormat::Toml)
+}
+
+#[cfg(all(feature = "yaml", feature = "toml"))]
+#[test]
+fn policy_format_merge_mixed() -> Result<()> {
+    let expected =
+        Policy::load_file(Path::new("tests/policy/merge/output.json"))?;
+    let policy = Policy::load_files(&[
+        PathBuf::from("tests/policy/format/policy1.yaml"),
+        PathBuf::from("tests/policy/format/policy2.toml"),
+    ])?;
+    assert_eq!(expected, policy);
+    Ok(())
+}
+
+#[cfg(not(feature = "yaml"))]
+#[test]
+fn policy_format_yaml_unsupported() -> Result<()> {
+    let result =
+        Policy::load_file(Path::new("tests/policy/format/policy1.yaml"));
+    let message = format!("{:#}", result.unwrap_err());
+    assert!(message.contains("require the yaml feature"));
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use basalt::{
+    policy::{Merge, Policy},
+    policy_glob, BasaltError,
+};
+
+use testing::read_to_string;
+
+fn load_expected(dir: &str) -> Result<Policy> {
+    let path = PathBuf::from(dir).join("output.json");
+    Ok(serde_json::from_str(&read_to_string(path)?)?)
+}
+
+#[test]
+fn policy_glob_merge() -> Result<()> {
+    let mut expected = load_expected("tests/policy/builtin/esm")?;
+    expected.merge(&load_expected("tests/policy/builtin/cjs")?);
+
+    // The esm module matches both of the first two patterns
+    let policy = policy_glob(vec![
+        String::from("tests/policy/builtin/esm/*.js"),
+        String::from("tests/policy/builtin/*sm/input.js"),
+        String::from("tests/policy/builtin/cjs/input.js"),
+    ])?;
+    assert_eq!(expected, policy);
+    Ok(())
+}
+
+#[test]
+fn policy_glob_no_match() -> Result<()> {
+    let result =
+        policy_glob(vec![String::from("tests/policy/builtin/*/missing.js")]);
+    assert!(matches!(result, Err(BasaltError::Usage(_))));
+    Ok(())
+}
+
+#[test]
+fn policy_glob_invalid_pattern() -> Result<()> {
+    let result = policy_glob(vec![String::from("tests/policy/***/input.js")]);
+    assert!(matches!(result, Err(BasaltError::Usage(_))));
+    Ok(())
+}
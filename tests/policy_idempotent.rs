@@ -0,0 +1,21 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::builder::PolicyBuilder;
+
+use testing::read_to_string;
+
+#[test]
+fn policy_idempotent() -> Result<()> {
+    // Compare against a committed policy as modules are cached
+    // for the process so generating twice reuses the parsed modules
+    let dir = PathBuf::from("tests/policy/idempotent");
+    let expected = read_to_string(dir.join("output.json"))?;
+    let policy = PolicyBuilder::new(dir.join("input.js"))
+        .load()?
+        .analyze()?
+        .finalize();
+    let result = serde_json::to_string_pretty(&policy)?;
+    assert_eq!(expected.trim_end(), result);
+    Ok(())
+}
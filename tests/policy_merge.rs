@@ -22,3 +22,36 @@ fn policy_merge() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn policy_merge_remove() -> Result<()> {
+    let expected: Policy = serde_json::from_str(&read_to_string(
+        PathBuf::from("tests/policy/override/output.json"),
+    )?)?;
+    let mut base: Policy = serde_json::from_str(&read_to_string(
+        PathBuf::from("tests/policy/override/base.json"),
+    )?)?;
+    let overrides: Policy = serde_json::from_str(&read_to_string(
+        PathBuf::from("tests/policy/override/override.json"),
+    )?)?;
+
+    base.merge(&overrides);
+    assert_eq!(expected, base);
+
+    Ok(())
+}
+
+#[test]
+fn policy_access_object() -> Result<()> {
+    let source = r#"{"resources": {"foo": {"globals": {
+        "fetch": {"execute": true},
+        "location": true
+    }}}}"#;
+    let policy: Policy = serde_json::from_str(source)?;
+    let value = serde_json::to_value(&policy)?;
+    let globals = &value["resources"]["foo"]["globals"];
+    assert_eq!(true, globals["fetch"]["execute"]);
+    assert_eq!(false, globals["fetch"]["read"]);
+    assert_eq!(true, globals["location"]);
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::builder::PolicyBuilder;
+
+#[test]
+fn policy_package_keys_canonical() -> Result<()> {
+    let file = PathBuf::from("tests/fixtures/package-keys/main.js");
+    let policy = PolicyBuilder::new(file).load()?.analyze()?.finalize();
+    let keys: Vec<&str> = policy.resources.keys().map(|k| &k[..]).collect();
+    assert_eq!(vec!["canonical-name"], keys);
+
+    // Deep import below a nested `package.json` is grouped with the package
+    let pkg = policy.resources.get("canonical-name").unwrap();
+    assert_eq!(
+        serde_json::json!({"builtin": {"fs": true, "path": true}}),
+        serde_json::to_value(pkg)?
+    );
+    Ok(())
+}
+
+#[test]
+fn policy_package_keys_grants() -> Result<()> {
+    let file = PathBuf::from("tests/fixtures/package-grants/main.js");
+    let policy = PolicyBuilder::new(file).load()?.analyze()?.finalize();
+    let keys: Vec<&str> = policy.resources.keys().map(|k| &k[..]).collect();
+    assert_eq!(vec!["alias-dependent", "canonical-name"], keys);
+
+    // Dependents of an aliased install are granted the canonical name
+    let pkg = policy.resources.get("alias-dependent").unwrap();
+    assert_eq!(
+        serde_json::json!({"packages": {"canonical-name": true}}),
+        serde_json::to_value(pkg)?
+    );
+    Ok(())
+}
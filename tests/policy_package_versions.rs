@@ -0,0 +1,31 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::builder::PolicyBuilder;
+
+#[test]
+fn policy_package_versions_default() -> Result<()> {
+    let file = PathBuf::from("tests/fixtures/package-keys/main.js");
+    let policy = PolicyBuilder::new(file).load()?.analyze()?.finalize();
+    let pkg = policy.resources.get("canonical-name").unwrap();
+    assert_eq!(None, pkg.version);
+    assert!(serde_json::to_value(pkg)?.get("version").is_none());
+    Ok(())
+}
+
+#[test]
+fn policy_package_versions() -> Result<()> {
+    let file = PathBuf::from("tests/fixtures/package-keys/main.js");
+    let policy = PolicyBuilder::new(file)
+        .package_versions(true)
+        .load()?
+        .analyze()?
+        .finalize();
+    let pkg = policy.resources.get("canonical-name").unwrap();
+    assert_eq!(Some(String::from("1.0.0")), pkg.version);
+    assert_eq!(
+        Some(&serde_json::json!("1.0.0")),
+        serde_json::to_value(pkg)?.get("version")
+    );
+    Ok(())
+}
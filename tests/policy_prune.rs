@@ -0,0 +1,43 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::{builder::PolicyBuilder, Policy};
+
+#[test]
+fn policy_prune_empty() -> Result<()> {
+    let file = PathBuf::from("tests/policy/prune-empty/input.js");
+    let policy = PolicyBuilder::new(file).load()?.analyze()?.finalize();
+    assert!(!policy.resources.contains_key("prune-empty"));
+    Ok(())
+}
+
+#[test]
+fn policy_prune_empty_disabled() -> Result<()> {
+    let file = PathBuf::from("tests/policy/prune-empty/input.js");
+    let policy = PolicyBuilder::new(file)
+        .prune_empty(false)
+        .load()?
+        .analyze()?
+        .finalize();
+    let pkg = policy.resources.get("prune-empty").unwrap();
+    assert!(pkg.is_empty());
+    Ok(())
+}
+
+#[test]
+fn policy_prune_empty_retain() -> Result<()> {
+    let mut policy: Policy = serde_json::from_str(
+        r#"{
+  "resources": {
+    "empty": {},
+    "native": {"native": true},
+    "env": {"env": "unfrozen"},
+    "globals": {"globals": {"console": true}}
+  }
+}"#,
+    )?;
+    policy.prune_empty();
+    let keys: Vec<&str> = policy.resources.keys().map(|k| &k[..]).collect();
+    assert_eq!(vec!["env", "globals", "native"], keys);
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use basalt::{
+    access::Access,
+    policy::{EnvPolicy, PackagePolicy, Policy, PolicyAccess, PolicyGroup},
+};
+
+use testing::read_to_string;
+
+const CORPUS: &str = "tests/policy/round-trip";
+
+const KEYS: &[&str] = &[
+    "console",
+    "console.log",
+    "fs",
+    "fs.readFileSync",
+    "process.env",
+    "@scope/pkg",
+    "pkg",
+];
+
+/// Minimal xorshift generator so the arbitrary policies are
+/// reproducible without any additional dependencies.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn flag(&mut self) -> bool {
+        self.below(2) == 1
+    }
+}
+
+fn arbitrary_access(rng: &mut Random) -> PolicyAccess {
+    match rng.below(3) {
+        0 => rng.flag().into(),
+        1 => Access {
+            read: rng.flag(),
+            write: rng.flag(),
+            execute: rng.flag(),
+        }
+        .into(),
+        _ => PolicyAccess::remove(),
+    }
+}
+
+fn arbitrary_group(rng: &mut Random) -> PolicyGroup {
+    let mut group: PolicyGroup = Default::default();
+    for _ in 0..rng.below(4) {
+        let key = KEYS[rng.below(KEYS.len() as u64) as usize];
+        group.insert(key, arbitrary_access(rng));
+    }
+    group
+}
+
+fn arbitrary_policy(rng: &mut Random) -> Policy {
+    let mut policy: Policy = Default::default();
+    for _ in 0..rng.below(4) {
+        let name = KEYS[rng.below(KEYS.len() as u64) as usize];
+        let package = PackagePolicy {
+            version: if rng.flag() {
+                Some(format!("{}.0.0", rng.below(10)))
+            } else {
+                None
+            },
+            native: rng.flag(),
+            env: if rng.flag() {
+                EnvPolicy::Frozen
+            } else {
+                EnvPolicy::Unfrozen
+            },
+            builtin: arbitrary_group(rng),
+            globals: arbitrary_group(rng),
+            packages: arbitrary_group(rng),
+        };
+        policy.insert(name, package);
+    }
+    policy
+}
+
+fn round_trip(policy: &Policy) -> Result<Policy> {
+    let contents = serde_json::to_string(policy)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+#[test]
+fn policy_round_trip_arbitrary() -> Result<()> {
+    let mut rng = Random(0x5eed_ba5a_17);
+    for _ in 0..512 {
+        let policy = arbitrary_policy(&mut rng);
+        assert_eq!(policy, round_trip(&policy)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn policy_round_trip_corpus() -> Result<()> {
+    let mut files = std::fs::read_dir(CORPUS)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>>>()?;
+    files.sort();
+    for file in files {
+        println!("Run policy round trip {:#?}", file);
+        let policy: Policy = serde_json::from_str(&read_to_string(&file)?)?;
+        assert_eq!(policy, round_trip(&policy)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn policy_normalize() -> Result<()> {
+    let file = PathBuf::from(CORPUS).join("override.json");
+    let mut policy: Policy = serde_json::from_str(&read_to_string(&file)?)?;
+    policy.normalize();
+
+    let pkg = policy.resources.get("override").unwrap();
+    assert_eq!(
+        r#"{"process.env":"remove"}"#,
+        serde_json::to_string(&pkg.globals)?
+    );
+    assert_eq!(
+        r#"{"path.basename":true,"path.join":"remove"}"#,
+        serde_json::to_string(&pkg.builtin)?
+    );
+
+    let file = PathBuf::from(CORPUS).join("access.json");
+    let mut policy: Policy = serde_json::from_str(&read_to_string(&file)?)?;
+    policy.normalize();
+    let pkg = policy.resources.get("access").unwrap();
+    assert!(!serde_json::to_string(&pkg.builtin)?.contains("writeFileSync"));
+
+    let mut rng = Random(0x0dd_ba11);
+    for _ in 0..512 {
+        let mut policy = arbitrary_policy(&mut rng);
+        policy.normalize();
+        let mut normalized = round_trip(&policy)?;
+        normalized.normalize();
+        assert_eq!(policy, normalized);
+    }
+    Ok(())
+}
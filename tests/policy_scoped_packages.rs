@@ -0,0 +1,50 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::{helpers::normalize_specifier, policy::builder::PolicyBuilder};
+
+#[test]
+fn policy_scoped_specifier() -> Result<()> {
+    assert_eq!("@scope/name", normalize_specifier("@scope/name"));
+    Ok(())
+}
+
+#[test]
+fn policy_scoped_deep_specifier() -> Result<()> {
+    assert_eq!(
+        "@scope/name",
+        normalize_specifier("@scope/name/lib/deep.js")
+    );
+    Ok(())
+}
+
+#[test]
+fn policy_unscoped_specifier() -> Result<()> {
+    assert_eq!("unscoped", normalize_specifier("unscoped"));
+    assert_eq!("unscoped", normalize_specifier("unscoped/lib/deep.js"));
+    Ok(())
+}
+
+#[test]
+fn policy_scoped_packages() -> Result<()> {
+    let file = PathBuf::from("tests/fixtures/scoped-packages/main.js");
+    let policy = PolicyBuilder::new(file).load()?.analyze()?.finalize();
+    let keys: Vec<&str> = policy.resources.keys().map(|k| &k[..]).collect();
+    assert_eq!(vec!["@scope/name", "unscoped"], keys);
+
+    let pkg = policy.resources.get("@scope/name").unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "builtin": {"fs": true},
+            "packages": {"unscoped": true}
+        }),
+        serde_json::to_value(pkg)?
+    );
+
+    let pkg = policy.resources.get("unscoped").unwrap();
+    assert_eq!(
+        serde_json::json!({"builtin": {"path": true}}),
+        serde_json::to_value(pkg)?
+    );
+    Ok(())
+}
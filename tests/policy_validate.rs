@@ -0,0 +1,38 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use basalt::policy::Policy;
+
+use testing::read_to_string;
+
+#[test]
+fn policy_validate_strict() -> Result<()> {
+    let source =
+        read_to_string(PathBuf::from("tests/policy/merge/policy1.json"))?;
+    let lenient: Policy = serde_json::from_str(&source)?;
+    let strict = Policy::from_str_strict(&source)?;
+    assert_eq!(lenient, strict);
+    Ok(())
+}
+
+#[test]
+fn policy_validate_unknown_package_key() -> Result<()> {
+    let source = r#"{"resources": {"foo": {"gobals": {"fetch": true}}}}"#;
+
+    // Lenient parsing ignores the misspelled key
+    let lenient: Policy = serde_json::from_str(source)?;
+    assert!(lenient.resources.get("foo").unwrap().is_empty());
+
+    let err = Policy::from_str_strict(source).unwrap_err().to_string();
+    assert!(err.contains("\"gobals\""));
+    assert!(err.contains("resources.foo"));
+    Ok(())
+}
+
+#[test]
+fn policy_validate_unknown_top_level_key() -> Result<()> {
+    let source = r#"{"resource": {}}"#;
+    let err = Policy::from_str_strict(source).unwrap_err().to_string();
+    assert!(err.contains("\"resource\""));
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use std::path::Path;
+use std::sync::Arc;
+
+use swc_common::FileName;
+use swc_ecma_ast::{Decl, ModuleDecl, ModuleItem, Stmt};
+use swc_ecma_parser::Syntax;
+
+use basalt::policy::analysis::globals_scope::{analyze_globals, GlobalOptions};
+use basalt::preprocess::{register_preprocessor, SourcePreprocessor};
+use basalt::swc_utils::{get_syntax, load_file, span_to_location};
+
+#[test]
+fn preprocess_vue() -> Result<()> {
+    let (_, source_map, module) =
+        load_file("tests/fixtures/sfc/counter.vue", None)?;
+    assert_eq!(3, module.body.len());
+    assert!(matches!(
+        &module.body[1],
+        ModuleItem::Stmt(Stmt::Decl(Decl::TsInterface(_)))
+    ));
+
+    // Line numbers match the component file
+    if let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = &module.body[0]
+    {
+        let loc = span_to_location(&source_map, import.span);
+        assert_eq!(6, loc.line);
+    } else {
+        panic!("expected an import declaration");
+    }
+
+    let globals = analyze_globals(&module, Default::default());
+    assert!(globals.contains("localStorage.setItem"));
+    Ok(())
+}
+
+#[test]
+fn preprocess_svelte() -> Result<()> {
+    let (_, _, module) = load_file("tests/fixtures/sfc/counter.svelte", None)?;
+    assert_eq!(3, module.body.len());
+    let globals = analyze_globals(&module, GlobalOptions::default());
+    assert!(globals.contains("window.innerWidth"));
+    Ok(())
+}
+
+/// Replace the file with a module that exports the file name.
+struct Markdown;
+
+impl SourcePreprocessor for Markdown {
+    fn extract(&self, path: &Path, _content: &str) -> Result<(String, Syntax)> {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let script = format!("export const name = '{}';", name);
+        Ok((script, get_syntax(&FileName::Anon)))
+    }
+}
+
+#[test]
+fn preprocess_register() -> Result<()> {
+    register_preprocessor("md", Arc::new(Markdown));
+    let (_, _, module) = load_file("README.md", None)?;
+    assert_eq!(1, module.body.len());
+    Ok(())
+}
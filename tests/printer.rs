@@ -0,0 +1,171 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use basalt::printer::{PrintFormat, PrintOptions, Printer};
+
+const MAIN: &str = "tests/fixtures/basic-tree/main.js";
+
+fn print(format: PrintFormat) -> Result<String> {
+    let options = PrintOptions {
+        format,
+        ..Default::default()
+    };
+    write(&options)
+}
+
+fn write(options: &PrintOptions) -> Result<String> {
+    let mut out = Vec::new();
+    Printer::new().write(MAIN, options, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn printer_format_parse() -> Result<()> {
+    assert_eq!(PrintFormat::Tree, "tree".parse()?);
+    assert_eq!(PrintFormat::Flat, "flat".parse()?);
+    assert_eq!(PrintFormat::Json, "json".parse()?);
+    assert!("yaml".parse::<PrintFormat>().is_err());
+    Ok(())
+}
+
+#[test]
+fn printer_format_flat() -> Result<()> {
+    let output = print(PrintFormat::Flat)?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(MAIN, lines[0]);
+    assert!(lines.iter().skip(1).all(|l| !l.contains("──")));
+    let mut unique = lines.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(lines.len(), unique.len());
+    Ok(())
+}
+
+#[test]
+fn printer_format_json() -> Result<()> {
+    let output = print(PrintFormat::Json)?;
+    let value: Value = serde_json::from_str(&output)?;
+    let modules = value.as_array().unwrap();
+    assert_eq!(4, modules.len());
+
+    let main = &modules[0];
+    assert!(main["file"].as_str().unwrap().ends_with("main.js"));
+    let deps: Vec<&str> = main["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert_eq!(2, deps.len());
+    assert!(deps[0].ends_with("foo.js"));
+    assert!(deps[1].ends_with("bar.js"));
+    Ok(())
+}
+
+#[test]
+fn printer_format_json_streamed() -> Result<()> {
+    // Streamed entries match pretty printing the entire array
+    let output = print(PrintFormat::Json)?;
+    let value: Vec<IndexMap<String, Value>> = serde_json::from_str(&output)?;
+    assert_eq!(serde_json::to_string_pretty(&value)? + "\n", output);
+    Ok(())
+}
+
+#[test]
+fn printer_cycles() -> Result<()> {
+    let options = PrintOptions {
+        cycles: true,
+        ..Default::default()
+    };
+    let output = write(&options)?;
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(1, lines.len());
+    let modules: Vec<&str> = lines[0].split(" -> ").collect();
+    assert_eq!(3, modules.len());
+    assert!(modules[0].ends_with("foo.js"));
+    assert!(modules[1].ends_with("bar.js"));
+    assert!(modules[2].ends_with("foo.js"));
+    Ok(())
+}
+
+/// Copy the fixture to a directory and print the module graph
+/// relative to that directory.
+fn write_from_root(name: &str, format: PrintFormat) -> Result<String> {
+    let root = std::env::temp_dir().join(name).join("project");
+    std::fs::create_dir_all(&root)?;
+    for entry in std::fs::read_dir("tests/fixtures/basic-tree")? {
+        let path = entry?.path();
+        std::fs::copy(&path, root.join(path.file_name().unwrap()))?;
+    }
+    let root = root.canonicalize()?;
+    let options = PrintOptions {
+        format,
+        include_file: true,
+        root: Some(root.clone()),
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    Printer::new().write(root.join("main.js"), &options, &mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn printer_root_relative() -> Result<()> {
+    for format in [PrintFormat::Tree, PrintFormat::Flat, PrintFormat::Json] {
+        let first = write_from_root("basalt-printer-root-a", format)?;
+        let second = write_from_root("basalt-printer-root-b", format)?;
+        assert_eq!(first, second);
+        assert!(!first.contains("basalt-printer-root"));
+    }
+
+    let output = write_from_root("basalt-printer-root-a", PrintFormat::Json)?;
+    let value: Value = serde_json::from_str(&output)?;
+    assert_eq!("main.js", value[0]["file"].as_str().unwrap());
+    Ok(())
+}
+
+fn annotate(format: PrintFormat) -> Result<String> {
+    let options = PrintOptions {
+        format,
+        annotate: true,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    Printer::new().write(
+        "tests/fixtures/annotate/main.js",
+        &options,
+        &mut out,
+    )?;
+    Ok(String::from_utf8(out)?)
+}
+
+#[test]
+fn printer_annotate_tree() -> Result<()> {
+    let output = annotate(PrintFormat::Tree)?;
+    let expected = concat!(
+        "tests/fixtures/annotate/main.js\n",
+        "  globals: document.title, location.href\n",
+        "└── ./util.js\n",
+        "      builtin: fs.readFileSync\n",
+    );
+    assert_eq!(expected, output);
+    Ok(())
+}
+
+#[test]
+fn printer_annotate_json() -> Result<()> {
+    let output = annotate(PrintFormat::Json)?;
+    let value: Value = serde_json::from_str(&output)?;
+    assert_eq!(
+        serde_json::json!(["document.title", "location.href"]),
+        value[0]["globals"]
+    );
+    assert_eq!(serde_json::json!(["fs.readFileSync"]), value[1]["builtin"]);
+
+    // Annotations are omitted by default
+    let output = print(PrintFormat::Json)?;
+    let value: Value = serde_json::from_str(&output)?;
+    assert!(value[0].get("globals").is_none());
+    Ok(())
+}
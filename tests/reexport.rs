@@ -4,7 +4,9 @@ use std::sync::Arc;
 use swc::TransformOutput;
 use swc_common::SourceMap;
 
-use basalt::static_module_record::{self, StaticModuleRecordMeta};
+use basalt::static_module_record::{
+    self, StaticModuleRecordMeta, TransformSource,
+};
 
 use testing::read_to_string;
 
@@ -41,3 +43,145 @@ fn reexport_default_alias() -> Result<()> {
     assert_eq!(expected, result.code);
     Ok(())
 }
+
+#[test]
+fn reexport_chain() -> Result<()> {
+    let (a, _) = transform("tests/transform/reexport-chain/a.js")?;
+    assert_eq!(Some(&(String::from("a"), true)), a.live_export_map.get("a"));
+
+    let (b, _) = transform("tests/transform/reexport-chain/b.js")?;
+    assert_eq!(Some(&vec![String::from("a")]), b.imports.get("./a.js"));
+    assert_eq!(
+        Some(&(String::from("a"), false)),
+        b.live_export_map.get("b")
+    );
+    assert!(b.live_export_map.get("a").is_none());
+
+    let (c, _) = transform("tests/transform/reexport-chain/input.js")?;
+    assert_eq!(Some(&vec![String::from("b")]), c.imports.get("./b.js"));
+    assert_eq!(1, c.live_export_map.len());
+    assert_eq!(
+        Some(&(String::from("b"), false)),
+        c.live_export_map.get("c")
+    );
+    Ok(())
+}
+
+#[test]
+fn reexport_merge_imports() -> Result<()> {
+    let expected =
+        read_to_string("tests/transform/reexport-merge-imports/output.js")?;
+    let (meta, result) =
+        transform("tests/transform/reexport-merge-imports/input.js")?;
+    //println!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert_eq!(
+        Some(&vec![String::from("x"), String::from("y")]),
+        meta.imports.get("./m.js")
+    );
+    Ok(())
+}
+
+#[test]
+fn reexport_namespace() -> Result<()> {
+    let expected =
+        read_to_string("tests/transform/reexport-namespace/output.js")?;
+    let (meta, result) =
+        transform("tests/transform/reexport-namespace/input.js")?;
+    //println!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert_eq!(Some(&vec![String::from("*")]), meta.imports.get("./foo.js"));
+    assert_eq!(
+        Some(&vec![String::from("ns")]),
+        meta.fixed_export_map.get("ns")
+    );
+    assert!(meta.export_alls.is_empty());
+    Ok(())
+}
+
+#[test]
+fn reexport_dedupe_decls() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let source = TransformSource::Str {
+        content: String::from(
+            "import { x } from './m.js';\nexport { x } from './m.js';\nimport { y } from './n.js';\nexport { y as z } from './n.js';",
+        ),
+        file_name: String::from("reexport-dedupe-decls.js"),
+    };
+    let (_, result) = static_module_record::transform(source, source_map)?;
+    //println!("{}", &result.code);
+    assert!(result.code.contains("let x, y;"));
+    assert!(!result.code.contains("let z"));
+    Ok(())
+}
+
+#[test]
+fn reexport_no_local_decl() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let source = TransformSource::Str {
+        content: String::from("export { x } from './m.js';"),
+        file_name: String::from("reexport-no-local-decl.js"),
+    };
+    let (meta, result) = static_module_record::transform(source, source_map)?;
+    assert_eq!(Some(&vec![String::from("x")]), meta.imports.get("./m.js"));
+    assert_eq!(
+        Some(&(String::from("x"), false)),
+        meta.live_export_map.get("x")
+    );
+    assert!(!result.code.contains("let "));
+    assert!(result.code.contains(r#"$h‍_live["x"]"#));
+    Ok(())
+}
+
+#[test]
+fn reexport_default_as() -> Result<()> {
+    let expected =
+        read_to_string("tests/transform/reexport-default-as/output.js")?;
+    let (meta, result) =
+        transform("tests/transform/reexport-default-as/input.js")?;
+    //println!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert_eq!(
+        Some(&vec![String::from("default")]),
+        meta.imports.get("./foo.js")
+    );
+    assert_eq!(
+        Some(&(String::from("default"), false)),
+        meta.live_export_map.get("Foo")
+    );
+    assert!(meta.live_export_map.get("default").is_none());
+    Ok(())
+}
+
+#[test]
+fn reexport_default_alias_meta() -> Result<()> {
+    let (meta, _) =
+        transform("tests/transform/reexport-default-alias/input.js")?;
+    assert_eq!(
+        Some(&vec![String::from("meaning")]),
+        meta.imports.get("./meaning.js")
+    );
+    assert_eq!(
+        Some(&(String::from("meaning"), false)),
+        meta.live_export_map.get("default")
+    );
+    Ok(())
+}
+
+#[test]
+fn reexport_default_as_with_import() -> Result<()> {
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let source = TransformSource::Str {
+        content: String::from(
+            "import foo from './foo.js';\nexport { default as Foo } from './foo.js';",
+        ),
+        file_name: String::from("reexport-default-as-with-import.js"),
+    };
+    let (_, result) = static_module_record::transform(source, source_map)?;
+    //println!("{}", &result.code);
+    // The import is assigned to the local and the re-export is live
+    assert!(result.code.contains("let foo;"));
+    assert!(result.code.contains("(foo = $h‍_a)"));
+    assert!(result.code.contains(r#"$h‍_live["default"]"#));
+    Ok(())
+}
@@ -0,0 +1,7 @@
+import { readFileSync } from 'fs';
+class Loader {
+    static base = location.href;
+    load() {
+        return readFileSync(this.path);
+    }
+}
@@ -0,0 +1,7 @@
+const url = new URL('./data.json', import.meta.url);
+const meta = import.meta;
+function Base() {
+    if (new.target === undefined) {
+        throw new TypeError(location.href);
+    }
+}
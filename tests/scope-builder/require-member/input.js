@@ -0,0 +1,3 @@
+const os = require('os');
+os.platform();
+navigator.userAgent;
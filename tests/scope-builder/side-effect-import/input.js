@@ -0,0 +1,3 @@
+import 'fs';
+import './polyfill.js';
+import { join } from 'path';
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use std::path::PathBuf;
+use swc_ecma_visit::VisitWith;
+
+use basalt::{
+    policy::analysis::{
+        analyze_module, globals_scope::GlobalAnalysis, join_words,
+    },
+    swc_utils::{load_code, load_file},
+};
+
+use testing::read_to_string;
+
+// The globals and builtins are computed by the same scope builder
+// so analyze once and compare both results.
+fn analyze(dir: &str) -> Result<(String, String)> {
+    let base = PathBuf::from(dir);
+    let input = base.join("input.js");
+    let expected = read_to_string(&base.join("output.json"))?;
+    let (_, _, module) = load_file(&input, None)?;
+    let mut analyzer = GlobalAnalysis::new(Default::default());
+    module.visit_children_with(&mut analyzer);
+    let builtins: Vec<String> = analyzer
+        .compute_builtins()
+        .iter()
+        .map(|words| join_words(words).as_ref().to_string())
+        .collect();
+    let globals = analyzer.compute_globals();
+    let globals: Vec<String> = analyzer
+        .flatten_join(globals)
+        .iter()
+        .map(|word| word.as_ref().to_string())
+        .collect();
+    let mut output = IndexMap::new();
+    output.insert("globals", globals);
+    output.insert("builtins", builtins);
+    let result = serde_json::to_string_pretty(&output)?;
+    Ok((expected.trim_end().to_owned(), result))
+}
+
+const MODULES: &[&str] = &[
+    "tests/scope-builder/class-members",
+    "tests/scope-builder/require-member",
+    "tests/scope-builder/meta-properties",
+    "tests/scope-builder/side-effect-import",
+];
+
+#[test]
+fn scope_builder_modules() -> Result<()> {
+    for dir in MODULES {
+        println!("Run scope builder spec {:#?}", dir);
+        let (expected, result) = analyze(dir)?;
+        //println!("{}", result);
+        assert_eq!(expected, result);
+    }
+    Ok(())
+}
+
+#[test]
+fn scope_builder_dynamic_require() -> Result<()> {
+    let code = r#"
+        const fs = require('fs');
+        const name = 'path';
+        require(name);
+        const lib = require('./lib/' + name);
+        const computed = require(`${name}`);"#;
+    let (_, _, module) = load_code(code, None, None)?;
+    let analysis = analyze_module(&module, &Default::default());
+    assert_eq!(3, analysis.dynamic_requires.len());
+    Ok(())
+}
+
+fn dangerous(code: &str) -> Result<Vec<String>> {
+    let (_, _, module) = load_code(code, None, None)?;
+    let analysis = analyze_module(&module, &Default::default());
+    Ok(analysis
+        .dangerous
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+#[test]
+fn scope_builder_dangerous_eval() -> Result<()> {
+    assert_eq!(vec!["eval"], dangerous("eval('1 + 1');")?);
+    assert_eq!(vec!["eval"], dangerous("(0, eval)('1 + 1');")?);
+    assert_eq!(vec!["eval"], dangerous("window.eval('1 + 1');")?);
+    assert_eq!(vec!["eval"], dangerous("globalThis['eval'](code);")?);
+    assert!(dangerous("evaluate('1 + 1'); obj.eval(code);")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn scope_builder_dangerous_function() -> Result<()> {
+    assert_eq!(vec!["Function"], dangerous("new Function('return 1');")?);
+    assert_eq!(vec!["Function"], dangerous("Function('return 1')();")?);
+    assert_eq!(
+        vec!["Function"],
+        dangerous("const f = () => new Function(body);")?
+    );
+    Ok(())
+}
+
+#[test]
+fn scope_builder_dangerous_timers() -> Result<()> {
+    assert_eq!(vec!["setTimeout"], dangerous("setTimeout('tick()', 10);")?);
+    assert_eq!(
+        vec!["setInterval"],
+        dangerous("setInterval(`tick()`, 10);")?
+    );
+    assert!(dangerous("setTimeout(() => tick(), 10);")?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use swc_common::SourceMap;
+
+use basalt::static_module_record;
+
+use testing::read_to_string;
+
+#[test]
+fn shebang_preserved() -> Result<()> {
+    let expected = read_to_string("tests/transform/shebang/output.js")?;
+    let source_map: Arc<SourceMap> = Arc::new(Default::default());
+    let (_, result) = static_module_record::transform(
+        "tests/transform/shebang/input.js".into(),
+        source_map,
+    )?;
+    //println!("{}", &result.code);
+    assert_eq!(expected, result.code);
+    assert!(result.code.starts_with("#!/usr/bin/env node"));
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+
+use swc_common::{FileName, Spanned};
+
+use basalt::swc_utils::{load_code, span_to_location};
+
+#[test]
+fn span_to_location_line_column() -> Result<()> {
+    let (_, source_map, module) = load_code(
+        "const a = 1;\n  console.log(a);\n",
+        Some(FileName::Custom("span.js".into())),
+        None,
+    )?;
+
+    let loc = span_to_location(&source_map, module.body[0].span());
+    assert_eq!(1, loc.line);
+    assert_eq!(0, loc.col_display);
+
+    let loc = span_to_location(&source_map, module.body[1].span());
+    assert_eq!(2, loc.line);
+    assert_eq!(2, loc.col_display);
+    assert!(loc.file.name.to_string().contains("span.js"));
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use basalt::{
+    module::parser::parse,
+    policy::builder::PolicyBuilder,
+    stats::{Phase, Timings},
+};
+
+#[test]
+fn stats_timings_add() -> Result<()> {
+    let timings = Timings::new();
+    timings.add(Phase::Parse, Duration::from_millis(2));
+    timings.add(Phase::Parse, Duration::from_millis(3));
+    let value = timings.time(Phase::Serialize, || 42);
+    assert_eq!(42, value);
+    assert_eq!(2, timings.count(Phase::Parse));
+    assert_eq!(Duration::from_millis(5), timings.get(Phase::Parse));
+    assert_eq!(1, timings.count(Phase::Serialize));
+    assert_eq!(0, timings.count(Phase::Resolve));
+    assert_eq!(Duration::from_millis(0), timings.get(Phase::Resolve));
+
+    let table = timings.to_string();
+    for phase in &["phase", "resolve", "parse", "analyze", "serialize"] {
+        assert!(table.contains(phase));
+    }
+    assert!(table.lines().last().unwrap().starts_with("elapsed"));
+    Ok(())
+}
+
+#[test]
+fn stats_policy_builder() -> Result<()> {
+    let timings = Arc::new(Timings::new());
+    let dir = PathBuf::from("tests/policy/builtin/esm");
+    let builder = PolicyBuilder::from_source(
+        String::from("import 'builtin-esm';"),
+        dir.join("input.js").to_string_lossy().into_owned(),
+    )
+    .timings(Arc::clone(&timings));
+    builder.load()?.analyze()?.finalize();
+    assert!(timings.count(Phase::Parse) > 0);
+    assert!(timings.count(Phase::Resolve) > 0);
+    assert!(timings.count(Phase::Analyze) > 0);
+    assert_eq!(0, timings.count(Phase::Serialize));
+    Ok(())
+}
+
+#[test]
+fn stats_module_parser() -> Result<()> {
+    let timings = Timings::new();
+    // Modules are cached for the process so use a fixture
+    // that is only parsed by this test
+    parse("tests/fixtures/stats/main.js", None, false, Some(&timings))?;
+    assert!(timings.count(Phase::Parse) > 0);
+    assert!(timings.count(Phase::Resolve) > 0);
+    Ok(())
+}
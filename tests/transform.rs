@@ -0,0 +1,160 @@
+//! Compare the transform output for each `.js` fixture in the
+//! snapshots directory with the committed `.expected` functor program.
+//!
+//! Set `UPDATE_SNAPSHOTS` to regenerate the expected output.
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use swc_common::SourceMap;
+
+use basalt::static_module_record::{self, TransformSource};
+
+use testing::read_to_string;
+
+const SNAPSHOTS: &str = "tests/transform/snapshots";
+const UPDATE_SNAPSHOTS: &str = "UPDATE_SNAPSHOTS";
+
+fn fixtures() -> Result<Vec<PathBuf>> {
+    let mut fixtures = Vec::new();
+    for entry in std::fs::read_dir(SNAPSHOTS)? {
+        let path = entry?.path();
+        if path.extension().map(|ext| ext == "js").unwrap_or(false) {
+            fixtures.push(path);
+        }
+    }
+    fixtures.sort();
+    Ok(fixtures)
+}
+
+#[test]
+fn transform_snapshots() -> Result<()> {
+    let update = std::env::var_os(UPDATE_SNAPSHOTS).is_some();
+    let mut failures = Vec::new();
+
+    for fixture in fixtures()? {
+        let source_map: Arc<SourceMap> = Arc::new(Default::default());
+        let (_, result) = static_module_record::transform(
+            TransformSource::File(fixture.clone()),
+            source_map,
+        )?;
+
+        let snapshot = fixture.with_extension("expected");
+        if update {
+            std::fs::write(&snapshot, &result.code)?;
+            continue;
+        }
+
+        let expected = read_to_string(&snapshot)?;
+        if expected != result.code {
+            println!("Snapshot mismatch {}", snapshot.display());
+            println!("{}", result.code);
+            failures.push(fixture);
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "snapshots do not match, set {} to regenerate: {:#?}",
+        UPDATE_SNAPSHOTS,
+        failures
+    );
+    Ok(())
+}
+
+#[test]
+fn transform_commonjs_output() -> Result<()> {
+    let options = static_module_record::TransformOptions {
+        output_kind: static_module_record::OutputKind::CommonJs,
+        ..Default::default()
+    };
+    let output = static_module_record::transform_program_with_options(
+        "tests/transform/commonjs/input.js".into(),
+        options,
+    )?;
+    assert!(output
+        .program
+        .starts_with("(function(require, module, exports)"));
+
+    // Run the program with node and inspect the exports
+    let script = format!(
+        r#"const init = {}
+const module = {{ exports: {{}} }};
+init(require, module, module.exports);
+const {{ exports }} = module;
+console.log(JSON.stringify({{
+    esModule: exports.__esModule,
+    keys: Object.keys(exports).sort(),
+    joined: exports.joined,
+    greeting: exports.greet("basalt"),
+    default: exports.default,
+}}));
+"#,
+        output.program
+    );
+    let mut child = Command::new("node")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(script.as_bytes())?;
+    let result = child.wait_with_output()?;
+    assert!(result.status.success());
+
+    let value: Value = serde_json::from_slice(&result.stdout)?;
+    assert_eq!(
+        json!({
+            "esModule": true,
+            "keys": ["default", "greet", "joined"],
+            "joined": "a/b",
+            "greeting": "hello basalt",
+            "default": "main",
+        }),
+        value
+    );
+    Ok(())
+}
+
+#[test]
+fn transform_top_level_await() -> Result<()> {
+    let source = TransformSource::Str {
+        content: String::from(
+            "const config = await fetch('/config.json');\nexport default config;",
+        ),
+        file_name: String::from("tla.js"),
+    };
+    let output = static_module_record::transform_program(source)?;
+    assert!(output.program.starts_with("(async ("));
+
+    // Await inside a function is not top-level await
+    let source = TransformSource::Str {
+        content: String::from(
+            "export async function load() {\n  return await fetch('/');\n}",
+        ),
+        file_name: String::from("async-fn.js"),
+    };
+    let output = static_module_record::transform_program(source)?;
+    assert!(output.program.starts_with("(({"));
+    Ok(())
+}
+
+#[test]
+fn transform_top_level_await_commonjs() -> Result<()> {
+    let source = TransformSource::Str {
+        content: String::from(
+            "const config = await fetch('/config.json');\nexport default config;",
+        ),
+        file_name: String::from("tla.js"),
+    };
+    let options = static_module_record::TransformOptions {
+        output_kind: static_module_record::OutputKind::CommonJs,
+        ..Default::default()
+    };
+    let err =
+        static_module_record::transform_program_with_options(source, options)
+            .unwrap_err();
+    assert!(format!("{:#}", err).contains("top-level await"));
+    Ok(())
+}
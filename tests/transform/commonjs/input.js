@@ -0,0 +1,6 @@
+import { posix } from 'path';
+export const joined = posix.join('a', 'b');
+export function greet(name) {
+  return `hello ${name}`;
+}
+export default 'main';
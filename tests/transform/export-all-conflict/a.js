@@ -0,0 +1,2 @@
+export const x = 'a';
+export const y = 'a';
@@ -0,0 +1,2 @@
+export const x = 'b';
+export const z = 'b';
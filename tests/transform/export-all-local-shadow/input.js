@@ -0,0 +1,2 @@
+export * from './star.js';
+export const x = 'local';
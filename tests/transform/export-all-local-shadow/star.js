@@ -0,0 +1,3 @@
+export const x = 'star';
+export const y = 'star';
+export default 'star';
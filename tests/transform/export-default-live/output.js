@@ -0,0 +1,6 @@
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    $h‍_imports(new Map([]), []);
+    let $c‍_count = 0;
+    $h‍_live.count($c‍_count);
+    count += 1;
+});
@@ -0,0 +1,4 @@
+const a = 1;
+let b = 2;
+var c = 3;
+export { a, b as bee, c };
@@ -0,0 +1,9 @@
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    $h‍_imports(new Map([]), []);
+    const a = 1;
+    let b = 2;
+    var c = 3;
+    $h‍_once.a(a);
+    $h‍_once.bee(b);
+    $h‍_once.c(c);
+});
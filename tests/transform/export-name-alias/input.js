@@ -0,0 +1,2 @@
+export const internalName = 1;
+export { internalName as publicName };
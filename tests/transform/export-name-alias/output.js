@@ -0,0 +1,6 @@
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    $h‍_imports(new Map([]), []);
+    const internalName = 1;
+    $h‍_once.internalName(internalName);
+    $h‍_once.publicName(internalName);
+});
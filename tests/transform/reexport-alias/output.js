@@ -1,5 +1,4 @@
 (({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
-    let grey;
     $h‍_imports(new Map([
         [
             "./import-and-reexport-name-as-from-me.js",
@@ -0,0 +1 @@
+export { a as b } from './a.js';
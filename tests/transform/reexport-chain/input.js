@@ -0,0 +1 @@
+export { b as c } from './b.js';
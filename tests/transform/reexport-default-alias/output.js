@@ -1,5 +1,4 @@
 (({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
-    let meaning;
     $h‍_imports(new Map([
         [
             "./meaning.js",
@@ -0,0 +1,15 @@
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    $h‍_imports(new Map([
+        [
+            "./foo.js",
+            new Map([
+                [
+                    "default",
+                    [
+                        $h‍_live["default"]
+                    ]
+                ]
+            ])
+        ]
+    ]), []);
+});
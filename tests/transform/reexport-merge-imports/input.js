@@ -0,0 +1,2 @@
+import { x } from './m.js';
+export { y as z } from './m.js';
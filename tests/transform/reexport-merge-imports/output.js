@@ -0,0 +1,22 @@
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    let x;
+    $h‍_imports(new Map([
+        [
+            "./m.js",
+            new Map([
+                [
+                    "x",
+                    [
+                        ($h‍_a)=>(x = $h‍_a)
+                    ]
+                ],
+                [
+                    "y",
+                    [
+                        $h‍_live["y"]
+                    ]
+                ]
+            ])
+        ]
+    ]), []);
+});
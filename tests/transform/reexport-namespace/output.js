@@ -0,0 +1,17 @@
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    let ns;
+    $h‍_imports(new Map([
+        [
+            "./foo.js",
+            new Map([
+                [
+                    "*",
+                    [
+                        ($h‍_a)=>(ns = $h‍_a)
+                    ]
+                ]
+            ])
+        ]
+    ]), []);
+    $h‍_once.ns(ns);
+});
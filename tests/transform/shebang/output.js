@@ -0,0 +1,9 @@
+#!/usr/bin/env node
+(({ imports: $h‍_imports , liveVar: $h‍_live , onceVar: $h‍_once  })=>{
+    $h‍_imports(new Map([
+        [
+            "module",
+            new Map([])
+        ]
+    ]), []);
+});